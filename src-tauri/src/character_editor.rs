@@ -1,5 +1,5 @@
 use crate::pz_compat::validate_server_name;
-use crate::utils::safe_relative_path;
+use crate::utils::{copy_directory, safe_relative_path};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use chrono::{DateTime, Utc};
 use rayon::prelude::*;
@@ -3657,26 +3657,6 @@ fn dedupe_strings(values: &mut Vec<String>) {
     values.truncate(60);
 }
 
-fn copy_directory(source: &Path, destination: &Path) -> Result<(), String> {
-    for entry in WalkDir::new(source).follow_links(false) {
-        let entry = entry.map_err(|error| error.to_string())?;
-        let relative = entry
-            .path()
-            .strip_prefix(source)
-            .map_err(|error| error.to_string())?;
-        let target = destination.join(relative);
-        if entry.file_type().is_dir() {
-            fs::create_dir_all(&target).map_err(|error| error.to_string())?;
-        } else if entry.file_type().is_file() {
-            if let Some(parent) = target.parent() {
-                fs::create_dir_all(parent).map_err(|error| error.to_string())?;
-            }
-            fs::copy(entry.path(), &target).map_err(|error| error.to_string())?;
-        }
-    }
-    Ok(())
-}
-
 fn path_to_relative_string(path: &Path) -> String {
     path.to_string_lossy().replace('\\', "/")
 }
@@ -0,0 +1,257 @@
+use crate::loadout::LoadoutMod;
+use crate::mod_scanner::mod_root_dir;
+use crate::timing::scoped_timer;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentUsage {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+impl AddAssign for ContentUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.bytes += other.bytes;
+        self.files += other.files;
+    }
+}
+
+/// Bytes and file counts of a mod folder, split by what the files are for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModContentProfile {
+    pub total: ContentUsage,
+    pub lua: ContentUsage,
+    pub scripts: ContentUsage,
+    /// Texture packs and loose images.
+    pub textures: ContentUsage,
+    /// FMOD banks and loose audio.
+    pub sounds: ContentUsage,
+    /// Everything under `media/maps`, lotpacks included.
+    pub maps: ContentUsage,
+    pub models: ContentUsage,
+    pub tiledefs: ContentUsage,
+    pub other: ContentUsage,
+}
+
+impl AddAssign<&ModContentProfile> for ModContentProfile {
+    fn add_assign(&mut self, other: &ModContentProfile) {
+        self.total += other.total;
+        self.lua += other.lua;
+        self.scripts += other.scripts;
+        self.textures += other.textures;
+        self.sounds += other.sounds;
+        self.maps += other.maps;
+        self.models += other.models;
+        self.tiledefs += other.tiledefs;
+        self.other += other.other;
+    }
+}
+
+impl ModContentProfile {
+    fn category_mut(&mut self, relative_path: &str) -> &mut ContentUsage {
+        let path = relative_path.to_ascii_lowercase();
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+        if path.contains("media/maps/") {
+            return &mut self.maps;
+        }
+        match extension {
+            "lua" => &mut self.lua,
+            "txt" if path.contains("media/scripts/") => &mut self.scripts,
+            "tiles" => &mut self.tiledefs,
+            "pack" | "png" | "dds" | "jpg" | "jpeg" | "tga" => &mut self.textures,
+            "bank" | "ogg" | "wav" | "mp3" | "fsb" => &mut self.sounds,
+            "fbx" | "x" | "glb" | "gltf" | "obj" => &mut self.models,
+            _ if path.contains("media/models") || path.contains("media/anims") => &mut self.models,
+            _ => &mut self.other,
+        }
+    }
+}
+
+/// Walks every file under `dir`, version folders included, since the client
+/// downloads all of them.
+pub(crate) fn profile_dir(dir: &Path) -> ModContentProfile {
+    let mut profile = ModContentProfile::default();
+    for entry in WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+    {
+        let Ok(relative) = entry.path().strip_prefix(dir) else {
+            continue;
+        };
+        let relative_path = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let usage = ContentUsage {
+            bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            files: 1,
+        };
+        profile.total += usage;
+        *profile.category_mut(&relative_path) += usage;
+    }
+    profile
+}
+
+/// Profiles the whole folder of the mod owning `mod_info_path`.
+pub(crate) fn profile_mod(mod_info_path: &Path) -> ModContentProfile {
+    profile_dir(&mod_root_dir(mod_info_path))
+}
+
+/// `<workshop>/<id>/mods/<mod>` resolves to `<workshop>/<id>`, the unit Steam
+/// downloads.
+fn workshop_item_dir(mod_root: &Path) -> Option<PathBuf> {
+    let mods_dir = mod_root.parent()?;
+    if !mods_dir
+        .file_name()?
+        .to_string_lossy()
+        .eq_ignore_ascii_case("mods")
+    {
+        return None;
+    }
+    let item_dir = mods_dir.parent()?;
+    let name = item_dir.file_name()?.to_string_lossy();
+    (!name.is_empty() && name.chars().all(|c| c.is_ascii_digit())).then(|| item_dir.to_path_buf())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadoutModContent {
+    pub mod_id: Option<String>,
+    pub name: Option<String>,
+    pub workshop_id: Option<String>,
+    pub profile: ModContentProfile,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadoutContentProfile {
+    pub mods: Vec<LoadoutModContent>,
+    /// Sum over the loadout's mod folders.
+    pub total: ModContentProfile,
+    /// Sum over the whole workshop items a client has to download, which may
+    /// carry mods the loadout does not enable.
+    pub download: ModContentProfile,
+}
+
+pub(crate) fn profile_loadout(mods: &[LoadoutMod]) -> LoadoutContentProfile {
+    let mut seen_roots: BTreeSet<PathBuf> = BTreeSet::new();
+    let roots: Vec<(&LoadoutMod, PathBuf)> = mods
+        .iter()
+        .filter_map(|entry| {
+            let info_path = entry.mod_info_path.as_deref()?.trim();
+            if info_path.is_empty() {
+                return None;
+            }
+            let root = mod_root_dir(Path::new(info_path));
+            seen_roots.insert(root.clone()).then_some((entry, root))
+        })
+        .collect();
+    let item_dirs: BTreeSet<PathBuf> = roots
+        .iter()
+        .filter_map(|(_, root)| workshop_item_dir(root))
+        .collect();
+
+    let mod_profiles: Vec<LoadoutModContent> = roots
+        .par_iter()
+        .map(|(entry, root)| LoadoutModContent {
+            mod_id: entry.mod_id.clone(),
+            name: entry.name.clone(),
+            workshop_id: entry.workshop_id.clone(),
+            profile: profile_dir(root),
+        })
+        .collect();
+    let item_profiles: Vec<ModContentProfile> =
+        item_dirs.par_iter().map(|dir| profile_dir(dir)).collect();
+
+    let mut total = ModContentProfile::default();
+    for entry in &mod_profiles {
+        total += &entry.profile;
+    }
+    let mut download = ModContentProfile::default();
+    for profile in &item_profiles {
+        download += profile;
+    }
+    LoadoutContentProfile {
+        mods: mod_profiles,
+        total,
+        download,
+    }
+}
+
+#[tauri::command]
+pub fn profile_loadout_content(mods: Vec<LoadoutMod>) -> Result<LoadoutContentProfile, String> {
+    let _timer = scoped_timer("profile_loadout_content");
+    Ok(profile_loadout(&mods))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn profiles_mod_folders_by_category_and_rolls_up_workshop_items() {
+        let root = TestDir::new("pz-content-profile-test");
+        root.write("100/mods/A/mod.info", vec![b'x'; 10]);
+        root.write("100/mods/A/42/media/lua/client/A.lua", vec![b'x'; 100]);
+        root.write("100/mods/A/common/media/scripts/items.txt", vec![b'x'; 200]);
+        root.write("100/mods/A/42/media/texturepacks/A.pack", vec![b'x'; 1000]);
+        root.write("100/mods/A/42/media/sound/banks/A.bank", vec![b'x'; 2000]);
+        root.write(
+            "100/mods/A/42/media/maps/Town/0_0.lotheader",
+            vec![b'x'; 300],
+        );
+        root.write("100/mods/A/42/media/models_X/Axe.fbx", vec![b'x'; 400]);
+        root.write("100/mods/A/42/media/Atiles.tiles", vec![b'x'; 50]);
+        root.write("100/mods/B/mod.info", vec![b'x'; 10]);
+        root.write("100/mods/B/media/lua/shared/B.lua", vec![b'x'; 5]);
+        root.write("100/preview.png", vec![b'x'; 7]);
+
+        let profile = profile_mod(&root.join("100/mods/A/mod.info"));
+        assert_eq!(
+            profile.total,
+            ContentUsage {
+                bytes: 4060,
+                files: 8
+            }
+        );
+        assert_eq!(profile.lua.bytes, 100);
+        assert_eq!(profile.scripts.bytes, 200);
+        assert_eq!(profile.textures.bytes, 1000);
+        assert_eq!(profile.sounds.bytes, 2000);
+        assert_eq!(profile.maps.bytes, 300);
+        assert_eq!(profile.models.bytes, 400);
+        assert_eq!(profile.tiledefs.bytes, 50);
+        assert_eq!(
+            profile.other,
+            ContentUsage {
+                bytes: 10,
+                files: 1
+            }
+        );
+
+        let loadout_mod = |dir: &str| LoadoutMod {
+            mod_id: Some(dir.to_string()),
+            mod_info_path: Some(
+                root.join("100/mods")
+                    .join(dir)
+                    .join("mod.info")
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            ..LoadoutMod::default()
+        };
+        let loadout = profile_loadout(&[loadout_mod("A"), loadout_mod("B"), loadout_mod("A")]);
+        assert_eq!(loadout.mods.len(), 2);
+        assert_eq!(loadout.total.total.bytes, 4075);
+        assert_eq!(loadout.download.total.bytes, 4082);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::mod_scanner::mod_content_dirs;
+use rayon::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A mod whose `media` tree takes part in conflict detection.
+#[derive(Debug, Clone)]
+pub(crate) struct MediaSource {
+    pub mod_id: String,
+    pub name: Option<String>,
+    pub mod_info_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictingFile {
+    pub mod_id: String,
+    pub name: Option<String>,
+    pub mod_info_path: Option<String>,
+    pub file_path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileConflict {
+    pub relative_path: String,
+    /// Every mod shipping the path, in load order; the last entry wins.
+    pub mods: Vec<ConflictingFile>,
+    pub winner_mod_id: String,
+    pub identical: bool,
+}
+
+struct MediaFile {
+    relative_path: String,
+    path: PathBuf,
+    size: u64,
+}
+
+fn media_files(mod_dir: &Path) -> Vec<MediaFile> {
+    let media_dir = mod_dir.join("media");
+    if !media_dir.is_dir() {
+        return Vec::new();
+    }
+    WalkDir::new(&media_dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(mod_dir).ok()?;
+            let relative_path = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            Some(MediaFile {
+                relative_path,
+                path: entry.path().to_path_buf(),
+                size,
+            })
+        })
+        .collect()
+}
+
+fn content_digest(path: &Path) -> Option<Vec<u8>> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).ok()?;
+    Some(hasher.finalize().to_vec())
+}
+
+fn all_identical(files: &[ConflictingFile]) -> bool {
+    let Some(first) = files.first() else {
+        return true;
+    };
+    if files.iter().any(|file| file.size != first.size) {
+        return false;
+    }
+    let Some(expected) = content_digest(Path::new(&first.file_path)) else {
+        return false;
+    };
+    files[1..]
+        .iter()
+        .all(|file| content_digest(Path::new(&file.file_path)).as_ref() == Some(&expected))
+}
+
+/// Finds `media/` paths shipped by more than one source.  `sources` must be in
+/// resolved load order; the game keeps the file from the last mod to load it.
+/// Paths are compared case-insensitively because the game runs on Windows too.
+pub(crate) fn find_file_conflicts(sources: &[MediaSource]) -> Vec<FileConflict> {
+    let listings: Vec<Vec<MediaFile>> = sources
+        .par_iter()
+        .map(|source| {
+            mod_content_dirs(Path::new(&source.mod_info_path))
+                .iter()
+                .flat_map(|dir| media_files(dir))
+                .collect()
+        })
+        .collect();
+
+    let mut by_path: HashMap<String, Vec<(usize, &MediaFile)>> = HashMap::new();
+    for (source_index, files) in listings.iter().enumerate() {
+        for file in files {
+            let entries = by_path
+                .entry(file.relative_path.to_lowercase())
+                .or_default();
+            // A version folder replaces the same file from the mod's own `common/`.
+            match entries.iter_mut().find(|(index, _)| *index == source_index) {
+                Some(existing) => existing.1 = file,
+                None => entries.push((source_index, file)),
+            }
+        }
+    }
+
+    let mut overlaps: Vec<(String, Vec<ConflictingFile>)> = by_path
+        .into_iter()
+        .filter(|(_, entries)| entries.len() > 1)
+        .map(|(_, entries)| {
+            let relative_path = entries[0].1.relative_path.clone();
+            let mods = entries
+                .into_iter()
+                .map(|(index, file)| ConflictingFile {
+                    mod_id: sources[index].mod_id.clone(),
+                    name: sources[index].name.clone(),
+                    mod_info_path: Some(sources[index].mod_info_path.clone()),
+                    file_path: file.path.to_string_lossy().to_string(),
+                    size: file.size,
+                })
+                .collect();
+            (relative_path, mods)
+        })
+        .collect();
+    overlaps.sort_by_cached_key(|(relative_path, _)| relative_path.to_lowercase());
+
+    overlaps
+        .into_par_iter()
+        .map(|(relative_path, mods)| {
+            let identical = all_identical(&mods);
+            let winner_mod_id = mods
+                .last()
+                .map(|file| file.mod_id.clone())
+                .unwrap_or_default();
+            FileConflict {
+                relative_path,
+                mods,
+                winner_mod_id,
+                identical,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn reports_overlapping_media_paths_with_the_last_loaded_winner() {
+        let root = TestDir::new("pz-file-conflicts-test");
+        root.write("first/mod.info", "id=First");
+        root.write("first/media/lua/shared/Shared.lua", "return 1");
+        root.write("first/media/scripts/items.txt", "module Base {}");
+        root.write("first/media/lua/client/OnlyFirst.lua", "");
+        root.write("second/mod.info", "id=Second");
+        root.write("second/media/lua/shared/shared.lua", "return 1");
+        root.write(
+            "second/media/scripts/items.txt",
+            "module Base { item Axe {} }",
+        );
+
+        let sources = ["first", "second"]
+            .iter()
+            .map(|dir| MediaSource {
+                mod_id: dir.to_string(),
+                name: None,
+                mod_info_path: root
+                    .join(dir)
+                    .join("mod.info")
+                    .to_string_lossy()
+                    .to_string(),
+            })
+            .collect::<Vec<_>>();
+        let conflicts = find_file_conflicts(&sources);
+
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].relative_path, "media/lua/shared/Shared.lua");
+        assert!(conflicts[0].identical);
+        assert_eq!(conflicts[1].relative_path, "media/scripts/items.txt");
+        assert!(!conflicts[1].identical);
+        assert_eq!(conflicts[1].winner_mod_id, "second");
+        assert_eq!(conflicts[1].mods[0].mod_id, "first");
+    }
+}
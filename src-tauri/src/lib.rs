@@ -1,20 +1,41 @@
 mod character_editor;
+mod content_profile;
 mod file_commands;
+mod file_conflicts;
+mod loadout;
 mod lua_export;
+mod map_mods;
 mod map_status;
 mod map_view;
 mod media;
+mod mod_info_lint;
 mod mod_scanner;
 mod models;
 mod modlist;
+mod mods_txt;
 mod presets;
 mod pz_compat;
+mod pz_version;
 mod pzmap2dzi;
 mod pzmap2dzi_renderer;
+mod rcon;
+mod sandbox_options;
+mod sandbox_vars;
+mod scan_cache;
+mod script_overrides;
+mod server_accounts;
+mod server_bundle;
+mod server_diff;
 mod server_files;
+mod server_ini;
+mod server_mods;
+mod server_process;
+mod spawn_files;
+mod steam_workshop;
 mod store;
 mod timing;
 mod utils;
+mod workshop_watcher;
 
 pub use models::*;
 
@@ -32,11 +53,20 @@ pub fn run() {
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_sql::Builder::default().build())
         .manage(pzmap2dzi::BuildManager::default())
+        .manage(workshop_watcher::WorkshopWatcher::default())
+        .manage(server_process::ServerProcessManager::default())
         .invoke_handler(tauri::generate_handler![
             store::get_bootstrap_store_items,
             mod_scanner::validate_pz_workshop_path,
             mod_scanner::scan_mod_folder,
+            mod_info_lint::lint_mod_info,
+            workshop_watcher::start_workshop_watch,
+            workshop_watcher::stop_workshop_watch,
+            workshop_watcher::get_workshop_watch_status,
+            steam_workshop::read_workshop_manifest,
             media::list_media_script_files,
+            script_overrides::analyze_script_overrides,
+            map_mods::inspect_map_mods,
             file_commands::backup_file,
             file_commands::read_text_file,
             file_commands::write_text_file,
@@ -45,12 +75,49 @@ pub fn run() {
             file_commands::get_default_zomboid_user_dir,
             server_files::list_server_names,
             server_files::delete_server_files,
+            server_files::clone_server,
+            server_files::rename_server,
+            server_diff::compare_servers,
+            server_diff::merge_server_settings,
+            server_process::start_dedicated_server,
+            server_process::get_dedicated_server_status,
+            server_process::stop_dedicated_server,
+            server_process::kill_dedicated_server,
+            rcon::rcon_execute,
+            rcon::rcon_players,
+            rcon::rcon_kick,
+            rcon::rcon_ban,
+            rcon::rcon_server_message,
+            rcon::rcon_save,
+            rcon::rcon_quit,
+            server_accounts::list_server_accounts,
+            server_accounts::add_server_account,
+            server_accounts::update_server_account,
+            server_accounts::remove_server_account,
+            server_accounts::add_server_ban,
+            server_accounts::remove_server_ban,
+            server_bundle::export_server_bundle,
+            server_bundle::inspect_server_bundle,
+            server_bundle::import_server_bundle,
+            server_mods::validate_server_mods,
+            sandbox_vars::read_sandbox_vars,
+            sandbox_vars::write_sandbox_vars,
+            sandbox_vars::diff_sandbox_vars_with_defaults,
+            sandbox_options::get_loadout_sandbox_schema,
+            sandbox_options::validate_mod_sandbox_options,
+            spawn_files::read_spawn_files,
+            spawn_files::write_spawn_regions,
+            spawn_files::write_spawn_points,
+            spawn_files::plan_spawn_regions,
+            spawn_files::validate_spawn_regions,
             presets::list_save_mods_files,
             presets::analyze_mod_loadout,
+            content_profile::profile_loadout_content,
             presets::plan_server_preset,
             presets::write_server_preset,
             presets::plan_singleplayer_save_mods,
             presets::write_singleplayer_save_mods,
+            mods_txt::read_save_mods,
             media::has_ogg_files,
             pz_compat::get_pz_compatibility_info,
             character_editor::list_character_save_slots,
@@ -83,6 +150,12 @@ pub fn run() {
             modlist::remove_mod_from_pz_modlist_settings,
             modlist::upsert_pz_modlist_settings_preset,
             modlist::remove_pz_modlist_settings_preset,
+            modlist::read_pz_modlist_settings,
+            modlist::rename_pz_modlist_settings_preset,
+            modlist::duplicate_pz_modlist_settings_preset,
+            modlist::reorder_pz_modlist_settings_presets,
+            modlist::import_pz_modlist_preset_from_server,
+            modlist::import_pz_modlist_preset_from_save,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,606 @@
+use crate::file_conflicts::{FileConflict, MediaSource, find_file_conflicts};
+use crate::mod_scanner::{normalize_mod_ref, read_mod_summary};
+use crate::models::ModSummary;
+use crate::pz_compat::GAME_VERSION;
+use crate::pz_version::{VersionCompatibility, compatibility_verdict};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
+use std::path::Path;
+
+/// One active mod as sent by the loadout page (`LoadoutResolvedMod`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LoadoutMod {
+    pub mod_id: Option<String>,
+    pub name: Option<String>,
+    pub workshop_id: Option<String>,
+    pub mod_info_path: Option<String>,
+    pub requires: Option<Vec<String>>,
+    pub dependencies: Option<Vec<String>>,
+    pub load_after: Option<Vec<String>>,
+    pub load_before: Option<Vec<String>>,
+    pub incompatible: Option<Vec<String>>,
+    pub compatibility: Option<VersionCompatibility>,
+    /// The `worldmap=` map name declared in `mod.info`.
+    pub worldmap: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingDependency {
+    pub mod_id: String,
+    pub required_by: Vec<String>,
+    pub workshop_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct IncompatiblePair {
+    pub a: String,
+    pub b: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadoutAnalysis {
+    pub ordered_mod_ids: Vec<String>,
+    pub missing_mod_ids: Vec<String>,
+    /// Active mods that carry no workshop id, so the server cannot download them.
+    pub missing_workshop_ids: Vec<String>,
+    pub missing_dependencies: Vec<MissingDependency>,
+    pub cycles: Vec<Vec<String>>,
+    pub incompatible_pairs: Vec<IncompatiblePair>,
+    pub conflicts: Vec<FileConflict>,
+    pub warnings: Vec<String>,
+}
+
+/// A loadout entry with its relations normalized to lowercase mod id keys.
+struct ModNode {
+    mod_id: String,
+    name: Option<String>,
+    mod_info_path: Option<String>,
+    workshop_id: Option<String>,
+    hard: Vec<String>,
+    after: Vec<String>,
+    before: Vec<String>,
+    incompatible: Vec<String>,
+    compatibility: Option<VersionCompatibility>,
+}
+
+fn normalized_refs(lists: &[&Option<Vec<String>>]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for list in lists {
+        for raw in list.iter().flatten() {
+            let value = normalize_mod_ref(raw);
+            if value.is_empty() || out.iter().any(|v| v.eq_ignore_ascii_case(&value)) {
+                continue;
+            }
+            out.push(value);
+        }
+    }
+    out
+}
+
+/// Fills relation lists and the version verdict the frontend left out from
+/// the mod's own `mod.info`.
+fn with_mod_info_details(entry: &LoadoutMod) -> LoadoutMod {
+    let mut entry = entry.clone();
+    let has_relations = entry.requires.is_some()
+        || entry.dependencies.is_some()
+        || entry.load_after.is_some()
+        || entry.load_before.is_some()
+        || entry.incompatible.is_some();
+    if has_relations && entry.compatibility.is_some() {
+        return entry;
+    }
+    let Some(info_path) = entry
+        .mod_info_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    else {
+        return entry;
+    };
+    if let Ok(parsed) = read_mod_summary(Path::new(info_path)) {
+        if !has_relations {
+            entry.requires = parsed.requires;
+            entry.dependencies = parsed.dependencies;
+            entry.load_after = parsed.load_after;
+            entry.load_before = parsed.load_before;
+            entry.incompatible = parsed.incompatible;
+        }
+        if entry.compatibility.is_none() {
+            entry.compatibility = parsed.compatibility;
+        }
+        if entry.workshop_id.is_none() {
+            entry.workshop_id = parsed.workshop_id;
+        }
+    }
+    entry
+}
+
+/// Maps lowercase mod ids to the workshop items that ship them.
+pub(crate) fn workshop_providers(installed: &[ModSummary]) -> HashMap<String, BTreeSet<String>> {
+    let mut providers: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for summary in installed {
+        let Some(mod_id) = summary
+            .mod_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        else {
+            continue;
+        };
+        let workshop_id = summary
+            .workshop_id
+            .clone()
+            .filter(|v| !v.trim().is_empty())
+            .or_else(|| {
+                summary
+                    .workshop
+                    .as_ref()
+                    .and_then(|w| w.get("fileid"))
+                    .map(|v| match v {
+                        JsonValue::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+            });
+        if let Some(workshop_id) = workshop_id {
+            providers
+                .entry(mod_id.to_lowercase())
+                .or_default()
+                .insert(workshop_id.trim().to_string());
+        }
+    }
+    providers
+}
+
+/// Tarjan's strongly connected components; returns every component that
+/// contains more than one node, in discovery order.
+fn find_cycles(successors: &[BTreeSet<usize>]) -> Vec<Vec<usize>> {
+    struct State<'a> {
+        successors: &'a [BTreeSet<usize>],
+        next_index: usize,
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        out: Vec<Vec<usize>>,
+    }
+
+    fn visit(state: &mut State<'_>, node: usize) {
+        state.index[node] = Some(state.next_index);
+        state.low[node] = state.next_index;
+        state.next_index += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+
+        for &next in state.successors[node].iter() {
+            match state.index[next] {
+                None => {
+                    visit(state, next);
+                    state.low[node] = state.low[node].min(state.low[next]);
+                }
+                Some(next_index) if state.on_stack[next] => {
+                    state.low[node] = state.low[node].min(next_index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(state.low[node]) == state.index[node] {
+            let mut component = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            if component.len() > 1 {
+                component.sort_unstable();
+                state.out.push(component);
+            }
+        }
+    }
+
+    let count = successors.len();
+    let mut state = State {
+        successors,
+        next_index: 0,
+        index: vec![None; count],
+        low: vec![0; count],
+        on_stack: vec![false; count],
+        stack: Vec::new(),
+        out: Vec::new(),
+    };
+    for node in 0..count {
+        if state.index[node].is_none() {
+            visit(&mut state, node);
+        }
+    }
+    state.out.sort_by_key(|component| component[0]);
+    state.out
+}
+
+/// Kahn's algorithm keyed on loadout position, so mods without constraints
+/// keep the order the user gave them.  When only cycles remain, the earliest
+/// remaining mod is placed next and the sort continues.
+fn stable_topological_order(successors: &[BTreeSet<usize>]) -> Vec<usize> {
+    let count = successors.len();
+    let mut indegree = vec![0usize; count];
+    for targets in successors {
+        for &target in targets {
+            indegree[target] += 1;
+        }
+    }
+    let mut placed = vec![false; count];
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..count)
+        .filter(|&node| indegree[node] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(count);
+
+    while order.len() < count {
+        let node = match ready.pop() {
+            Some(Reverse(node)) if placed[node] => continue,
+            Some(Reverse(node)) => node,
+            None => match (0..count).find(|&node| !placed[node]) {
+                Some(node) => node,
+                None => break,
+            },
+        };
+        placed[node] = true;
+        order.push(node);
+        for &next in &successors[node] {
+            indegree[next] = indegree[next].saturating_sub(1);
+            if indegree[next] == 0 && !placed[next] {
+                ready.push(Reverse(next));
+            }
+        }
+    }
+    order
+}
+
+/// Active mods with the ordering edges between them.
+struct LoadoutGraph {
+    nodes: Vec<ModNode>,
+    index_by_id: HashMap<String, usize>,
+    successors: Vec<BTreeSet<usize>>,
+    /// Keyed by lowercase id; holds the first spelling seen and the requiring mods.
+    missing: BTreeMap<String, (String, Vec<String>)>,
+    warnings: Vec<String>,
+}
+
+impl LoadoutGraph {
+    fn media_sources(&self, order: &[usize]) -> Vec<MediaSource> {
+        order
+            .iter()
+            .filter_map(|&node| {
+                let node = &self.nodes[node];
+                Some(MediaSource {
+                    mod_id: node.mod_id.clone(),
+                    name: node.name.clone(),
+                    mod_info_path: node.mod_info_path.clone()?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn build_graph(mods: &[LoadoutMod]) -> LoadoutGraph {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut nodes: Vec<ModNode> = Vec::new();
+    let mut index_by_id: HashMap<String, usize> = HashMap::new();
+
+    for raw in mods {
+        let Some(mod_id) = raw
+            .mod_id
+            .as_deref()
+            .map(normalize_mod_ref)
+            .filter(|id| !id.is_empty())
+        else {
+            continue;
+        };
+        let key = mod_id.to_lowercase();
+        if index_by_id.contains_key(&key) {
+            warnings.push(format!(
+                "{mod_id} is listed more than once; later entries are ignored."
+            ));
+            continue;
+        }
+        let entry = with_mod_info_details(raw);
+        index_by_id.insert(key, nodes.len());
+        nodes.push(ModNode {
+            name: entry.name.clone(),
+            mod_info_path: entry.mod_info_path.clone().filter(|p| !p.trim().is_empty()),
+            workshop_id: entry.workshop_id.filter(|v| !v.trim().is_empty()),
+            hard: normalized_refs(&[&entry.requires, &entry.dependencies]),
+            after: normalized_refs(&[&entry.load_after]),
+            before: normalized_refs(&[&entry.load_before]),
+            incompatible: normalized_refs(&[&entry.incompatible]),
+            compatibility: entry.compatibility,
+            mod_id,
+        });
+    }
+
+    let mut successors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); nodes.len()];
+    let mut missing: BTreeMap<String, (String, Vec<String>)> = BTreeMap::new();
+
+    for (node_index, node) in nodes.iter().enumerate() {
+        for dependency in &node.hard {
+            match index_by_id.get(&dependency.to_lowercase()) {
+                Some(&target) if target == node_index => {
+                    warnings.push(format!("{} lists itself as a requirement.", node.mod_id))
+                }
+                Some(&target) => {
+                    successors[target].insert(node_index);
+                }
+                None => {
+                    let entry = missing
+                        .entry(dependency.to_lowercase())
+                        .or_insert_with(|| (dependency.clone(), Vec::new()));
+                    entry.1.push(node.mod_id.clone());
+                }
+            }
+        }
+        for other in &node.after {
+            if let Some(&target) = index_by_id.get(&other.to_lowercase()) {
+                if target != node_index {
+                    successors[target].insert(node_index);
+                }
+            }
+        }
+        for other in &node.before {
+            if let Some(&target) = index_by_id.get(&other.to_lowercase()) {
+                if target != node_index {
+                    successors[node_index].insert(target);
+                }
+            }
+        }
+    }
+
+    LoadoutGraph {
+        nodes,
+        index_by_id,
+        successors,
+        missing,
+        warnings,
+    }
+}
+
+/// Active mods with a known `mod.info`, in the order the game loads them.
+pub(crate) fn ordered_media_sources(mods: &[LoadoutMod]) -> Vec<MediaSource> {
+    let graph = build_graph(mods);
+    let order = stable_topological_order(&graph.successors);
+    graph.media_sources(&order)
+}
+
+pub(crate) fn analyze_loadout(mods: &[LoadoutMod], installed: &[ModSummary]) -> LoadoutAnalysis {
+    let mut graph = build_graph(mods);
+    let mut analysis = LoadoutAnalysis {
+        warnings: std::mem::take(&mut graph.warnings),
+        ..LoadoutAnalysis::default()
+    };
+    let missing = std::mem::take(&mut graph.missing);
+    let (nodes, index_by_id, successors) = (&graph.nodes, &graph.index_by_id, &graph.successors);
+
+    let order = stable_topological_order(successors);
+    analysis.ordered_mod_ids = order
+        .iter()
+        .map(|&node| nodes[node].mod_id.clone())
+        .collect();
+
+    for component in find_cycles(successors) {
+        let members: Vec<String> = component
+            .iter()
+            .map(|&node| nodes[node].mod_id.clone())
+            .collect();
+        analysis.warnings.push(format!(
+            "Load order cycle between {}; these mods keep their loadout order.",
+            members.join(", ")
+        ));
+        analysis.cycles.push(members);
+    }
+
+    let installed_by_id: HashMap<String, &ModSummary> = installed
+        .iter()
+        .filter_map(|summary| Some((summary.mod_id.as_deref()?.trim().to_lowercase(), summary)))
+        .collect();
+    for &node_index in &order {
+        let node = &nodes[node_index];
+        let verdict = node.compatibility.or_else(|| {
+            installed_by_id
+                .get(&node.mod_id.to_lowercase())
+                .map(|summary| {
+                    summary
+                        .compatibility
+                        .unwrap_or_else(|| compatibility_verdict(summary))
+                })
+        });
+        match verdict {
+            Some(VersionCompatibility::TooOld) => analysis.warnings.push(format!(
+                "{} was made for an older build than {GAME_VERSION} and may not load.",
+                node.mod_id
+            )),
+            Some(VersionCompatibility::RequiresNewerBuild) => analysis.warnings.push(format!(
+                "{} requires a newer build than {GAME_VERSION}.",
+                node.mod_id
+            )),
+            _ => {}
+        }
+    }
+
+    let providers = workshop_providers(installed);
+    let mut provider_ids: BTreeSet<String> = BTreeSet::new();
+    for (key, (mod_id, required_by)) in missing {
+        let workshop_ids: Vec<String> = providers
+            .get(&key)
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default();
+        if workshop_ids.is_empty() {
+            analysis
+                .warnings
+                .push(format!("No installed workshop item provides {mod_id}."));
+        }
+        provider_ids.extend(workshop_ids.iter().cloned());
+        analysis.missing_mod_ids.push(mod_id.clone());
+        analysis.missing_dependencies.push(MissingDependency {
+            mod_id,
+            required_by,
+            workshop_ids,
+        });
+    }
+    if !provider_ids.is_empty() {
+        analysis.warnings.push(format!(
+            "Subscribe to or enable workshop items {} to satisfy missing requirements.",
+            provider_ids.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    let mut seen_pairs: BTreeSet<(usize, usize)> = BTreeSet::new();
+    for (node_index, node) in nodes.iter().enumerate() {
+        for other in &node.incompatible {
+            let Some(&target) = index_by_id.get(&other.to_lowercase()) else {
+                continue;
+            };
+            if target == node_index {
+                continue;
+            }
+            if seen_pairs.insert((node_index.min(target), node_index.max(target))) {
+                analysis.incompatible_pairs.push(IncompatiblePair {
+                    a: node.mod_id.clone(),
+                    b: nodes[target].mod_id.clone(),
+                });
+            }
+        }
+    }
+
+    analysis.conflicts = find_file_conflicts(&graph.media_sources(&order));
+    let overridden = analysis
+        .conflicts
+        .iter()
+        .filter(|conflict| !conflict.identical)
+        .count();
+    if overridden > 0 {
+        analysis.warnings.push(format!(
+            "{overridden} media file(s) are replaced by a later mod with different content."
+        ));
+    }
+
+    analysis.missing_workshop_ids = nodes
+        .iter()
+        .filter(|node| node.workshop_id.is_none())
+        .map(|node| node.mod_id.clone())
+        .collect();
+
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mod_id: &str) -> LoadoutMod {
+        LoadoutMod {
+            mod_id: Some(mod_id.to_string()),
+            workshop_id: Some("1".to_string()),
+            ..LoadoutMod::default()
+        }
+    }
+
+    fn list(values: &[&str]) -> Option<Vec<String>> {
+        Some(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    #[test]
+    fn orders_dependencies_first_and_keeps_unconstrained_mods_stable() {
+        let mut ui = entry("UI");
+        ui.requires = list(&["\\Core"]);
+        let mut patch = entry("Patch");
+        patch.load_after = list(&["UI"]);
+        patch.load_before = list(&["Late"]);
+        let mods = vec![entry("Late"), patch, ui, entry("Solo"), entry("Core")];
+
+        let analysis = analyze_loadout(&mods, &[]);
+        assert_eq!(
+            analysis.ordered_mod_ids,
+            vec!["Solo", "Core", "UI", "Patch", "Late"]
+        );
+        assert!(analysis.cycles.is_empty());
+        assert!(analysis.missing_mod_ids.is_empty());
+    }
+
+    #[test]
+    fn reports_missing_requirements_with_their_providers() {
+        let mut needs = entry("NeedsLib");
+        needs.requires = list(&["\\ModLib", "\\Ghost"]);
+        let provider = ModSummary {
+            mod_id: Some("modlib".to_string()),
+            workshop_id: Some("2169435993".to_string()),
+            ..ModSummary::default()
+        };
+
+        let analysis = analyze_loadout(&[needs], &[provider]);
+        assert_eq!(analysis.missing_mod_ids, vec!["Ghost", "ModLib"]);
+        assert_eq!(
+            analysis.missing_dependencies[1],
+            MissingDependency {
+                mod_id: "ModLib".to_string(),
+                required_by: vec!["NeedsLib".to_string()],
+                workshop_ids: vec!["2169435993".to_string()],
+            }
+        );
+        assert!(analysis.missing_dependencies[0].workshop_ids.is_empty());
+    }
+
+    #[test]
+    fn lists_cycle_members_and_still_orders_every_mod() {
+        let mut a = entry("A");
+        a.requires = list(&["C"]);
+        let mut b = entry("B");
+        b.requires = list(&["A"]);
+        let mut c = entry("C");
+        c.load_after = list(&["B"]);
+        let analysis = analyze_loadout(&[entry("Base"), a, b, c], &[]);
+
+        assert_eq!(analysis.cycles, vec![vec!["A", "B", "C"]]);
+        assert_eq!(analysis.ordered_mod_ids, vec!["Base", "A", "B", "C"]);
+    }
+
+    #[test]
+    fn warns_about_mods_built_for_another_game_version() {
+        let mut legacy = entry("Legacy");
+        legacy.compatibility = Some(VersionCompatibility::TooOld);
+        let installed = ModSummary {
+            mod_id: Some("Future".to_string()),
+            version_min: Some("99.0".to_string()),
+            ..ModSummary::default()
+        };
+        let analysis = analyze_loadout(&[legacy, entry("Future"), entry("Fine")], &[installed]);
+
+        assert_eq!(analysis.warnings.len(), 2);
+        assert!(analysis.warnings[0].starts_with("Legacy was made for an older build"));
+        assert!(analysis.warnings[1].starts_with("Future requires a newer build"));
+    }
+
+    #[test]
+    fn flags_each_active_incompatible_pair_once() {
+        let mut a = entry("A");
+        a.incompatible = list(&["B", "NotActive"]);
+        let mut b = entry("B");
+        b.incompatible = list(&["a"]);
+        let mut unlisted = entry("NoWorkshop");
+        unlisted.workshop_id = None;
+
+        let analysis = analyze_loadout(&[a, b, unlisted], &[]);
+        assert_eq!(
+            analysis.incompatible_pairs,
+            vec![IncompatiblePair {
+                a: "A".to_string(),
+                b: "B".to_string()
+            }]
+        );
+        assert_eq!(analysis.missing_workshop_ids, vec!["NoWorkshop"]);
+    }
+}
@@ -0,0 +1,466 @@
+use crate::loadout::LoadoutMod;
+use crate::mod_scanner::{decode_mod_info, mod_content_dirs, read_mod_summary};
+use crate::pz_compat::{VANILLA_MAPS, VANILLA_WORLD_MAP};
+use crate::pzmap2dzi_renderer::scan_headers;
+use crate::timing::scoped_timer;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CellBounds {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapFolderInfo {
+    /// `None` for the base game's world map.
+    pub mod_id: Option<String>,
+    pub map_name: String,
+    /// Every folder contributing to this map, lowest priority first.
+    pub paths: Vec<String>,
+    pub title: Option<String>,
+    /// Other maps this one loads its lots from, as listed in `map.info`.
+    pub lots: Vec<String>,
+    pub description: Option<String>,
+    pub cell_count: usize,
+    /// Squares per cell side: 300 for B41 lotheaders, 256 for B42.
+    pub cell_size: Option<i32>,
+    pub bounds: Option<CellBounds>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapCellOverlap {
+    pub first_mod_id: Option<String>,
+    pub first_map: String,
+    pub second_mod_id: Option<String>,
+    pub second_map: String,
+    /// Cells of the first map, in its own cell coordinates, that the second
+    /// map also covers.
+    pub cells: Vec<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapModReport {
+    pub maps: Vec<MapFolderInfo>,
+    pub overlaps: Vec<MapCellOverlap>,
+}
+
+#[derive(Debug, Default)]
+struct MapInfoFile {
+    title: Option<String>,
+    lots: Vec<String>,
+    description: Option<String>,
+}
+
+/// Reads `map.info`.  `lots` may repeat and is never split on commas because
+/// map names such as `Muldraugh, KY` contain them.
+fn read_map_info(path: &Path) -> MapInfoFile {
+    let mut info = MapInfoFile::default();
+    let Ok(raw) = fs::read(path) else {
+        return info;
+    };
+    let (content, _) = decode_mod_info(raw);
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim().to_ascii_lowercase().as_str() {
+            "title" => info.title = Some(value.to_string()),
+            "lots" => info.lots.push(value.to_string()),
+            "description" => info.description = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    info
+}
+
+pub(crate) struct MapSource {
+    pub mod_id: Option<String>,
+    pub map_name: String,
+    pub dirs: Vec<PathBuf>,
+}
+
+struct MapCells {
+    cell_size: i32,
+    cells: HashSet<(i32, i32)>,
+}
+
+fn inspect_map(source: &MapSource) -> (MapFolderInfo, Option<MapCells>) {
+    let mut info = MapInfoFile::default();
+    for dir in &source.dirs {
+        let map_info = dir.join("map.info");
+        if map_info.is_file() {
+            info = read_map_info(&map_info);
+        }
+    }
+
+    let mut cells: HashSet<(i32, i32)> = HashSet::new();
+    let mut cell_size: Option<i32> = None;
+    let mut error: Option<String> = None;
+    for dir in &source.dirs {
+        match scan_headers(dir, "utf-8") {
+            Ok(headers) => {
+                for header in headers.values() {
+                    cell_size.get_or_insert(header.cell_size);
+                    cells.insert((header.x, header.y));
+                }
+            }
+            Err(message) => error = Some(message),
+        }
+    }
+    let bounds = cells
+        .iter()
+        .fold(None, |bounds: Option<CellBounds>, &(x, y)| {
+            Some(match bounds {
+                Some(b) => CellBounds {
+                    min_x: b.min_x.min(x),
+                    min_y: b.min_y.min(y),
+                    max_x: b.max_x.max(x),
+                    max_y: b.max_y.max(y),
+                },
+                None => CellBounds {
+                    min_x: x,
+                    min_y: y,
+                    max_x: x,
+                    max_y: y,
+                },
+            })
+        });
+
+    let folder = MapFolderInfo {
+        mod_id: source.mod_id.clone(),
+        map_name: source.map_name.clone(),
+        paths: source
+            .dirs
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect(),
+        title: info.title,
+        lots: info.lots,
+        description: info.description,
+        cell_count: cells.len(),
+        cell_size,
+        bounds,
+        error,
+    };
+    let cells = cell_size.map(|cell_size| MapCells { cell_size, cells });
+    (folder, cells)
+}
+
+/// Cells of `first` whose squares are also covered by a cell of `second`.
+/// Works across B41 and B42 cell sizes by comparing square ranges.
+fn overlapping_cells(first: &MapCells, second: &MapCells) -> Vec<(i32, i32)> {
+    let mut overlaps: Vec<(i32, i32)> = first
+        .cells
+        .iter()
+        .copied()
+        .filter(|&(x, y)| {
+            let square = |cell: i32| cell * first.cell_size;
+            let range = |start: i32| {
+                start.div_euclid(second.cell_size)
+                    ..=(start + first.cell_size - 1).div_euclid(second.cell_size)
+            };
+            range(square(x)).any(|other_x| {
+                range(square(y)).any(|other_y| second.cells.contains(&(other_x, other_y)))
+            })
+        })
+        .collect();
+    overlaps.sort_unstable();
+    overlaps
+}
+
+/// Map folders under each mod's `media/maps`, merged across the mod's
+/// `common/` and version folders.
+pub(crate) fn mod_map_sources(mods: &[LoadoutMod]) -> Vec<MapSource> {
+    let mut sources = Vec::new();
+    for entry in mods {
+        let Some(info_path) = entry
+            .mod_info_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+        else {
+            continue;
+        };
+        let mut by_name: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for content_dir in mod_content_dirs(Path::new(info_path)) {
+            let Ok(entries) = fs::read_dir(content_dir.join("media").join("maps")) else {
+                continue;
+            };
+            for map_dir in entries.flatten() {
+                if map_dir.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    let name = map_dir.file_name().to_string_lossy().to_string();
+                    by_name.entry(name).or_default().push(map_dir.path());
+                }
+            }
+        }
+        let mod_id = entry.mod_id.clone().or_else(|| entry.name.clone());
+        sources.extend(by_name.into_iter().map(|(map_name, dirs)| MapSource {
+            mod_id: mod_id.clone(),
+            map_name,
+            dirs,
+        }));
+    }
+    sources
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapLinePlan {
+    /// Highest priority first, ending with `VANILLA_WORLD_MAP`.
+    pub maps: Vec<String>,
+    /// The `Map=` value, the entries joined with `;`, or `None` when no mod
+    /// in the loadout provides a map and the existing line should be kept.
+    pub value: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+fn declared_worldmaps(entry: &LoadoutMod) -> Vec<String> {
+    let declared = entry.worldmap.clone().or_else(|| {
+        let info_path = entry.mod_info_path.as_deref()?.trim();
+        read_mod_summary(Path::new(info_path)).ok()?.worldmap
+    });
+    declared
+        .unwrap_or_default()
+        .split(';')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Derives `Map=` from the loadout.  The game takes the first map listing a
+/// cell, so mod maps come in loadout order ahead of the vanilla world, which
+/// they would otherwise never override.
+pub(crate) fn plan_map_line(mods: &[LoadoutMod]) -> MapLinePlan {
+    let sources = mod_map_sources(mods);
+    let mut plan = MapLinePlan::default();
+    for entry in mods {
+        let mod_id = entry.mod_id.clone().or_else(|| entry.name.clone());
+        let label = mod_id.as_deref().unwrap_or("an unnamed mod");
+        for declared in declared_worldmaps(entry) {
+            let found = sources
+                .iter()
+                .any(|source| source.mod_id == mod_id && source.map_name == declared);
+            if !found && !VANILLA_MAPS.contains(&declared.as_str()) {
+                plan.warnings.push(format!(
+                    "{label} declares worldmap \"{declared}\" but has no media/maps/{declared} folder."
+                ));
+            }
+        }
+        for source in sources.iter().filter(|source| source.mod_id == mod_id) {
+            let name = &source.map_name;
+            if VANILLA_MAPS.contains(&name.as_str()) {
+                // Patches to vanilla cells load through the vanilla entry.
+                continue;
+            }
+            if !source.dirs.iter().any(|dir| dir.join("map.info").is_file()) {
+                plan.warnings.push(format!(
+                    "Map \"{name}\" from {label} has no map.info and was left out."
+                ));
+                continue;
+            }
+            if plan.maps.contains(name) {
+                plan.warnings.push(format!(
+                    "Map \"{name}\" from {label} is already provided by an earlier mod."
+                ));
+                continue;
+            }
+            plan.maps.push(name.clone());
+        }
+    }
+    if plan.maps.is_empty() {
+        return plan;
+    }
+    plan.maps.push(VANILLA_WORLD_MAP.to_string());
+    plan.value = Some(plan.maps.join(";"));
+    plan
+}
+
+pub(crate) fn inspect_maps(vanilla_maps_dir: Option<&Path>, mods: &[LoadoutMod]) -> MapModReport {
+    let mut sources = Vec::new();
+    if let Some(dir) = vanilla_maps_dir {
+        let world = dir.join(VANILLA_WORLD_MAP);
+        if world.is_dir() {
+            sources.push(MapSource {
+                mod_id: None,
+                map_name: VANILLA_WORLD_MAP.to_string(),
+                dirs: vec![world],
+            });
+        }
+    }
+    sources.extend(mod_map_sources(mods));
+
+    let inspected: Vec<(MapFolderInfo, Option<MapCells>)> =
+        sources.par_iter().map(inspect_map).collect();
+    let mut overlaps = Vec::new();
+    for (index, (first, first_cells)) in inspected.iter().enumerate() {
+        let Some(first_cells) = first_cells else {
+            continue;
+        };
+        for (second, second_cells) in &inspected[index + 1..] {
+            let Some(second_cells) = second_cells else {
+                continue;
+            };
+            if first.mod_id.is_some() && first.mod_id == second.mod_id {
+                continue;
+            }
+            let cells = overlapping_cells(first_cells, second_cells);
+            if !cells.is_empty() {
+                overlaps.push(MapCellOverlap {
+                    first_mod_id: first.mod_id.clone(),
+                    first_map: first.map_name.clone(),
+                    second_mod_id: second.mod_id.clone(),
+                    second_map: second.map_name.clone(),
+                    cells,
+                });
+            }
+        }
+    }
+
+    MapModReport {
+        maps: inspected.into_iter().map(|(info, _)| info).collect(),
+        overlaps,
+    }
+}
+
+/// Lists the map folders of `mods` and the cells they share with each other
+/// and with the vanilla world under `media_dir/maps`.
+#[tauri::command]
+pub fn inspect_map_mods(media_dir: String, mods: Vec<LoadoutMod>) -> Result<MapModReport, String> {
+    let _timer = scoped_timer("inspect_map_mods");
+    let media_dir = media_dir.trim();
+    let vanilla_maps_dir = (!media_dir.is_empty()).then(|| Path::new(media_dir).join("maps"));
+    Ok(inspect_maps(vanilla_maps_dir.as_deref(), &mods))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    fn write_b42_header(dir: &Path, x: i32, y: i32) {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"LOTH");
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&256u32.to_le_bytes());
+        bytes.extend_from_slice(&256u32.to_le_bytes());
+        bytes.extend_from_slice(&0i32.to_le_bytes());
+        bytes.extend_from_slice(&1i32.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend(std::iter::repeat_n(0u8, 32 * 32));
+        fs::create_dir_all(dir).expect("map directory should be created");
+        fs::write(dir.join(format!("{x}_{y}.lotheader")), bytes).expect("header should be written");
+    }
+
+    #[test]
+    fn reports_map_folders_and_cells_shared_with_vanilla_and_other_mods() {
+        let root = TestDir::new("pz-map-mods-test");
+        let vanilla = root.join("media/maps");
+        write_b42_header(&vanilla.join(VANILLA_WORLD_MAP), 40, 40);
+        write_b42_header(&vanilla.join(VANILLA_WORLD_MAP), 41, 40);
+
+        let town = root.join("TownMod/media/maps/Town");
+        write_b42_header(&town, 41, 40);
+        write_b42_header(&town, 50, 52);
+        root.write(
+            "TownMod/media/maps/Town/map.info",
+            "title=Town\nlots=Muldraugh, KY\ndescription=A small town\n",
+        );
+        root.write("TownMod/mod.info", "id=TownMod");
+        let ranch = root.join("RanchMod/media/maps/Ranch");
+        write_b42_header(&ranch, 50, 52);
+        root.write("RanchMod/mod.info", "id=RanchMod");
+
+        let loadout_mod = |id: &str| LoadoutMod {
+            mod_id: Some(id.to_string()),
+            mod_info_path: Some(root.join(id).join("mod.info").to_string_lossy().to_string()),
+            ..LoadoutMod::default()
+        };
+        let report = inspect_maps(
+            Some(&vanilla),
+            &[loadout_mod("TownMod"), loadout_mod("RanchMod")],
+        );
+
+        assert_eq!(report.maps.len(), 3);
+        let town_info = &report.maps[1];
+        assert_eq!(town_info.title.as_deref(), Some("Town"));
+        assert_eq!(town_info.lots, ["Muldraugh, KY"]);
+        assert_eq!(town_info.cell_count, 2);
+        assert_eq!(town_info.cell_size, Some(256));
+        assert_eq!(
+            town_info.bounds,
+            Some(CellBounds {
+                min_x: 41,
+                min_y: 40,
+                max_x: 50,
+                max_y: 52
+            })
+        );
+        assert_eq!(report.overlaps.len(), 2);
+        assert_eq!(report.overlaps[0].first_mod_id, None);
+        assert_eq!(report.overlaps[0].second_mod_id.as_deref(), Some("TownMod"));
+        assert_eq!(report.overlaps[0].cells, [(41, 40)]);
+        assert_eq!(report.overlaps[1].second_map, "Ranch");
+        assert_eq!(report.overlaps[1].cells, [(50, 52)]);
+    }
+
+    #[test]
+    fn compares_cells_of_different_sizes_by_square() {
+        let b41 = MapCells {
+            cell_size: 300,
+            cells: HashSet::from([(1, 1)]),
+        };
+        let b42 = MapCells {
+            cell_size: 256,
+            cells: HashSet::from([(2, 2), (5, 5)]),
+        };
+        assert_eq!(overlapping_cells(&b41, &b42), [(1, 1)]);
+        assert_eq!(overlapping_cells(&b42, &b41), [(2, 2)]);
+    }
+
+    #[test]
+    fn puts_mod_maps_before_the_vanilla_world_map() {
+        let root = TestDir::new("pz-map-line-test");
+        root.write("TownMod/mod.info", "id=TownMod\nworldmap=Town");
+        root.write("TownMod/media/maps/Town/map.info", "title=Town");
+        root.write("RanchMod/mod.info", "id=RanchMod\nworldmap=Ranch");
+        root.write("PatchMod/mod.info", "id=PatchMod");
+        root.write("PatchMod/media/maps/Muldraugh, KY/spawnpoints.lua", "");
+        let mods: Vec<LoadoutMod> = ["TownMod", "RanchMod", "PatchMod"]
+            .iter()
+            .map(|id| LoadoutMod {
+                mod_id: Some(id.to_string()),
+                mod_info_path: Some(root.join(id).join("mod.info").to_string_lossy().to_string()),
+                ..LoadoutMod::default()
+            })
+            .collect();
+
+        let plan = plan_map_line(&mods);
+        assert_eq!(plan.value, Some(format!("Town;{VANILLA_WORLD_MAP}")));
+        assert_eq!(plan.warnings.len(), 1);
+        assert!(plan.warnings[0].contains("RanchMod"));
+
+        let without_maps = plan_map_line(&mods[1..]);
+        assert_eq!(without_maps.value, None);
+        assert!(without_maps.maps.is_empty());
+        assert_eq!(without_maps.warnings.len(), 1);
+    }
+}
@@ -1,6 +1,28 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// Every script `.txt` under `<media_dir>/scripts`, sorted by path.
+pub(crate) fn script_files(media_dir: &Path) -> Vec<PathBuf> {
+    let script_dir = media_dir.join("scripts");
+    if !script_dir.exists() {
+        return Vec::new();
+    }
+    let mut out: Vec<PathBuf> = WalkDir::new(script_dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"))
+        })
+        .collect();
+    out.sort();
+    out
+}
+
 #[tauri::command]
 pub fn list_media_script_files(
     media_dir: String,
@@ -17,25 +39,11 @@ pub fn list_media_script_files(
         }
     }
     for root in roots {
-        let script_dir = Path::new(&root).join("scripts");
-        if !script_dir.exists() {
-            continue;
-        }
-        for entry in WalkDir::new(script_dir)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-        {
-            if !entry.file_type().is_file() {
-                continue;
-            }
-            let path = entry.path();
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-            if !ext.eq_ignore_ascii_case("txt") {
-                continue;
-            }
-            out.push(path.to_string_lossy().to_string());
-        }
+        out.extend(
+            script_files(Path::new(&root))
+                .into_iter()
+                .map(|path| path.to_string_lossy().to_string()),
+        );
     }
     out.sort();
     Ok(out)
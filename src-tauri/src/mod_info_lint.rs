@@ -0,0 +1,264 @@
+use crate::mod_scanner::{
+    MOD_INFO_KEYS, decode_mod_info, find_mod_info_paths, layout_folder_name, normalize_mod_ref,
+};
+use crate::timing::scoped_timer;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const IMAGE_KEYS: &[&str] = &["icon", "preview", "poster"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModInfoDiagnostic {
+    pub mod_info_path: String,
+    pub severity: DiagnosticSeverity,
+    /// 1-based; `None` for problems with the file as a whole.
+    pub line: Option<usize>,
+    pub key: Option<String>,
+    pub message: String,
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split([';', ','])
+        .map(|part| part.trim().trim_matches('"').trim_matches('\''))
+        .filter(|part| !part.is_empty())
+}
+
+/// Checks one `mod.info` the way the game and the scanner will read it.
+pub(crate) fn lint_mod_info_file(path: &Path) -> Result<Vec<ModInfoDiagnostic>, String> {
+    let raw = fs::read(path).map_err(|e| e.to_string())?;
+    let invalid_utf8_line = std::str::from_utf8(&raw).err().map(|error| {
+        raw[..error.valid_up_to()]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count()
+            + 1
+    });
+    let (content, fallback_encoding) = decode_mod_info(raw);
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mod_info_path = path.to_string_lossy().to_string();
+    let mut diagnostics = Vec::new();
+    let mut push = |severity, line: Option<usize>, key: Option<&str>, message: String| {
+        diagnostics.push(ModInfoDiagnostic {
+            mod_info_path: mod_info_path.clone(),
+            severity,
+            line,
+            key: key.map(str::to_string),
+            message,
+        });
+    };
+
+    if let Some(encoding) = fallback_encoding {
+        push(
+            DiagnosticSeverity::Warning,
+            invalid_utf8_line,
+            None,
+            format!("mod.info is not valid UTF-8 and was read as {encoding}; save it as UTF-8."),
+        );
+    }
+
+    let b42_layout = layout_folder_name(path).is_some();
+    let mut first_line_by_key: HashMap<&str, usize> = HashMap::new();
+    let mut mod_id: Option<String> = None;
+    let mut requirements: Vec<(usize, String, String)> = Vec::new();
+
+    for (index, raw_line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("//")
+            || line.starts_with(';')
+        {
+            continue;
+        }
+        let Some((key_raw, value_raw)) = line.split_once('=') else {
+            push(
+                DiagnosticSeverity::Warning,
+                Some(line_number),
+                None,
+                "Line is not a key=value pair and is ignored.".to_string(),
+            );
+            continue;
+        };
+        let key_text = key_raw.trim();
+        let key = key_text.to_lowercase();
+        let value = value_raw.trim();
+        let Some(&(_, canonical, repeatable)) =
+            MOD_INFO_KEYS.iter().find(|(known, _, _)| *known == key)
+        else {
+            push(
+                DiagnosticSeverity::Warning,
+                Some(line_number),
+                Some(key_text),
+                format!("Unknown key \"{key_text}\" is ignored."),
+            );
+            continue;
+        };
+        if value.is_empty() {
+            push(
+                DiagnosticSeverity::Warning,
+                Some(line_number),
+                Some(key_text),
+                format!("\"{key_text}\" has no value."),
+            );
+            continue;
+        }
+        if let Some(first_line) = first_line_by_key.get(canonical) {
+            if !repeatable {
+                push(
+                    DiagnosticSeverity::Warning,
+                    Some(line_number),
+                    Some(key_text),
+                    format!(
+                        "\"{key_text}\" is already set on line {first_line}; this value replaces it."
+                    ),
+                );
+            }
+        } else {
+            first_line_by_key.insert(canonical, line_number);
+        }
+
+        match canonical {
+            "id" => mod_id = Some(value.to_string()),
+            "require" | "depend" => {
+                for entry in split_list(value) {
+                    requirements.push((line_number, key_text.to_string(), entry.to_string()));
+                }
+            }
+            _ if IMAGE_KEYS.contains(&canonical) => {
+                let images: Vec<&str> = if repeatable {
+                    split_list(value).collect()
+                } else {
+                    vec![value]
+                };
+                for image in images {
+                    let candidate = Path::new(image);
+                    let exists = if candidate.is_absolute() {
+                        candidate.exists()
+                    } else {
+                        base_dir.join(image).exists()
+                    };
+                    if !exists {
+                        push(
+                            DiagnosticSeverity::Warning,
+                            Some(line_number),
+                            Some(key_text),
+                            format!("Image \"{image}\" does not exist next to mod.info."),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if mod_id.is_none() {
+        push(
+            DiagnosticSeverity::Error,
+            None,
+            Some("id"),
+            "mod.info has no id; the game cannot load or require this mod.".to_string(),
+        );
+    }
+    for (line_number, key, entry) in requirements {
+        let normalized = normalize_mod_ref(&entry);
+        if mod_id
+            .as_deref()
+            .is_some_and(|id| id.eq_ignore_ascii_case(&normalized))
+        {
+            push(
+                DiagnosticSeverity::Error,
+                Some(line_number),
+                Some(&key),
+                format!("The mod requires itself (\"{entry}\")."),
+            );
+        } else if b42_layout && !entry.starts_with('\\') {
+            push(
+                DiagnosticSeverity::Warning,
+                Some(line_number),
+                Some(&key),
+                format!("Build 42 expects requirements as \"\\{entry}\"."),
+            );
+        }
+    }
+    Ok(diagnostics)
+}
+
+#[tauri::command]
+pub fn lint_mod_info(path: String) -> Result<Vec<ModInfoDiagnostic>, String> {
+    let _timer = scoped_timer("lint_mod_info");
+    let target = Path::new(&path);
+    let mut paths = if target.is_file() {
+        vec![target.to_path_buf()]
+    } else {
+        find_mod_info_paths(&path)
+    };
+    if paths.is_empty() {
+        return Err(format!("No mod.info was found under {path}."));
+    }
+    paths.sort();
+    let mut diagnostics = Vec::new();
+    for info_path in paths {
+        diagnostics.extend(lint_mod_info_file(&info_path)?);
+    }
+    Ok(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn reports_line_level_mod_info_problems() {
+        let root = TestDir::new("pz-mod-info-lint-test");
+        let version_dir = root.join("Example").join("42");
+        fs::create_dir_all(&version_dir).expect("mod directory should be created");
+        fs::write(version_dir.join("poster.png"), b"png").expect("poster should be written");
+        let mut content = b"name=Example\nposter=poster.png\nposter=missing.png\nname=Again\nrequire=\\Other,Example,Plain\nfavourite=yes\n"
+            .to_vec();
+        content.extend_from_slice(b"description=Caf\xe9\n");
+        fs::write(version_dir.join("mod.info"), content).expect("mod.info should be written");
+
+        let diagnostics =
+            lint_mod_info(root.to_string_lossy().to_string()).expect("lint should succeed");
+        let summary: Vec<(DiagnosticSeverity, Option<usize>, Option<&str>)> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.line, d.key.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (DiagnosticSeverity::Warning, Some(7), None),
+                (DiagnosticSeverity::Warning, Some(3), Some("poster")),
+                (DiagnosticSeverity::Warning, Some(4), Some("name")),
+                (DiagnosticSeverity::Warning, Some(6), Some("favourite")),
+                (DiagnosticSeverity::Error, None, Some("id")),
+                (DiagnosticSeverity::Warning, Some(5), Some("require")),
+                (DiagnosticSeverity::Warning, Some(5), Some("require")),
+            ]
+        );
+
+        fs::write(
+            version_dir.join("mod.info"),
+            "id=Example\nname=Example\nrequire=\\Other,\\example\n",
+        )
+        .expect("mod.info should be written");
+        let diagnostics =
+            lint_mod_info(root.to_string_lossy().to_string()).expect("lint should succeed");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert!(diagnostics[0].message.contains("requires itself"));
+    }
+}
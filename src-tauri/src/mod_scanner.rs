@@ -1,12 +1,16 @@
+use crate::content_profile::profile_mod;
 use crate::models::{ModFileInfo, ModFolderScanResult, ModSummary, RequiredByInfo};
 use crate::pz_compat::WORKSHOP_APP_ID;
+use crate::pz_version::{PzVersion, compatibility_verdict, game_version, mod_break_version};
+use crate::scan_cache::ScanCache;
 use crate::timing::scoped_timer;
 use crate::utils::to_iso_string;
 use encoding_rs::{EUC_KR, WINDOWS_1252};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 use walkdir::WalkDir;
 
 fn parse_list(raw: &str) -> Vec<String> {
@@ -17,7 +21,7 @@ fn parse_list(raw: &str) -> Vec<String> {
         .collect()
 }
 
-fn normalize_mod_ref(raw: &str) -> String {
+pub(crate) fn normalize_mod_ref(raw: &str) -> String {
     let trimmed = raw.trim().trim_matches('"').trim_matches('\'');
     trimmed.trim_start_matches('\\').to_string()
 }
@@ -58,10 +62,11 @@ fn derive_workshop_id(base: &str, mod_info_path: &str) -> Option<String> {
     }
     None
 }
-fn parse_mod_info_file(path: &Path) -> Result<ModSummary, String> {
-    let raw = fs::read(path).map_err(|e| e.to_string())?;
-    let content = match String::from_utf8(raw) {
-        Ok(s) => s,
+/// Decodes `mod.info` bytes, falling back to the legacy encodings mod authors
+/// still save with.  Returns the fallback encoding's name when one was needed.
+pub(crate) fn decode_mod_info(raw: Vec<u8>) -> (String, Option<&'static str>) {
+    match String::from_utf8(raw) {
+        Ok(s) => (s, None),
         Err(e) => {
             let bytes = e.as_bytes();
             let encodings = [EUC_KR, WINDOWS_1252];
@@ -70,14 +75,65 @@ fn parse_mod_info_file(path: &Path) -> Result<ModSummary, String> {
                 .find_map(|enc| {
                     let (decoded, _, had_errors) = enc.decode(bytes);
                     if !had_errors {
-                        Some(decoded.into_owned())
+                        Some((decoded.into_owned(), Some(enc.name())))
                     } else {
                         None
                     }
                 })
-                .unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned())
+                .unwrap_or_else(|| {
+                    (
+                        String::from_utf8_lossy(bytes).into_owned(),
+                        Some("lossy UTF-8"),
+                    )
+                })
         }
-    };
+    }
+}
+
+/// Keys `parse_mod_info_file` understands, as `(key, canonical key, may repeat)`.
+/// The linter checks files against the same table.  List keys may repeat
+/// because every line adds to the list.
+pub(crate) const MOD_INFO_KEYS: &[(&str, &str, bool)] = &[
+    ("id", "id", false),
+    ("modid", "id", false),
+    ("name", "name", false),
+    ("workshopid", "workshopid", false),
+    ("author", "author", false),
+    ("authors", "author", false),
+    ("version", "version", false),
+    ("modversion", "version", false),
+    ("versionmin", "versionmin", false),
+    ("version_min", "versionmin", false),
+    ("versionmax", "versionmax", false),
+    ("version_max", "versionmax", false),
+    ("url", "url", false),
+    ("description", "description", false),
+    ("require", "require", true),
+    ("requires", "require", true),
+    ("depend", "depend", true),
+    ("dependencies", "depend", true),
+    ("loadafter", "loadafter", true),
+    ("loadbefore", "loadbefore", true),
+    ("incompatible", "incompatible", true),
+    ("pack", "pack", true),
+    ("packs", "pack", true),
+    ("tiledef", "tiledef", true),
+    ("tiledefs", "tiledef", true),
+    ("soundbank", "soundbank", true),
+    ("soundbanks", "soundbank", true),
+    ("worldmap", "worldmap", false),
+    ("icon", "icon", false),
+    ("iconfile", "icon", false),
+    ("preview", "preview", false),
+    ("previewimage", "preview", false),
+    ("preview_image", "preview", false),
+    ("poster", "poster", true),
+    ("posters", "poster", true),
+];
+
+pub(crate) fn parse_mod_info_file(path: &Path) -> Result<ModSummary, String> {
+    let raw = fs::read(path).map_err(|e| e.to_string())?;
+    let (content, _) = decode_mod_info(raw);
     let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
 
     let mut mod_id: Option<String> = None;
@@ -120,31 +176,33 @@ fn parse_mod_info_file(path: &Path) -> Result<ModSummary, String> {
         if value.is_empty() {
             continue;
         }
+        let Some(&(_, canonical, _)) = MOD_INFO_KEYS.iter().find(|(known, _, _)| *known == key)
+        else {
+            continue;
+        };
 
-        match key.as_str() {
-            "id" | "modid" => mod_id = Some(value.to_string()),
+        match canonical {
+            "id" => mod_id = Some(value.to_string()),
             "name" => name = Some(value.to_string()),
             "workshopid" => workshop_id = Some(value.to_string()),
-            "author" | "authors" => author = Some(value.to_string()),
-            "version" | "modversion" => version = Some(value.to_string()),
-            "versionmin" | "version_min" => version_min = Some(value.to_string()),
-            "versionmax" | "version_max" => version_max = Some(value.to_string()),
+            "author" => author = Some(value.to_string()),
+            "version" => version = Some(value.to_string()),
+            "versionmin" => version_min = Some(value.to_string()),
+            "versionmax" => version_max = Some(value.to_string()),
             "url" => url = Some(value.to_string()),
             "description" => description = Some(value.to_string()),
-            "require" | "requires" => requires.extend(parse_list(value)),
-            "depend" | "dependencies" => dependencies.extend(parse_list(value)),
+            "require" => requires.extend(parse_list(value)),
+            "depend" => dependencies.extend(parse_list(value)),
             "loadafter" => load_after.extend(parse_list(value)),
             "loadbefore" => load_before.extend(parse_list(value)),
             "incompatible" => incompatible.extend(parse_list(value)),
-            "pack" | "packs" => packs.extend(parse_list(value)),
-            "tiledef" | "tiledefs" => tiledefs.extend(parse_list(value)),
-            "soundbank" | "soundbanks" => soundbanks.extend(parse_list(value)),
+            "pack" => packs.extend(parse_list(value)),
+            "tiledef" => tiledefs.extend(parse_list(value)),
+            "soundbank" => soundbanks.extend(parse_list(value)),
             "worldmap" => worldmap = Some(value.to_string()),
-            "icon" | "iconfile" => icon = resolve_relative_path(base_dir, value),
-            "preview" | "previewimage" | "preview_image" => {
-                preview_image = resolve_relative_path(base_dir, value)
-            }
-            "poster" | "posters" => {
+            "icon" => icon = resolve_relative_path(base_dir, value),
+            "preview" => preview_image = resolve_relative_path(base_dir, value),
+            "poster" => {
                 for entry in parse_list(value) {
                     if let Some(path) = resolve_relative_path(base_dir, &entry) {
                         poster_images.push(path);
@@ -226,6 +284,11 @@ fn parse_mod_info_file(path: &Path) -> Result<ModSummary, String> {
         description,
         mod_info_path: Some(info_path),
         required_by: None,
+        version_folders: None,
+        selected_version_folder: None,
+        no_usable_folder: None,
+        compatibility: None,
+        content_profile: None,
         workshop: None,
     })
 }
@@ -290,6 +353,174 @@ fn merge_summary(base: &mut ModSummary, incoming: ModSummary) {
     merge_optional_string(&mut base.mod_info_path, incoming.mod_info_path);
 }
 
+const COMMON_FOLDER: &str = "common";
+
+fn is_common_folder(name: &str) -> bool {
+    name.eq_ignore_ascii_case(COMMON_FOLDER)
+}
+
+/// `common/` or a build-numbered folder such as `42` or `42.13`.
+pub(crate) fn is_layout_folder(name: &str) -> bool {
+    is_common_folder(name) || PzVersion::parse(name).is_some()
+}
+
+/// Name of the B42 `common/` or `42.x/` folder holding `mod_info_path`, if any.
+pub(crate) fn layout_folder_name(mod_info_path: &Path) -> Option<String> {
+    let name = mod_info_path.parent()?.file_name()?.to_str()?;
+    is_layout_folder(name).then(|| name.to_string())
+}
+
+/// The mod's own folder: the parent of its version folders in the B42 layout,
+/// otherwise the folder that contains `mod.info`.
+pub(crate) fn mod_root_dir(mod_info_path: &Path) -> PathBuf {
+    let parent = mod_info_path.parent().unwrap_or_else(|| Path::new(""));
+    if layout_folder_name(mod_info_path).is_some() {
+        if let Some(root) = parent.parent() {
+            return root.to_path_buf();
+        }
+    }
+    parent.to_path_buf()
+}
+
+/// The version folders of the mod owning `mod_info_path`, the one the game
+/// loads, and the `mod.info` it reads: the selected folder's for B42 mods, so
+/// any of its version folders resolves the same way, otherwise the file itself.
+fn resolve_version_layout(mod_info_path: &Path) -> (PathBuf, Vec<String>, Option<String>) {
+    let root = mod_root_dir(mod_info_path);
+    let folders = list_version_folders(&root);
+    if layout_folder_name(mod_info_path).is_none() {
+        return (mod_info_path.to_path_buf(), folders, None);
+    }
+    let selected = select_version_folder(
+        folders
+            .iter()
+            .map(String::as_str)
+            .filter(|folder| root.join(folder).join("mod.info").is_file()),
+    )
+    .map(str::to_string);
+    let info_path = match &selected {
+        Some(folder) => root.join(folder).join("mod.info"),
+        None => mod_info_path.to_path_buf(),
+    };
+    (info_path, folders, selected)
+}
+
+/// Folders whose `media` the game merges for this mod, lowest priority first:
+/// `common/` and then the selected version folder for B42 mods.
+pub(crate) fn mod_content_dirs(mod_info_path: &Path) -> Vec<PathBuf> {
+    let (mod_info_path, _, _) = resolve_version_layout(mod_info_path);
+    let Some(parent) = mod_info_path.parent() else {
+        return Vec::new();
+    };
+    let mut dirs = Vec::new();
+    if let Some(folder) = layout_folder_name(&mod_info_path) {
+        let common = mod_root_dir(&mod_info_path).join(COMMON_FOLDER);
+        if !is_common_folder(&folder) && common.is_dir() {
+            dirs.push(common);
+        }
+    }
+    dirs.push(parent.to_path_buf());
+    dirs
+}
+
+fn list_version_folders(root: &Path) -> Vec<String> {
+    let mut folders: Vec<String> = fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+                .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+                .filter(|name| is_layout_folder(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    folders.sort_by_key(|name| (!is_common_folder(name), PzVersion::parse(name)));
+    folders
+}
+
+/// Picks the version folder the running build loads: the newest one that is
+/// not newer than `GAME_VERSION` and not older than `MOD_BREAK_VERSION`.
+pub(crate) fn select_version_folder<'a>(
+    folders: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let game = game_version();
+    let oldest = mod_break_version();
+    folders
+        .into_iter()
+        .filter_map(|name| PzVersion::parse(name).map(|version| (version, name)))
+        .filter(|(version, _)| *version <= game && *version >= oldest)
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, name)| name)
+}
+
+/// Reduces every mod folder to the one `mod.info` the game would read and
+/// records the version folders it ships.  Mods without a folder the current
+/// build can load, including pre-B42 layouts, are flagged.
+fn select_version_layouts(summaries: Vec<ModSummary>) -> Vec<ModSummary> {
+    let mut by_root: BTreeMap<PathBuf, Vec<(Option<String>, ModSummary)>> = BTreeMap::new();
+    let mut without_path: Vec<ModSummary> = Vec::new();
+    for summary in summaries {
+        let Some(info_path) = summary.mod_info_path.clone() else {
+            without_path.push(summary);
+            continue;
+        };
+        let info_path = Path::new(&info_path);
+        by_root
+            .entry(mod_root_dir(info_path))
+            .or_default()
+            .push((layout_folder_name(info_path), summary));
+    }
+
+    let mut out = without_path;
+    for (root, mut entries) in by_root {
+        let folders = list_version_folders(&root);
+        let selected =
+            select_version_folder(entries.iter().filter_map(|(folder, _)| folder.as_deref()))
+                .map(|name| name.to_string());
+        let primary = match &selected {
+            Some(name) => entries
+                .iter()
+                .position(|(folder, _)| folder.as_deref() == Some(name.as_str())),
+            None => entries
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, (folder, _))| folder.as_deref().and_then(PzVersion::parse))
+                .map(|(index, _)| index),
+        };
+        let Some(primary) = primary else {
+            continue;
+        };
+        let (_, mut summary) = entries.swap_remove(primary);
+        apply_version_layout(&mut summary, folders, selected);
+        out.push(summary);
+    }
+    for summary in &mut out {
+        summary.compatibility = Some(compatibility_verdict(summary));
+    }
+    out
+}
+
+fn apply_version_layout(summary: &mut ModSummary, folders: Vec<String>, selected: Option<String>) {
+    summary.no_usable_folder = Some(selected.is_none());
+    summary.selected_version_folder = selected;
+    summary.version_folders = if folders.is_empty() {
+        None
+    } else {
+        Some(folders)
+    };
+}
+
+/// Parses the `mod.info` the game reads for the mod owning `path` and fills in
+/// the layout fields and compatibility verdict the same way a folder scan
+/// would.
+pub(crate) fn read_mod_summary(path: &Path) -> Result<ModSummary, String> {
+    let (info_path, folders, selected) = resolve_version_layout(path);
+    let mut summary = parse_mod_info_file(&info_path)?;
+    apply_version_layout(&mut summary, folders, selected);
+    summary.compatibility = Some(compatibility_verdict(&summary));
+    Ok(summary)
+}
+
 #[tauri::command]
 pub fn validate_pz_workshop_path(path: String) -> Result<bool, String> {
     let dir = Path::new(&path);
@@ -328,13 +559,32 @@ pub fn validate_pz_workshop_path(path: String) -> Result<bool, String> {
     Ok(has_numeric_folder)
 }
 
-#[tauri::command]
-pub fn scan_mod_folder(path: String) -> Result<ModFolderScanResult, String> {
-    let _timer = scoped_timer("scan_mod_folder");
-    let mut paths: Vec<std::path::PathBuf> = Vec::new();
-    for entry in WalkDir::new(&path)
+/// A mod's own `media/`, next to its `mod.info` or inside `common/`.  It holds
+/// almost every file of a mod but never a `mod.info` the game reads.
+pub(crate) fn is_mod_media_dir(path: &Path) -> bool {
+    if !path
+        .file_name()
+        .is_some_and(|name| name.eq_ignore_ascii_case("media"))
+        || !path.is_dir()
+    {
+        return false;
+    }
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    parent.join("mod.info").is_file()
+        || parent
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(is_common_folder)
+}
+
+pub(crate) fn find_mod_info_paths(root: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for entry in WalkDir::new(root)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !is_mod_media_dir(entry.path()))
         .filter_map(|entry| entry.ok())
     {
         if !entry.file_type().is_file() {
@@ -345,36 +595,82 @@ pub fn scan_mod_folder(path: String) -> Result<ModFolderScanResult, String> {
             paths.push(entry.path().to_path_buf());
         }
     }
+    paths
+}
+
+/// Reads one `mod.info` as found under the scanned `root`, before version
+/// folders are resolved and duplicates are merged.
+pub(crate) fn read_mod_info_entry(
+    root: &str,
+    info_path: &Path,
+) -> Result<(ModFileInfo, ModSummary), String> {
+    let metadata = fs::metadata(info_path).map_err(|e| e.to_string())?;
+    let modified = metadata.modified().ok().and_then(to_iso_string);
+    let file_info = ModFileInfo {
+        path: info_path.to_string_lossy().to_string(),
+        file_name: "mod.info".to_string(),
+        modified,
+        size: metadata.len(),
+    };
+
+    let mut summary = parse_mod_info_file(info_path)?;
+    summary.install_date = metadata.modified().ok().and_then(to_iso_string);
+    if summary.workshop_id.is_none() {
+        if let Some(mod_info_path) = summary.mod_info_path.clone() {
+            summary.workshop_id = derive_workshop_id(root, &mod_info_path);
+        }
+    }
+    Ok((file_info, summary))
+}
 
-    let results: Vec<(ModFileInfo, ModSummary)> = paths
+#[tauri::command]
+pub fn scan_mod_folder(
+    app: AppHandle,
+    path: String,
+    content_profile: Option<bool>,
+) -> Result<ModFolderScanResult, String> {
+    let _timer = scoped_timer("scan_mod_folder");
+    let mut result = scan_mod_folder_cached(&app, &path)?;
+    if content_profile.unwrap_or(false) {
+        result.summaries.par_iter_mut().for_each(|summary| {
+            summary.content_profile = summary
+                .mod_info_path
+                .as_deref()
+                .map(|info_path| profile_mod(Path::new(info_path)));
+        });
+    }
+    Ok(result)
+}
+
+/// Scans through the persistent cache, or directly when it cannot be opened.
+pub(crate) fn scan_mod_folder_cached(
+    app: &AppHandle,
+    path: &str,
+) -> Result<ModFolderScanResult, String> {
+    match ScanCache::open_for_app(app) {
+        Ok(cache) => cache.scan(path),
+        Err(_) => scan_mod_folder_uncached(path),
+    }
+}
+
+pub(crate) fn scan_mod_folder_uncached(path: &str) -> Result<ModFolderScanResult, String> {
+    let entries = find_mod_info_paths(path)
         .par_iter()
-        .map(|info_path| -> Result<(ModFileInfo, ModSummary), String> {
-            let metadata = fs::metadata(info_path).map_err(|e| e.to_string())?;
-            let modified = metadata.modified().ok().and_then(to_iso_string);
-            let file_info = ModFileInfo {
-                path: info_path.to_string_lossy().to_string(),
-                file_name: "mod.info".to_string(),
-                modified,
-                size: metadata.len(),
-            };
-
-            let mut summary = parse_mod_info_file(info_path)?;
-            summary.install_date = metadata.modified().ok().and_then(to_iso_string);
-            if summary.workshop_id.is_none() {
-                if let Some(mod_info_path) = summary.mod_info_path.clone() {
-                    summary.workshop_id = derive_workshop_id(&path, &mod_info_path);
-                }
-            }
-            Ok((file_info, summary))
-        })
+        .map(|info_path| read_mod_info_entry(path, info_path))
         .collect::<Result<Vec<_>, String>>()?;
+    Ok(build_scan_result(entries))
+}
 
-    let mut files: Vec<ModFileInfo> = Vec::with_capacity(results.len());
-    let mut summaries: Vec<ModSummary> = Vec::with_capacity(results.len());
-    for (file, summary) in results {
+/// Resolves version folders, merges duplicate mods and links `required_by`
+/// for the raw `mod.info` entries of one scan.
+pub(crate) fn build_scan_result(entries: Vec<(ModFileInfo, ModSummary)>) -> ModFolderScanResult {
+    let mut files: Vec<ModFileInfo> = Vec::with_capacity(entries.len());
+    let mut summaries: Vec<ModSummary> = Vec::with_capacity(entries.len());
+    for (file, summary) in entries {
         files.push(file);
         summaries.push(summary);
     }
+    let summaries = select_version_layouts(summaries);
 
     let mut deduped: HashMap<String, ModSummary> = HashMap::new();
     let mut uniques: Vec<ModSummary> = Vec::new();
@@ -463,5 +759,127 @@ pub fn scan_mod_folder(path: String) -> Result<ModFolderScanResult, String> {
         }
     }
 
-    Ok(ModFolderScanResult { files, summaries })
+    ModFolderScanResult {
+        files,
+        summaries,
+        changes: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pz_version::VersionCompatibility;
+    use crate::utils::TestDir;
+
+    /// Version folder names around the targeted build: the oldest loadable
+    /// one, the current build, the next minor and the next major build.
+    fn fixture_versions() -> (String, String, String, String) {
+        let game = game_version();
+        (
+            mod_break_version().major.to_string(),
+            game.to_string(),
+            format!("{}.{}", game.major, game.minor + 1),
+            (game.major + 1).to_string(),
+        )
+    }
+
+    #[test]
+    fn selects_the_newest_version_folder_the_game_can_load() {
+        let (oldest, current, next_minor, next_major) = fixture_versions();
+        assert_eq!(
+            select_version_folder([
+                oldest.as_str(),
+                current.as_str(),
+                next_minor.as_str(),
+                "common"
+            ]),
+            Some(current.as_str())
+        );
+        let older = (mod_break_version().major - 1).to_string();
+        assert_eq!(
+            select_version_folder([older.as_str(), next_major.as_str()]),
+            None
+        );
+    }
+
+    #[test]
+    fn scans_b42_version_folders_as_one_mod() {
+        let root = TestDir::new("pz-mod-scanner-test");
+        let (oldest, current, next_minor, _) = fixture_versions();
+        root.write("100/mods/Layered/common/media/lua/a.lua", "");
+        root.write("100/mods/Layered/common/media/mod.info", "id=Ignored");
+        root.write(
+            format!("100/mods/Layered/{oldest}/mod.info"),
+            "id=Layered\nname=Old",
+        );
+        root.write(
+            format!("100/mods/Layered/{current}/mod.info"),
+            "id=Layered\nname=Current",
+        );
+        root.write(
+            format!("100/mods/Layered/{next_minor}/mod.info"),
+            "id=Layered\nname=Future",
+        );
+        root.write("200/mods/Legacy/mod.info", "id=Legacy\nname=Legacy");
+        root.write("300/media/Nested/mod.info", "id=Nested\nname=Nested");
+
+        let result =
+            scan_mod_folder_uncached(&root.to_string_lossy()).expect("scan should succeed");
+        assert_eq!(result.files.len(), 5);
+        assert_eq!(result.summaries.len(), 3);
+        let layered = result
+            .summaries
+            .iter()
+            .find(|summary| summary.mod_id.as_deref() == Some("Layered"))
+            .expect("layered mod should be scanned");
+        assert_eq!(layered.name, "Current");
+        assert_eq!(
+            layered.selected_version_folder.as_deref(),
+            Some(current.as_str())
+        );
+        assert_eq!(
+            layered.version_folders.as_deref(),
+            Some(
+                &[
+                    "common".to_string(),
+                    oldest.clone(),
+                    current.clone(),
+                    next_minor
+                ][..]
+            )
+        );
+        assert_eq!(layered.no_usable_folder, Some(false));
+        assert_eq!(layered.workshop_id.as_deref(), Some("100"));
+        let content_dirs = mod_content_dirs(Path::new(
+            layered.mod_info_path.as_deref().unwrap_or_default(),
+        ));
+        assert!(content_dirs[0].ends_with("common"));
+        assert!(content_dirs[1].ends_with(&current));
+
+        let old_info = root.join(format!("100/mods/Layered/{oldest}/mod.info"));
+        let resolved = read_mod_summary(&old_info).expect("mod.info should be read");
+        assert_eq!(resolved.name, "Current");
+        assert_eq!(
+            resolved.selected_version_folder.as_deref(),
+            Some(current.as_str())
+        );
+        assert_eq!(
+            mod_content_dirs(&old_info).last(),
+            Some(&root.join(format!("100/mods/Layered/{current}")))
+        );
+
+        let legacy = result
+            .summaries
+            .iter()
+            .find(|summary| summary.mod_id.as_deref() == Some("Legacy"))
+            .expect("legacy mod should be scanned");
+        assert_eq!(legacy.no_usable_folder, Some(true));
+        assert_eq!(legacy.compatibility, Some(VersionCompatibility::TooOld));
+        assert_eq!(
+            layered.compatibility,
+            Some(VersionCompatibility::Compatible)
+        );
+        assert_eq!(legacy.version_folders, None);
+    }
 }
@@ -1,3 +1,5 @@
+use crate::content_profile::ModContentProfile;
+use crate::pz_version::VersionCompatibility;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
@@ -16,7 +18,7 @@ pub struct RequiredByInfo {
     pub name: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ModSummary {
     pub id: String,
     pub mod_id: Option<String>,
@@ -45,14 +47,47 @@ pub struct ModSummary {
     pub description: Option<String>,
     pub mod_info_path: Option<String>,
     pub required_by: Option<Vec<RequiredByInfo>>,
+    /// `common` and `42.x` folders shipped alongside a B42 `mod.info`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_folders: Option<Vec<String>>,
+    /// The version folder the current game build loads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_version_folder: Option<String>,
+    /// Set when no folder of this mod can be loaded by the current game build.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_usable_folder: Option<bool>,
+    /// Verdict against `pz_compat::GAME_VERSION`, filled in by the scanner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<VersionCompatibility>,
+    /// Disk usage of the mod folder, filled in when a scan asks for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_profile: Option<ModContentProfile>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workshop: Option<JsonValue>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModScanChange {
+    pub path: String,
+    pub mod_id: Option<String>,
+    pub workshop_id: Option<String>,
+}
+
+/// Difference between a scan and the previous scan of the same folder.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ModScanChanges {
+    pub added: Vec<ModScanChange>,
+    pub changed: Vec<ModScanChange>,
+    pub removed: Vec<ModScanChange>,
+    pub unchanged: usize,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ModFolderScanResult {
     pub files: Vec<ModFileInfo>,
     pub summaries: Vec<ModSummary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<ModScanChanges>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
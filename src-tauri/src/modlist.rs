@@ -1,43 +1,25 @@
-use crate::utils::{ensure_parent_dir, safe_relative_path};
+use crate::mods_txt::{read_mods_txt, save_mods_txt_path};
+use crate::pz_compat::{SERVER_INI_FILE, server_config_path, validate_server_name};
+use crate::server_ini::ServerIni;
+use crate::timing::scoped_timer;
+use crate::utils::{read_optional_text, write_atomically};
+use serde::Serialize;
 use serde_json::Value as JsonValue;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 fn rewrite_mods_txt(path: &Path, mod_id: &str) -> Result<bool, String> {
-    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let normalized = content.replace("\r\n", "\n");
-    let mut lines: Vec<String> = normalized.lines().map(|l| l.to_string()).collect();
-    let mut updated = false;
-    let mut in_mods = false;
-    for line in &mut lines {
-        let trimmed = line.trim();
-        if trimmed.eq_ignore_ascii_case("mods") {
-            in_mods = true;
-            continue;
-        }
-        if in_mods && trimmed.starts_with('}') {
-            in_mods = false;
-        }
-        if !in_mods {
-            continue;
-        }
-        if let Some((key, value)) = trimmed.split_once('=') {
-            let value = value.trim().trim_end_matches(',').trim_start_matches('\\');
-            if key.trim().eq_ignore_ascii_case("mod") && value.eq_ignore_ascii_case(mod_id.trim()) {
-                *line = String::new();
-                updated = true;
-            }
-        }
-    }
-    if !updated {
+    let (mut file, _) = read_mods_txt(path)?;
+    let mods = file.mods();
+    let kept: Vec<String> = mods
+        .iter()
+        .filter(|id| !id.eq_ignore_ascii_case(mod_id.trim()))
+        .cloned()
+        .collect();
+    if kept.len() == mods.len() {
         return Ok(false);
     }
-    let rewritten = lines
-        .into_iter()
-        .filter(|l| !l.is_empty())
-        .collect::<Vec<_>>()
-        .join("\n");
-    fs::write(path, rewritten).map_err(|e| e.to_string())?;
+    file.set_mods(&kept);
+    write_atomically(path, file.render().as_bytes())?;
     Ok(true)
 }
 
@@ -47,7 +29,7 @@ pub fn remove_mod_from_active_mods(
     rel_dir: String,
     mod_id: String,
 ) -> Result<JsonValue, String> {
-    let path = safe_relative_path(&Path::new(&user_dir).join("Saves"), &rel_dir)?.join("mods.txt");
+    let path = save_mods_txt_path(&user_dir, &rel_dir)?;
     let updated = if path.exists() {
         rewrite_mods_txt(&path, &mod_id)?
     } else {
@@ -59,45 +41,367 @@ pub fn remove_mod_from_active_mods(
     }))
 }
 
-fn rewrite_modlist_settings(path: &Path, mod_id: &str) -> Result<bool, String> {
-    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let normalized = content.replace("\r\n", "\n");
-    let mut lines: Vec<String> = normalized.lines().map(|l| l.to_string()).collect();
-    let mut updated = false;
+/// One line of `Lua/pz_modlist_settings.cfg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ModlistLine {
+    /// `!name:value` entries the game keeps ahead of the presets.  They are
+    /// not presets, but a removed mod is still stripped from their value.
+    Header {
+        name: String,
+        value: String,
+        raw: Option<String>,
+    },
+    /// `name:\ModA;\ModB;`; `raw` is dropped once the preset changes.
+    Preset {
+        name: String,
+        mod_ids: Vec<String>,
+        raw: Option<String>,
+    },
+    Other(String),
+}
+
+impl ModlistLine {
+    fn is_preset(&self, preset_name: &str) -> bool {
+        matches!(self, Self::Preset { name, .. } if name.eq_ignore_ascii_case(preset_name.trim()))
+    }
+}
+
+/// `!` entries whose value is a mod list, like the game's favourites.
+const MOD_LIST_HEADERS: &[&str] = &["!fav!"];
+
+/// The parsed modlist settings file.  Lines are kept in order and unchanged
+/// lines, blank ones included, are written back verbatim with the file's own
+/// line endings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ModlistSettings {
+    lines: Vec<ModlistLine>,
+    bom: bool,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModlistHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModlistPreset {
+    pub name: String,
+    pub mod_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModlistSettingsFile {
+    pub path: String,
+    pub exists: bool,
+    pub headers: Vec<ModlistHeader>,
+    pub presets: Vec<ModlistPreset>,
+}
+
+fn clean_mod_ids<'a>(mod_ids: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut cleaned = Vec::new();
+    for raw in mod_ids {
+        let value = raw.trim().trim_start_matches('\\').trim().to_string();
+        if !value.is_empty() && seen.insert(value.to_lowercase()) {
+            cleaned.push(value);
+        }
+    }
+    cleaned
+}
+
+impl ModlistSettings {
+    fn parse(text: &str) -> Self {
+        let (bom, text) = match text.strip_prefix('\u{feff}') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let lines = text
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim();
+                let Some((name, value)) = trimmed.split_once(':') else {
+                    return ModlistLine::Other(line.to_string());
+                };
+                if trimmed.starts_with('!') {
+                    return ModlistLine::Header {
+                        name: name.trim().to_string(),
+                        value: value.trim().to_string(),
+                        raw: Some(line.to_string()),
+                    };
+                }
+                if name.trim().is_empty() {
+                    return ModlistLine::Other(line.to_string());
+                }
+                ModlistLine::Preset {
+                    name: name.trim().to_string(),
+                    mod_ids: clean_mod_ids(value.split(';')),
+                    raw: Some(line.to_string()),
+                }
+            })
+            .collect();
+        Self {
+            lines,
+            bom,
+            crlf: text.contains("\r\n"),
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        Ok(Self::parse(&read_optional_text(path)?.unwrap_or_default()))
+    }
+
+    fn render_lines(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .map(|line| match line {
+                ModlistLine::Header { raw: Some(raw), .. }
+                | ModlistLine::Preset { raw: Some(raw), .. }
+                | ModlistLine::Other(raw) => raw.clone(),
+                ModlistLine::Header {
+                    name,
+                    value,
+                    raw: None,
+                } => format!("{name}:{value}"),
+                ModlistLine::Preset {
+                    name,
+                    mod_ids,
+                    raw: None,
+                } => build_pz_modlist_entry(name, mod_ids),
+            })
+            .collect()
+    }
+
+    fn render(&self) -> String {
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        let mut out = if self.bom {
+            "\u{feff}".to_string()
+        } else {
+            String::new()
+        };
+        out.push_str(&self.render_lines().join(newline));
+        if self.trailing_newline && !self.lines.is_empty() {
+            out.push_str(newline);
+        }
+        out
+    }
+
+    fn write(&self, path: &Path) -> Result<(), String> {
+        write_atomically(path, self.render().as_bytes())
+    }
+
+    fn headers(&self) -> Vec<ModlistHeader> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                ModlistLine::Header { name, value, .. } => Some(ModlistHeader {
+                    name: name.clone(),
+                    value: value.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn presets(&self) -> Vec<ModlistPreset> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                ModlistLine::Preset { name, mod_ids, .. } => Some(ModlistPreset {
+                    name: name.clone(),
+                    mod_ids: mod_ids.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn preset_index(&self, preset_name: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| line.is_preset(preset_name))
+    }
+
+    fn require_preset(&self, preset_name: &str) -> Result<usize, String> {
+        self.preset_index(preset_name)
+            .ok_or_else(|| format!("Preset \"{}\" does not exist.", preset_name.trim()))
+    }
 
-    for line in &mut lines {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+    fn require_free_name(&self, preset_name: &str) -> Result<String, String> {
+        let name = preset_name.trim();
+        if name.is_empty() {
+            return Err("Preset name is required.".to_string());
+        }
+        if name.starts_with('!') || name.contains(':') {
+            return Err("Preset names cannot start with \"!\" or contain \":\".".to_string());
+        }
+        if self.preset_index(name).is_some() {
+            return Err(format!("Preset \"{name}\" already exists."));
         }
-        if trimmed.starts_with('!') && trimmed.ends_with(':') {
-            continue;
+        Ok(name.to_string())
+    }
+
+    /// Replaces the first preset called `preset_name` and drops any repeats,
+    /// or appends a new one.
+    fn upsert(&mut self, preset_name: &str, mod_ids: &[String]) {
+        let entry = ModlistLine::Preset {
+            name: preset_name.trim().to_string(),
+            mod_ids: clean_mod_ids(mod_ids.iter().map(String::as_str)),
+            raw: None,
+        };
+        match self.preset_index(preset_name) {
+            Some(index) => {
+                let repeats: Vec<usize> = (index + 1..self.lines.len())
+                    .filter(|other| self.lines[*other].is_preset(preset_name))
+                    .collect();
+                for other in repeats.into_iter().rev() {
+                    self.lines.remove(other);
+                }
+                self.lines[index] = entry;
+            }
+            None => self.lines.push(entry),
         }
+    }
+
+    fn remove(&mut self, preset_name: &str) -> bool {
+        let before = self.lines.len();
+        self.lines.retain(|line| !line.is_preset(preset_name));
+        self.lines.len() != before
+    }
 
-        if let Some(idx) = line.find(':') {
-            let name = line[..idx].trim();
-            if name.is_empty() {
-                continue;
+    /// Strips `mod_id` from every preset and from the `MOD_LIST_HEADERS`.
+    fn remove_mod(&mut self, mod_id: &str) -> bool {
+        let target = mod_id.trim();
+        let mut updated = false;
+        for line in &mut self.lines {
+            match line {
+                ModlistLine::Preset { mod_ids, raw, .. } => {
+                    let before = mod_ids.len();
+                    mod_ids.retain(|id| !id.eq_ignore_ascii_case(target));
+                    if mod_ids.len() != before {
+                        *raw = None;
+                        updated = true;
+                    }
+                }
+                ModlistLine::Header { name, value, raw }
+                    if !value.is_empty()
+                        && MOD_LIST_HEADERS
+                            .iter()
+                            .any(|header| header.eq_ignore_ascii_case(name)) =>
+                {
+                    let mod_ids = clean_mod_ids(value.split(';'));
+                    let kept: Vec<String> = mod_ids
+                        .iter()
+                        .filter(|id| !id.eq_ignore_ascii_case(target))
+                        .cloned()
+                        .collect();
+                    if kept.len() != mod_ids.len() {
+                        *value = mod_list_value(&kept);
+                        *raw = None;
+                        updated = true;
+                    }
+                }
+                _ => {}
             }
-            let mods_part = &line[idx + 1..];
-            let cleaned = mods_part
-                .split(';')
-                .map(|chunk| chunk.replace('\\', "").trim().to_string())
-                .filter(|chunk| !chunk.is_empty() && !chunk.eq_ignore_ascii_case(mod_id.trim()))
-                .collect::<Vec<String>>();
-            let rebuilt = build_pz_modlist_entry(name, &cleaned);
-            if rebuilt != *line {
-                *line = rebuilt;
-                updated = true;
+        }
+        updated
+    }
+
+    fn rename(&mut self, preset_name: &str, new_name: &str) -> Result<(), String> {
+        let index = self.require_preset(preset_name)?;
+        let same = preset_name.trim().eq_ignore_ascii_case(new_name.trim());
+        let new_name = if same {
+            new_name.trim().to_string()
+        } else {
+            self.require_free_name(new_name)?
+        };
+        if let ModlistLine::Preset { name, raw, .. } = &mut self.lines[index] {
+            *name = new_name;
+            *raw = None;
+        }
+        Ok(())
+    }
+
+    /// Copies a preset to `new_name`, right after the original.
+    fn duplicate(&mut self, preset_name: &str, new_name: &str) -> Result<(), String> {
+        let index = self.require_preset(preset_name)?;
+        let new_name = self.require_free_name(new_name)?;
+        let ModlistLine::Preset { mod_ids, .. } = &self.lines[index] else {
+            return Err(format!("\"{}\" is not a preset.", preset_name.trim()));
+        };
+        let copy = ModlistLine::Preset {
+            name: new_name,
+            mod_ids: mod_ids.clone(),
+            raw: None,
+        };
+        self.lines.insert(index + 1, copy);
+        Ok(())
+    }
+
+    /// Puts the presets in `order`, which must name each preset exactly once.
+    /// Headers and other lines keep their positions.
+    fn reorder(&mut self, order: &[String]) -> Result<(), String> {
+        let slots: Vec<usize> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line, ModlistLine::Preset { .. }))
+            .map(|(index, _)| index)
+            .collect();
+        if order.len() != slots.len() {
+            return Err(format!(
+                "Expected {} preset names but got {}.",
+                slots.len(),
+                order.len()
+            ));
+        }
+        let mut used = vec![false; self.lines.len()];
+        let mut ordered = Vec::with_capacity(order.len());
+        for name in order {
+            let index = self.require_preset(name)?;
+            if used[index] {
+                return Err(format!("Preset \"{}\" is listed twice.", name.trim()));
             }
+            used[index] = true;
+            ordered.push(self.lines[index].clone());
+        }
+        for (slot, line) in slots.into_iter().zip(ordered) {
+            self.lines[slot] = line;
         }
+        Ok(())
     }
 
-    if !updated {
-        return Ok(false);
+    fn into_file(self, path: &Path) -> ModlistSettingsFile {
+        ModlistSettingsFile {
+            path: path.to_string_lossy().to_string(),
+            exists: path.is_file(),
+            headers: self.headers(),
+            presets: self.presets(),
+        }
     }
-    write_modlist_lines(path, &lines)?;
-    Ok(true)
+}
+
+fn modlist_settings_path(user_dir: &str) -> PathBuf {
+    Path::new(user_dir)
+        .join("Lua")
+        .join("pz_modlist_settings.cfg")
+}
+
+fn update_modlist_settings(
+    user_dir: &str,
+    edit: impl FnOnce(&mut ModlistSettings) -> Result<(), String>,
+) -> Result<ModlistSettingsFile, String> {
+    let path = modlist_settings_path(user_dir);
+    let mut settings = ModlistSettings::read(&path)?;
+    edit(&mut settings)?;
+    settings.write(&path)?;
+    Ok(settings.into_file(&path))
 }
 
 #[tauri::command]
@@ -105,41 +409,28 @@ pub fn remove_mod_from_pz_modlist_settings(
     user_dir: String,
     mod_id: String,
 ) -> Result<JsonValue, String> {
-    let path = Path::new(&user_dir)
-        .join("Lua")
-        .join("pz_modlist_settings.cfg");
-    let updated = if path.exists() {
-        rewrite_modlist_settings(&path, &mod_id)?
-    } else {
-        false
-    };
+    let path = modlist_settings_path(&user_dir);
+    let mut settings = ModlistSettings::read(&path)?;
+    let updated = path.exists() && settings.remove_mod(&mod_id);
+    if updated {
+        settings.write(&path)?;
+    }
     Ok(serde_json::json!({
         "updated": updated,
         "path": path.to_string_lossy().to_string(),
     }))
 }
 
-fn build_pz_modlist_entry(preset_name: &str, mod_ids: &[String]) -> String {
-    let mut seen = std::collections::HashSet::new();
-    let mut cleaned = Vec::new();
-    for raw in mod_ids {
-        let value = raw.trim().trim_start_matches('\\').to_string();
-        if value.is_empty() {
-            continue;
-        }
-        if seen.insert(value.to_lowercase()) {
-            cleaned.push(value);
-        }
-    }
-    if cleaned.is_empty() {
-        return format!("{}:", preset_name.trim());
-    }
-    let mods = cleaned
+/// `\ModA;\ModB;`, or nothing for an empty list.
+fn mod_list_value(mod_ids: &[String]) -> String {
+    clean_mod_ids(mod_ids.iter().map(String::as_str))
         .iter()
-        .map(|id| format!("\\{}", id))
-        .collect::<Vec<_>>()
-        .join(";");
-    format!("{}:{};", preset_name.trim(), mods)
+        .map(|id| format!("\\{id};"))
+        .collect()
+}
+
+fn build_pz_modlist_entry(preset_name: &str, mod_ids: &[String]) -> String {
+    format!("{}:{}", preset_name.trim(), mod_list_value(mod_ids))
 }
 
 #[tauri::command]
@@ -148,56 +439,10 @@ pub fn upsert_pz_modlist_settings_preset(
     preset_name: String,
     mod_ids: Vec<String>,
 ) -> Result<JsonValue, String> {
-    let path = Path::new(&user_dir)
-        .join("Lua")
-        .join("pz_modlist_settings.cfg");
-    let existing = if path.exists() {
-        fs::read_to_string(&path).map_err(|e| e.to_string())?
-    } else {
-        String::new()
-    };
-    let mut normalized = existing.replace("\r\n", "\n");
-    if normalized.starts_with('\u{feff}') {
-        normalized = normalized.trim_start_matches('\u{feff}').to_string();
-    }
-    let mut lines: Vec<String> = normalized.lines().map(|l| l.to_string()).collect();
-    let preset_entry = build_pz_modlist_entry(&preset_name, &mod_ids);
-
-    let target_name = preset_name.trim().to_lowercase();
-    let mut replaced = false;
-    let mut i = 0;
-    while i < lines.len() {
-        let line = lines[i].trim().to_string();
-        if line.is_empty() {
-            i += 1;
-            continue;
-        }
-        if line.starts_with('!') && line.ends_with(':') {
-            i += 1;
-            continue;
-        }
-        if let Some(colon) = line.find(':') {
-            let name = line[..colon].trim().to_lowercase();
-            if name == target_name {
-                if replaced {
-                    lines.remove(i);
-                    continue;
-                }
-                lines[i] = preset_entry.clone();
-                replaced = true;
-            }
-        }
-        i += 1;
-    }
-
-    if !replaced {
-        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
-            lines.push(String::new());
-        }
-        lines.push(preset_entry);
-    }
-
-    write_modlist_lines(&path, &lines)?;
+    let path = modlist_settings_path(&user_dir);
+    let mut settings = ModlistSettings::read(&path)?;
+    settings.upsert(&preset_name, &mod_ids);
+    settings.write(&path)?;
     Ok(serde_json::json!({
         "updated": true,
         "path": path.to_string_lossy().to_string(),
@@ -209,64 +454,152 @@ pub fn remove_pz_modlist_settings_preset(
     user_dir: String,
     preset_name: String,
 ) -> Result<JsonValue, String> {
-    let path = Path::new(&user_dir)
-        .join("Lua")
-        .join("pz_modlist_settings.cfg");
-    if !path.exists() {
-        return Ok(serde_json::json!({
-            "updated": false,
-            "path": path.to_string_lossy().to_string(),
-        }));
+    let path = modlist_settings_path(&user_dir);
+    let mut settings = ModlistSettings::read(&path)?;
+    let updated = settings.remove(&preset_name);
+    if updated {
+        settings.write(&path)?;
     }
-    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let normalized = content.replace("\r\n", "\n");
-    let mut lines: Vec<String> = normalized.lines().map(|l| l.to_string()).collect();
-    let target = preset_name.trim().to_lowercase();
-    let mut updated = false;
-    let mut i = 0;
-    while i < lines.len() {
-        let trimmed = lines[i].trim();
-        if trimmed.is_empty() {
-            i += 1;
-            continue;
-        }
-        if trimmed.starts_with('!') && trimmed.ends_with(':') {
-            i += 1;
-            continue;
-        }
-        if let Some(colon) = trimmed.find(':') {
-            let name = trimmed[..colon].trim().to_lowercase();
-            if name == target {
-                lines.remove(i);
-                updated = true;
-                continue;
-            }
-        }
-        i += 1;
+    Ok(serde_json::json!({
+        "updated": updated,
+        "path": path.to_string_lossy().to_string(),
+    }))
+}
+
+#[tauri::command]
+pub fn read_pz_modlist_settings(user_dir: String) -> Result<ModlistSettingsFile, String> {
+    let _timer = scoped_timer("read_pz_modlist_settings");
+    let path = modlist_settings_path(&user_dir);
+    Ok(ModlistSettings::read(&path)?.into_file(&path))
+}
+
+#[tauri::command]
+pub fn rename_pz_modlist_settings_preset(
+    user_dir: String,
+    preset_name: String,
+    new_name: String,
+) -> Result<ModlistSettingsFile, String> {
+    let _timer = scoped_timer("rename_pz_modlist_settings_preset");
+    update_modlist_settings(&user_dir, |settings| {
+        settings.rename(&preset_name, &new_name)
+    })
+}
+
+#[tauri::command]
+pub fn duplicate_pz_modlist_settings_preset(
+    user_dir: String,
+    preset_name: String,
+    new_name: String,
+) -> Result<ModlistSettingsFile, String> {
+    let _timer = scoped_timer("duplicate_pz_modlist_settings_preset");
+    update_modlist_settings(&user_dir, |settings| {
+        settings.duplicate(&preset_name, &new_name)
+    })
+}
+
+#[tauri::command]
+pub fn reorder_pz_modlist_settings_presets(
+    user_dir: String,
+    preset_names: Vec<String>,
+) -> Result<ModlistSettingsFile, String> {
+    let _timer = scoped_timer("reorder_pz_modlist_settings_presets");
+    update_modlist_settings(&user_dir, |settings| settings.reorder(&preset_names))
+}
+
+/// Saves a server's `Mods=` list as a preset, replacing one of the same name.
+#[tauri::command]
+pub fn import_pz_modlist_preset_from_server(
+    user_dir: String,
+    server_name: String,
+    preset_name: String,
+) -> Result<ModlistSettingsFile, String> {
+    let _timer = scoped_timer("import_pz_modlist_preset_from_server");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SERVER_INI_FILE);
+    if !path.is_file() {
+        return Err(format!("Server \"{name}\" does not exist."));
     }
+    let ini = ServerIni::read(&path)?;
+    let mod_ids: Vec<String> = ini
+        .get("Mods")
+        .unwrap_or_default()
+        .split(';')
+        .map(str::to_string)
+        .collect();
+    import_preset(&user_dir, &preset_name, &mod_ids)
+}
 
-    if !updated {
-        return Ok(serde_json::json!({
-            "updated": false,
-            "path": path.to_string_lossy().to_string(),
-        }));
+/// Saves the mods enabled in a save's `mods.txt` as a preset.
+#[tauri::command]
+pub fn import_pz_modlist_preset_from_save(
+    user_dir: String,
+    save_rel_path: String,
+    preset_name: String,
+) -> Result<ModlistSettingsFile, String> {
+    let _timer = scoped_timer("import_pz_modlist_preset_from_save");
+    let path = save_mods_txt_path(&user_dir, &save_rel_path)?;
+    let (file, exists) = read_mods_txt(&path)?;
+    if !exists {
+        return Err(format!("{} does not exist.", path.display()));
     }
+    import_preset(&user_dir, &preset_name, &file.mods())
+}
 
-    write_modlist_lines(&path, &lines)?;
-    Ok(serde_json::json!({
-        "updated": true,
-        "path": path.to_string_lossy().to_string(),
-    }))
+fn import_preset(
+    user_dir: &str,
+    preset_name: &str,
+    mod_ids: &[String],
+) -> Result<ModlistSettingsFile, String> {
+    let name = preset_name.trim();
+    if name.is_empty() || name.starts_with('!') || name.contains(':') {
+        return Err(format!("\"{name}\" is not a valid preset name."));
+    }
+    update_modlist_settings(user_dir, |settings| {
+        settings.upsert(name, mod_ids);
+        Ok(())
+    })
 }
 
-fn write_modlist_lines(path: &Path, lines: &[String]) -> Result<(), String> {
-    let rewritten = lines
-        .iter()
-        .map(|line| line.trim_end())
-        .filter(|line| !line.trim().is_empty())
-        .collect::<Vec<_>>()
-        .join("\n");
-    ensure_parent_dir(path)?;
-    fs::write(path, rewritten).map_err(|e| e.to_string())?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_presets_and_keeps_header_entries() {
+        let mut settings = ModlistSettings::parse(
+            "\u{feff}!fav!:\\Core;\\Map;\r\n!sort:\\Map;\r\n\r\nAlpha:\\Core;\\Map;\r\nbeta:\\Other;\r\n",
+        );
+        assert_eq!(settings.headers()[0].name, "!fav!");
+        assert_eq!(settings.presets()[0].mod_ids, ["Core", "Map"]);
+
+        settings
+            .rename("BETA", "Gamma")
+            .expect("preset should be renamed");
+        assert!(settings.rename("Gamma", "alpha").is_err());
+        settings
+            .duplicate("Alpha", "Alpha copy")
+            .expect("preset should be duplicated");
+        settings
+            .reorder(&[
+                "gamma".to_string(),
+                "Alpha".to_string(),
+                "Alpha copy".to_string(),
+            ])
+            .expect("presets should be reordered");
+        assert!(settings.reorder(&["Alpha".to_string()]).is_err());
+        assert!(settings.remove_mod("map"));
+        settings.upsert("Imported", &["\\Core".to_string(), "Core".to_string()]);
+
+        assert_eq!(
+            settings.render(),
+            "\u{feff}!fav!:\\Core;\r\n!sort:\\Map;\r\n\r\nGamma:\\Other;\r\nAlpha:\\Core;\r\nAlpha copy:\\Core;\r\nImported:\\Core;\r\n"
+        );
+    }
+
+    #[test]
+    fn writes_an_untouched_file_back_byte_for_byte() {
+        let text = "!fav!:\n\nAlpha:\\Core;\n\nbeta:\\Other;";
+        assert_eq!(ModlistSettings::parse(text).render(), text);
+        assert_eq!(ModlistSettings::parse("").render(), "");
+    }
 }
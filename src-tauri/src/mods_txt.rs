@@ -0,0 +1,256 @@
+use crate::mod_scanner::normalize_mod_ref;
+use crate::timing::scoped_timer;
+use crate::utils::{read_optional_text, safe_relative_path};
+use serde::Serialize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// What the game writes for a save that has never had mods.
+const EMPTY_MODS_TXT: &str = "mods\n{\n}\n\nmaps\n{\n}\n";
+
+/// A save's `mods.txt`, kept as its original lines so that comments, the
+/// `VERSION` line and anything else the game adds survive a rewrite.  Only the
+/// `mod = ...` and `map = ...` entries are ever regenerated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ModsTxt {
+    lines: Vec<String>,
+    /// The file started with a UTF-8 byte order mark.
+    bom: bool,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+/// Line ranges of one `name { ... }` block: the lines between the braces.
+struct Section {
+    body: Range<usize>,
+}
+
+fn entry_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let (name, value) = line.trim().split_once('=')?;
+    if !name.trim().eq_ignore_ascii_case(key) {
+        return None;
+    }
+    let value = value.trim();
+    // One trailing comma ends the entry; map names keep their own commas.
+    Some(value.strip_suffix(',').unwrap_or(value).trim())
+}
+
+impl ModsTxt {
+    pub(crate) fn parse(text: &str) -> Self {
+        let (bom, text) = match text.strip_prefix('\u{feff}') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        Self {
+            lines: text.lines().map(str::to_string).collect(),
+            bom,
+            crlf: text.contains("\r\n"),
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+        }
+    }
+
+    pub(crate) fn empty() -> Self {
+        Self::parse(EMPTY_MODS_TXT)
+    }
+
+    pub(crate) fn render(&self) -> String {
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        let mut out = if self.bom {
+            "\u{feff}".to_string()
+        } else {
+            String::new()
+        };
+        out.push_str(&self.lines.join(newline));
+        if self.trailing_newline && !self.lines.is_empty() {
+            out.push_str(newline);
+        }
+        out
+    }
+
+    /// Finds `name` followed by `{`, on the same line or the next one.
+    fn section(&self, name: &str) -> Option<Section> {
+        let mut index = 0;
+        while index < self.lines.len() {
+            let trimmed = self.lines[index].trim();
+            let rest = trimmed
+                .get(..name.len())
+                .filter(|head| head.eq_ignore_ascii_case(name))
+                .map(|_| trimmed[name.len()..].trim());
+            let open = match rest {
+                Some("{") => Some(index),
+                Some("")
+                    if self
+                        .lines
+                        .get(index + 1)
+                        .is_some_and(|next| next.trim() == "{") =>
+                {
+                    Some(index + 1)
+                }
+                _ => None,
+            };
+            if let Some(open) = open {
+                let close = (open + 1..self.lines.len())
+                    .find(|line| self.lines[*line].trim().starts_with('}'))
+                    .unwrap_or(self.lines.len());
+                return Some(Section {
+                    body: open + 1..close,
+                });
+            }
+            index += 1;
+        }
+        None
+    }
+
+    fn values(&self, section: &str, key: &str) -> Vec<String> {
+        let Some(section) = self.section(section) else {
+            return Vec::new();
+        };
+        self.lines[section.body]
+            .iter()
+            .filter_map(|line| entry_value(line, key))
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub(crate) fn mods(&self) -> Vec<String> {
+        self.values("mods", "mod")
+            .iter()
+            .map(|value| normalize_mod_ref(value))
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+
+    pub(crate) fn maps(&self) -> Vec<String> {
+        self.values("maps", "map")
+    }
+
+    /// Replaces the `key` entries of `section` with `values`, written in the
+    /// style of the first existing entry.  Other lines stay where they were;
+    /// a missing section is appended.
+    fn set_values(&mut self, section: &str, key: &str, values: &[String]) {
+        let Some(found) = self.section(section) else {
+            if self
+                .lines
+                .last()
+                .is_some_and(|line| !line.trim().is_empty())
+            {
+                self.lines.push(String::new());
+            }
+            self.lines.push(section.to_string());
+            self.lines.push("{".to_string());
+            self.lines
+                .extend(values.iter().map(|value| format!("    {key}={value},")));
+            self.lines.push("}".to_string());
+            return;
+        };
+        let body = found.body;
+        let existing: Vec<usize> = body
+            .clone()
+            .filter(|index| entry_value(&self.lines[*index], key).is_some())
+            .collect();
+        let template = existing.first().map(|index| self.lines[*index].clone());
+        let (indent, separator) = match &template {
+            Some(line) => {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                let separator = if line.contains(" = ") { " = " } else { "=" };
+                (indent.to_string(), separator)
+            }
+            None => ("    ".to_string(), "="),
+        };
+        let insert_at = existing.first().copied().unwrap_or(body.end);
+        for index in existing.iter().rev() {
+            self.lines.remove(*index);
+        }
+        self.lines.splice(
+            insert_at..insert_at,
+            values
+                .iter()
+                .map(|value| format!("{indent}{key}{separator}{value},")),
+        );
+    }
+
+    /// Sets the enabled mods, keeping the maps list and everything else.
+    pub(crate) fn set_mods(&mut self, mod_ids: &[String]) {
+        let values: Vec<String> = mod_ids
+            .iter()
+            .map(|id| normalize_mod_ref(id))
+            .filter(|id| !id.is_empty())
+            .map(|id| format!("\\{id}"))
+            .collect();
+        self.set_values("mods", "mod", &values);
+    }
+}
+
+pub(crate) fn save_mods_txt_path(user_dir: &str, save_rel_path: &str) -> Result<PathBuf, String> {
+    Ok(safe_relative_path(&Path::new(user_dir).join("Saves"), save_rel_path)?.join("mods.txt"))
+}
+
+/// Reads a save's `mods.txt`, falling back to an empty one when it is missing.
+pub(crate) fn read_mods_txt(path: &Path) -> Result<(ModsTxt, bool), String> {
+    Ok(match read_optional_text(path)? {
+        Some(text) => (ModsTxt::parse(&text), true),
+        None => (ModsTxt::empty(), false),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveModsFile {
+    pub path: String,
+    pub exists: bool,
+    pub mods: Vec<String>,
+    pub maps: Vec<String>,
+}
+
+#[tauri::command]
+pub fn read_save_mods(user_dir: String, save_rel_path: String) -> Result<SaveModsFile, String> {
+    let _timer = scoped_timer("read_save_mods");
+    let path = save_mods_txt_path(&user_dir, &save_rel_path)?;
+    let (file, exists) = read_mods_txt(&path)?;
+    Ok(SaveModsFile {
+        path: path.to_string_lossy().to_string(),
+        exists,
+        mods: file.mods(),
+        maps: file.maps(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_mods_and_keeps_maps_and_unknown_lines() {
+        let text = "VERSION = 1,\r\n\r\nmods\r\n{\r\n    -- pinned\r\n    mod = \\Core,\r\n    mod = \\Old,\r\n    other = kept,\r\n}\r\n\r\nmaps\r\n{\r\n    map = Town,\r\n    map = Muldraugh, KY,\r\n}\r\n";
+        let mut file = ModsTxt::parse(text);
+        assert_eq!(file.render(), text);
+        assert_eq!(file.mods(), ["Core", "Old"]);
+        assert_eq!(file.maps(), ["Town", "Muldraugh, KY"]);
+
+        file.set_mods(&["Core".to_string(), "\\New".to_string()]);
+        assert_eq!(
+            file.render(),
+            "VERSION = 1,\r\n\r\nmods\r\n{\r\n    -- pinned\r\n    mod = \\Core,\r\n    mod = \\New,\r\n    other = kept,\r\n}\r\n\r\nmaps\r\n{\r\n    map = Town,\r\n    map = Muldraugh, KY,\r\n}\r\n"
+        );
+
+        let mut inline = ModsTxt::parse("mods {\n}\n");
+        inline.set_mods(&["A".to_string()]);
+        assert_eq!(inline.render(), "mods {\n    mod=\\A,\n}\n");
+        let mut without_mods = ModsTxt::parse("maps\n{\n    map=Muldraugh, KY,\n}");
+        without_mods.set_mods(&["A".to_string()]);
+        assert_eq!(
+            without_mods.render(),
+            "maps\n{\n    map=Muldraugh, KY,\n}\n\nmods\n{\n    mod=\\A,\n}"
+        );
+        assert_eq!(without_mods.maps(), ["Muldraugh, KY"]);
+    }
+
+    #[test]
+    fn keeps_a_leading_byte_order_mark() {
+        let mut file = ModsTxt::parse("\u{feff}mods\n{\n    mod = \\Old,\n}\n");
+        assert_eq!(file.mods(), ["Old"]);
+        file.set_mods(&["New".to_string()]);
+        assert_eq!(file.render(), "\u{feff}mods\n{\n    mod = \\New,\n}\n");
+    }
+}
@@ -1,8 +1,12 @@
+use crate::loadout::{LoadoutAnalysis, LoadoutMod, analyze_loadout};
+use crate::map_mods::{MapLinePlan, plan_map_line};
+use crate::models::ModSummary;
+use crate::mods_txt::{read_mods_txt, save_mods_txt_path};
+use crate::server_ini::ServerIni;
 use crate::timing::scoped_timer;
-use crate::utils::{ensure_parent_dir, safe_relative_path, sanitize_filename_component};
+use crate::utils::{sanitize_filename_component, write_atomically};
 use serde_json::Value as JsonValue;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 #[tauri::command]
@@ -30,29 +34,51 @@ pub fn list_save_mods_files(user_dir: String) -> Result<Vec<String>, String> {
     Ok(out)
 }
 #[tauri::command]
-pub fn analyze_mod_loadout(mods: Vec<JsonValue>) -> Result<JsonValue, String> {
+pub fn analyze_mod_loadout(
+    mods: Vec<LoadoutMod>,
+    installed: Option<Vec<ModSummary>>,
+) -> Result<LoadoutAnalysis, String> {
     let _timer = scoped_timer("analyze_mod_loadout");
-    let ordered: Vec<String> = mods
-        .iter()
-        .filter_map(|m| m.get("modId").and_then(|v| v.as_str()))
-        .map(|v| v.to_string())
-        .collect();
-    let result = serde_json::json!({
-        "orderedModIds": ordered,
-        "missingModIds": [],
-        "missingWorkshopIds": [],
-        "cycles": [],
-        "incompatiblePairs": [],
-        "conflicts": [],
-        "warnings": [],
-    });
-    Ok(result)
+    Ok(analyze_loadout(
+        &mods,
+        installed.as_deref().unwrap_or_default(),
+    ))
 }
 
-fn build_server_ini(mod_ids: &[String], workshop_ids: &[String]) -> String {
-    let mods = mod_ids.join(";");
-    let workshops = workshop_ids.join(";");
-    format!("Mods={}\nWorkshopItems={}\n", mods, workshops)
+/// Loads `<preset_name>.ini` and updates only the keys a loadout owns, leaving
+/// every other setting, comment and unknown key where it was.
+fn merge_server_ini(
+    target: &Path,
+    mod_ids: &[String],
+    workshop_ids: &[String],
+    map: Option<&str>,
+) -> Result<(ServerIni, ServerIni), String> {
+    let existing = ServerIni::read(target)?;
+    let mut merged = existing.clone();
+    merged.set("Mods", &mod_ids.join(";"));
+    merged.set("WorkshopItems", &workshop_ids.join(";"));
+    if let Some(map) = map.map(str::trim).filter(|map| !map.is_empty()) {
+        merged.set("Map", map);
+    }
+    Ok((existing, merged))
+}
+
+/// An explicit `map` wins; otherwise `Map=` is derived from the loadout when
+/// one is given and left alone when not, or when none of its mods has a map.
+fn preset_map_line(map: Option<String>, mods: Option<&[LoadoutMod]>) -> Option<MapLinePlan> {
+    match map.map(|map| map.trim().to_string()) {
+        Some(map) if !map.is_empty() => Some(MapLinePlan {
+            maps: map.split(';').map(str::to_string).collect(),
+            value: Some(map),
+            warnings: Vec::new(),
+        }),
+        _ => mods.map(plan_map_line),
+    }
+}
+
+fn server_preset_path(zomboid_user_dir: &str, preset_name: &str) -> PathBuf {
+    let file_name = format!("{}.ini", sanitize_filename_component(preset_name));
+    Path::new(zomboid_user_dir).join("Server").join(file_name)
 }
 
 #[tauri::command]
@@ -61,15 +87,26 @@ pub fn plan_server_preset(
     preset_name: String,
     mod_ids: Vec<String>,
     workshop_ids: Vec<String>,
+    map: Option<String>,
+    mods: Option<Vec<LoadoutMod>>,
 ) -> Result<JsonValue, String> {
     let _timer = scoped_timer("plan_server_preset");
-    let file_name = format!("{}.ini", sanitize_filename_component(&preset_name));
-    let target = Path::new(&zomboid_user_dir).join("Server").join(file_name);
-    let ini_preview = build_server_ini(&mod_ids, &workshop_ids);
+    let target = server_preset_path(&zomboid_user_dir, &preset_name);
+    let map_line = preset_map_line(map, mods.as_deref());
+    let (existing, merged) = merge_server_ini(
+        &target,
+        &mod_ids,
+        &workshop_ids,
+        map_line.as_ref().and_then(|line| line.value.as_deref()),
+    )?;
     Ok(serde_json::json!({
         "presetName": preset_name,
         "targetPath": target.to_string_lossy().to_string(),
-        "iniPreview": ini_preview,
+        "iniPreview": merged.render(),
+        "exists": target.is_file(),
+        "changes": existing.diff(&merged),
+        "map": map_line.as_ref().and_then(|line| line.value.clone()),
+        "mapWarnings": map_line.map(|line| line.warnings).unwrap_or_default(),
     }))
 }
 
@@ -79,24 +116,28 @@ pub fn write_server_preset(
     preset_name: String,
     mod_ids: Vec<String>,
     workshop_ids: Vec<String>,
-) -> Result<(), String> {
+    map: Option<String>,
+    mods: Option<Vec<LoadoutMod>>,
+) -> Result<Vec<String>, String> {
     let _timer = scoped_timer("write_server_preset");
-    let file_name = format!("{}.ini", sanitize_filename_component(&preset_name));
-    let target = Path::new(&zomboid_user_dir).join("Server").join(file_name);
-    ensure_parent_dir(&target)?;
-    let ini = build_server_ini(&mod_ids, &workshop_ids);
-    fs::write(&target, ini).map_err(|e| e.to_string())?;
-    Ok(())
+    let target = server_preset_path(&zomboid_user_dir, &preset_name);
+    let map_line = preset_map_line(map, mods.as_deref());
+    let (_, merged) = merge_server_ini(
+        &target,
+        &mod_ids,
+        &workshop_ids,
+        map_line.as_ref().and_then(|line| line.value.as_deref()),
+    )?;
+    write_atomically(&target, &merged.to_bytes())?;
+    Ok(map_line.map(|line| line.warnings).unwrap_or_default())
 }
 
-fn build_mods_txt(mod_ids: &[String]) -> String {
-    let mut out = String::new();
-    out.push_str("mods\n{\n");
-    for id in mod_ids {
-        out.push_str(&format!("    mod=\\{},\n", id));
-    }
-    out.push_str("}\n\nmaps\n{\n}\n");
-    out
+/// Renders the save's `mods.txt` with `mod_ids` enabled, keeping its maps
+/// list and any other content.
+fn build_mods_txt(target: &Path, mod_ids: &[String]) -> Result<String, String> {
+    let (mut file, _) = read_mods_txt(target)?;
+    file.set_mods(mod_ids);
+    Ok(file.render())
 }
 
 #[tauri::command]
@@ -107,9 +148,8 @@ pub fn plan_singleplayer_save_mods(
     _workshop_ids: Vec<String>,
 ) -> Result<JsonValue, String> {
     let _timer = scoped_timer("plan_singleplayer_save_mods");
-    let target = safe_relative_path(&Path::new(&zomboid_user_dir).join("Saves"), &save_rel_path)?
-        .join("mods.txt");
-    let preview = build_mods_txt(&mod_ids);
+    let target = save_mods_txt_path(&zomboid_user_dir, &save_rel_path)?;
+    let preview = build_mods_txt(&target, &mod_ids)?;
     Ok(serde_json::json!({
         "presetName": format!("Active Mods ({})", save_rel_path),
         "targetPath": target.to_string_lossy().to_string(),
@@ -125,10 +165,69 @@ pub fn write_singleplayer_save_mods(
     _workshop_ids: Vec<String>,
 ) -> Result<(), String> {
     let _timer = scoped_timer("write_singleplayer_save_mods");
-    let target = safe_relative_path(&Path::new(&zomboid_user_dir).join("Saves"), &save_rel_path)?
-        .join("mods.txt");
-    ensure_parent_dir(&target)?;
-    let content = build_mods_txt(&mod_ids);
-    fs::write(&target, content).map_err(|e| e.to_string())?;
-    Ok(())
+    let target = save_mods_txt_path(&zomboid_user_dir, &save_rel_path)?;
+    let content = build_mods_txt(&target, &mod_ids)?;
+    write_atomically(&target, content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+    use serde_json::json;
+
+    #[test]
+    fn analyzes_a_loadout_against_the_installed_scan() {
+        let mods: Vec<LoadoutMod> = serde_json::from_value(json!([
+            { "modId": "NeedsLib", "workshopId": "1", "requires": ["\\ModLib"] }
+        ]))
+        .expect("loadout mods should deserialize");
+        let installed: Vec<ModSummary> = serde_json::from_value(json!([{
+            "id": "lib",
+            "name": "Mod Lib",
+            "mod_id": "ModLib",
+            "workshop_id": "2169435993",
+            "version_min": null,
+            "version_max": null,
+            "compatibility": null,
+        }]))
+        .expect("installed mods should deserialize");
+
+        let analysis = serde_json::to_value(
+            analyze_mod_loadout(mods, Some(installed)).expect("loadout should be analyzed"),
+        )
+        .expect("analysis should serialize");
+        assert_eq!(analysis["missingModIds"], json!(["ModLib"]));
+        assert_eq!(
+            analysis["missingDependencies"][0]["workshopIds"],
+            json!(["2169435993"])
+        );
+    }
+
+    #[test]
+    fn keeps_the_map_line_when_no_loadout_mod_provides_a_map() {
+        let root = TestDir::new("pz-preset-map-test");
+        root.write("Server/servertest.ini", "Mods=\nMap=Town;Muldraugh, KY\n");
+        let mods = vec![LoadoutMod {
+            mod_id: Some("NoMaps".to_string()),
+            ..LoadoutMod::default()
+        }];
+
+        let plan = plan_server_preset(
+            root.to_string_lossy().to_string(),
+            "servertest".to_string(),
+            vec!["NoMaps".to_string()],
+            Vec::new(),
+            None,
+            Some(mods),
+        )
+        .expect("preset should be planned");
+        assert_eq!(plan["map"], JsonValue::Null);
+        assert!(
+            plan["iniPreview"]
+                .as_str()
+                .expect("preview should be text")
+                .contains("Map=Town;Muldraugh, KY")
+        );
+    }
 }
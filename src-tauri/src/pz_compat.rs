@@ -26,11 +26,20 @@ pub const VANILLA_MAPS: &[&str] = &[
     "West Point, KY",
 ];
 
+/// The map folder holding every vanilla cell; the other `VANILLA_MAPS` names
+/// only select spawn regions.
+pub const VANILLA_WORLD_MAP: &str = "Muldraugh, KY";
+
+pub const SERVER_INI_FILE: &str = "{name}.ini";
+pub const SANDBOX_VARS_FILE: &str = "{name}_SandboxVars.lua";
+pub const SPAWN_REGIONS_FILE: &str = "{name}_spawnregions.lua";
+pub const SPAWN_POINTS_FILE: &str = "{name}_spawnpoints.lua";
+
 pub const SERVER_CONFIG_FILES: &[&str] = &[
-    "{name}.ini",
-    "{name}_SandboxVars.lua",
-    "{name}_spawnregions.lua",
-    "{name}_spawnpoints.lua",
+    SERVER_INI_FILE,
+    SANDBOX_VARS_FILE,
+    SPAWN_REGIONS_FILE,
+    SPAWN_POINTS_FILE,
 ];
 
 #[derive(Debug, Serialize)]
@@ -81,14 +90,31 @@ pub(crate) fn validate_server_name(name: &str) -> Result<&str, String> {
     Ok(trimmed)
 }
 
+/// Path of one `SERVER_CONFIG_FILES` entry for `server_name`.
+pub(crate) fn server_config_path(user_dir: &str, server_name: &str, file: &str) -> PathBuf {
+    server_dir(user_dir).join(file.replace("{name}", server_name))
+}
+
+/// The server's world, `Saves/Multiplayer/<name>`.
+pub(crate) fn server_world_dir(user_dir: &str, server_name: &str) -> PathBuf {
+    Path::new(user_dir)
+        .join("Saves")
+        .join("Multiplayer")
+        .join(server_name)
+}
+
+/// The server's account database, `db/<name>.db`.
+pub(crate) fn server_db_path(user_dir: &str, server_name: &str) -> PathBuf {
+    Path::new(user_dir)
+        .join("db")
+        .join(format!("{server_name}.db"))
+}
+
 pub(crate) fn server_config_paths(user_dir: &str, server_name: &str) -> Vec<PathBuf> {
-    let base = server_dir(user_dir);
-    vec![
-        base.join(format!("{server_name}.ini")),
-        base.join(format!("{server_name}_SandboxVars.lua")),
-        base.join(format!("{server_name}_spawnregions.lua")),
-        base.join(format!("{server_name}_spawnpoints.lua")),
-    ]
+    SERVER_CONFIG_FILES
+        .iter()
+        .map(|file| server_config_path(user_dir, server_name, file))
+        .collect()
 }
 
 #[cfg(test)]
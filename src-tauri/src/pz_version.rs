@@ -0,0 +1,262 @@
+use crate::models::ModSummary;
+use crate::pz_compat::{GAME_VERSION, MOD_BREAK_VERSION};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A Project Zomboid build number such as `42`, `42.13` or `41.78.16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct PzVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl PzVersion {
+    /// Parses a dotted numeric version with one to three components.
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        let mut parts = raw.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map(str::parse).transpose().ok()?.unwrap_or(0);
+        let patch = parts.next().map(str::parse).transpose().ok()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    /// Parses the free-form build numbers mod authors write in `versionMin` and
+    /// `versionMax`, such as `B42`, `build 41.78.16`, `v42.13` or
+    /// `42.0.0-unstable.1234`, and returns how many components were written.
+    /// Components past the patch number are ignored.
+    pub(crate) fn parse_loose(raw: &str) -> Option<(Self, usize)> {
+        let trimmed = raw.trim().trim_matches(|c| c == '"' || c == '\'');
+        let start = trimmed.find(|c: char| c.is_ascii_digit())?;
+        let prefix = trimmed[..start]
+            .trim()
+            .trim_end_matches(['-', '_', ':'])
+            .trim()
+            .to_ascii_lowercase();
+        if !matches!(prefix.as_str(), "" | "b" | "v" | "build" | "version") {
+            return None;
+        }
+        let numeric: String = trimmed[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let components: Vec<&str> = numeric
+            .split('.')
+            .take_while(|part| !part.is_empty())
+            .take(3)
+            .collect();
+        Some((Self::parse(&components.join("."))?, components.len()))
+    }
+
+    /// This version with the components after the first `components` zeroed,
+    /// so it compares equal to every build a shorter version covers.
+    pub(crate) fn truncated(self, components: usize) -> Self {
+        match components {
+            0 | 1 => Self {
+                minor: 0,
+                patch: 0,
+                ..self
+            },
+            2 => Self { patch: 0, ..self },
+            _ => self,
+        }
+    }
+}
+
+impl fmt::Display for PzVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch > 0 {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        } else {
+            write!(f, "{}.{}", self.major, self.minor)
+        }
+    }
+}
+
+pub(crate) fn game_version() -> PzVersion {
+    PzVersion::parse(GAME_VERSION).expect("GAME_VERSION must be a dotted build number")
+}
+
+pub(crate) fn mod_break_version() -> PzVersion {
+    PzVersion::parse(MOD_BREAK_VERSION).expect("MOD_BREAK_VERSION must be a dotted build number")
+}
+
+/// Whether a mod can run on the build this application targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionCompatibility {
+    Compatible,
+    /// Made for a build before `MOD_BREAK_VERSION` or capped below `GAME_VERSION`.
+    TooOld,
+    /// Declares a minimum build newer than `GAME_VERSION`.
+    RequiresNewerBuild,
+    Unknown,
+}
+
+fn declared_version(raw: &Option<String>) -> Result<Option<(PzVersion, usize)>, ()> {
+    match raw.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => PzVersion::parse_loose(value).map(Some).ok_or(()),
+        None => Ok(None),
+    }
+}
+
+/// Judges a scanned mod against `GAME_VERSION` and `MOD_BREAK_VERSION`.  The
+/// declared `versionMin`/`versionMax` win, compared only as far as they are
+/// written so `versionMax=42` covers every 42.x build; without them the B42
+/// folder layout recorded by the scanner decides.
+pub(crate) fn compatibility_verdict(summary: &ModSummary) -> VersionCompatibility {
+    let game = game_version();
+    let (Ok(min), Ok(max)) = (
+        declared_version(&summary.version_min),
+        declared_version(&summary.version_max),
+    ) else {
+        return VersionCompatibility::Unknown;
+    };
+    if let Some((max, components)) = max {
+        if max < game.truncated(components) {
+            return VersionCompatibility::TooOld;
+        }
+    }
+    if let Some((min, components)) = min {
+        if min > game.truncated(components) {
+            return VersionCompatibility::RequiresNewerBuild;
+        }
+    }
+    if summary.selected_version_folder.is_some() {
+        return VersionCompatibility::Compatible;
+    }
+    if summary.no_usable_folder == Some(true) {
+        let newest_folder = summary
+            .version_folders
+            .iter()
+            .flatten()
+            .filter_map(|folder| PzVersion::parse(folder))
+            .max();
+        return match newest_folder {
+            Some(folder) if folder > game => VersionCompatibility::RequiresNewerBuild,
+            _ => VersionCompatibility::TooOld,
+        };
+    }
+    match (min, max) {
+        (None, None) => VersionCompatibility::Unknown,
+        (Some((min, _)), None) if min < mod_break_version() => VersionCompatibility::TooOld,
+        _ => VersionCompatibility::Compatible,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_orders_dotted_build_numbers() {
+        let forty_two = PzVersion::parse("42").expect("major-only version should parse");
+        let thirteen = PzVersion::parse("42.13").expect("minor version should parse");
+        let legacy = PzVersion::parse("41.78.16").expect("patch version should parse");
+        let game = game_version();
+        let next_minor = PzVersion {
+            minor: game.minor + 1,
+            patch: 0,
+            ..game
+        };
+        let next_major = PzVersion::parse(&(game.major + 1).to_string())
+            .expect("next major version should parse");
+        assert!(legacy < forty_two && forty_two < thirteen);
+        assert!(game < next_minor && next_minor < next_major);
+        assert_eq!(forty_two, mod_break_version());
+        assert_eq!(legacy.to_string(), "41.78.16");
+        assert_eq!(PzVersion::parse("common"), None);
+        assert_eq!(PzVersion::parse("42.1.2.3"), None);
+    }
+
+    #[test]
+    fn parses_free_form_mod_info_versions() {
+        let expected = PzVersion::parse("41.78.16").map(|version| (version, 3));
+        assert_eq!(PzVersion::parse_loose("41.78.16"), expected);
+        assert_eq!(PzVersion::parse_loose("build 41.78.16.2"), expected);
+        assert_eq!(
+            PzVersion::parse_loose("B42"),
+            PzVersion::parse("42").map(|version| (version, 1))
+        );
+        assert_eq!(
+            PzVersion::parse_loose("v42.13.0-unstable.27000"),
+            PzVersion::parse("42.13").map(|version| (version, 3))
+        );
+        assert_eq!(PzVersion::parse_loose("latest"), None);
+        assert_eq!(PzVersion::parse_loose("Linux 42"), None);
+    }
+
+    #[test]
+    fn judges_mods_against_the_targeted_build() {
+        let game = game_version();
+        let oldest = mod_break_version().to_string();
+        let next_minor = format!("{}.{}", game.major, game.minor + 1);
+        let summary = |min: Option<&str>, max: Option<&str>| ModSummary {
+            version_min: min.map(String::from),
+            version_max: max.map(String::from),
+            ..ModSummary::default()
+        };
+        assert_eq!(
+            compatibility_verdict(&summary(Some(&oldest), Some(&game.to_string()))),
+            VersionCompatibility::Compatible
+        );
+        assert_eq!(
+            compatibility_verdict(&summary(Some("41.60"), Some("41.78.16"))),
+            VersionCompatibility::TooOld
+        );
+        let major = game.major.to_string();
+        assert_eq!(
+            compatibility_verdict(&summary(Some(&major), Some(&major))),
+            VersionCompatibility::Compatible
+        );
+        assert_eq!(
+            compatibility_verdict(&summary(
+                None,
+                Some(&format!("{}.{}", game.major, game.minor))
+            )),
+            VersionCompatibility::Compatible
+        );
+        assert_eq!(
+            compatibility_verdict(&summary(None, Some(&(game.major - 1).to_string()))),
+            VersionCompatibility::TooOld
+        );
+        assert_eq!(
+            compatibility_verdict(&summary(Some(&next_minor), None)),
+            VersionCompatibility::RequiresNewerBuild
+        );
+        assert_eq!(
+            compatibility_verdict(&summary(Some("41.78"), None)),
+            VersionCompatibility::TooOld
+        );
+        assert_eq!(
+            compatibility_verdict(&summary(None, None)),
+            VersionCompatibility::Unknown
+        );
+        assert_eq!(
+            compatibility_verdict(&summary(Some("soon"), None)),
+            VersionCompatibility::Unknown
+        );
+
+        let legacy = ModSummary {
+            no_usable_folder: Some(true),
+            ..ModSummary::default()
+        };
+        assert_eq!(compatibility_verdict(&legacy), VersionCompatibility::TooOld);
+        let future = ModSummary {
+            no_usable_folder: Some(true),
+            version_folders: Some(vec!["common".to_string(), next_minor.clone()]),
+            ..ModSummary::default()
+        };
+        assert_eq!(
+            compatibility_verdict(&future),
+            VersionCompatibility::RequiresNewerBuild
+        );
+    }
+}
@@ -2199,7 +2199,10 @@ fn collect_source_paths(path: &Path, paths: &mut Vec<PathBuf>) {
     }
 }
 
-fn scan_headers(path: &Path, encoding: &str) -> RenderResult<HashMap<(i32, i32), LotHeader>> {
+pub(crate) fn scan_headers(
+    path: &Path,
+    encoding: &str,
+) -> RenderResult<HashMap<(i32, i32), LotHeader>> {
     let mut headers = HashMap::new();
     for entry in fs::read_dir(path).map_err(|error| format!("{}: {error}", path.display()))? {
         let path = entry.map_err(|error| error.to_string())?.path();
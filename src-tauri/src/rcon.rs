@@ -0,0 +1,484 @@
+use crate::pz_compat::{SERVER_INI_FILE, server_config_path, validate_server_name};
+use crate::server_ini::ServerIni;
+use crate::timing::scoped_timer;
+use serde::Serialize;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+/// Largest packet the protocol allows a client to send, size field excluded.
+const MAX_SEND_SIZE: usize = 4096 + 10;
+/// Project Zomboid answers `help` or a long `players` list in one packet far
+/// over the protocol limit, so received packets only get a sanity cap.
+const MAX_RECEIVE_SIZE: usize = 1024 * 1024;
+const DEFAULT_RCON_PORT: u16 = 27015;
+const RCON_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait for more output once a command has answered.
+const OUTPUT_IDLE_TIMEOUT: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+fn encode_packet(id: i32, kind: i32, body: &str) -> Vec<u8> {
+    let size = (body.len() + 10) as i32;
+    let mut bytes = Vec::with_capacity(body.len() + 14);
+    bytes.extend_from_slice(&size.to_le_bytes());
+    bytes.extend_from_slice(&id.to_le_bytes());
+    bytes.extend_from_slice(&kind.to_le_bytes());
+    bytes.extend_from_slice(body.as_bytes());
+    bytes.extend_from_slice(&[0, 0]);
+    bytes
+}
+
+fn read_packet(stream: &mut impl Read) -> std::io::Result<Packet> {
+    let mut size = [0u8; 4];
+    stream.read_exact(&mut size)?;
+    let size = i32::from_le_bytes(size);
+    if size < 10 || size as usize > MAX_RECEIVE_SIZE {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("RCON packet size {size} is out of range"),
+        ));
+    }
+    let mut payload = vec![0u8; size as usize];
+    stream.read_exact(&mut payload)?;
+    let id = i32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+    let kind = i32::from_le_bytes([payload[4], payload[5], payload[6], payload[7]]);
+    let body = &payload[8..payload.len() - 2];
+    Ok(Packet {
+        id,
+        kind,
+        body: String::from_utf8_lossy(body).into_owned(),
+    })
+}
+
+fn is_timeout(error: &std::io::Error) -> bool {
+    matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+fn is_closed(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::UnexpectedEof
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+    )
+}
+
+/// A Source RCON connection, authenticated on creation.
+pub(crate) struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+    timeout: Duration,
+}
+
+impl RconClient {
+    pub(crate) fn connect(
+        host: &str,
+        port: u16,
+        password: &str,
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        let address = (host, port)
+            .to_socket_addrs()
+            .map_err(|error| format!("Could not resolve {host}:{port}: {error}"))?
+            .next()
+            .ok_or_else(|| format!("Could not resolve {host}:{port}."))?;
+        let stream = TcpStream::connect_timeout(&address, timeout)
+            .map_err(|error| format!("Could not connect to RCON at {address}: {error}"))?;
+        stream
+            .set_read_timeout(Some(timeout))
+            .and_then(|_| stream.set_write_timeout(Some(timeout)))
+            .map_err(|error| error.to_string())?;
+        let mut client = Self {
+            stream,
+            next_id: 1,
+            timeout,
+        };
+        client.authenticate(password)?;
+        Ok(client)
+    }
+
+    fn send(&mut self, kind: i32, body: &str) -> Result<i32, String> {
+        if body.len() > MAX_SEND_SIZE - 10 {
+            return Err("The RCON command is too long.".to_string());
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        self.stream
+            .write_all(&encode_packet(id, kind, body))
+            .map_err(|error| format!("Could not send to RCON: {error}"))?;
+        Ok(id)
+    }
+
+    fn receive(&mut self) -> std::io::Result<Packet> {
+        read_packet(&mut self.stream)
+    }
+
+    fn authenticate(&mut self, password: &str) -> Result<(), String> {
+        let id = self.send(SERVERDATA_AUTH, password)?;
+        loop {
+            let packet = self.receive().map_err(|error| match is_timeout(&error) {
+                true => "The server did not answer the RCON login.".to_string(),
+                false => format!("RCON login failed: {error}"),
+            })?;
+            if packet.kind != SERVERDATA_AUTH_RESPONSE {
+                // Source servers send an empty RESPONSE_VALUE first.
+                continue;
+            }
+            return match packet.id {
+                -1 => Err("The RCON password was rejected.".to_string()),
+                answered if answered == id => Ok(()),
+                _ => Err("The server sent an unexpected RCON login answer.".to_string()),
+            };
+        }
+    }
+
+    /// Runs `command` and joins every response packet.  An empty
+    /// RESPONSE_VALUE sent after the command marks the end of a multi-packet
+    /// answer on servers that mirror it; servers that ignore it are done once
+    /// no output has arrived for `OUTPUT_IDLE_TIMEOUT`.
+    pub(crate) fn execute(&mut self, command: &str) -> Result<String, String> {
+        let id = self.send(SERVERDATA_EXECCOMMAND, command)?;
+        let sentinel = self.send(SERVERDATA_RESPONSE_VALUE, "")?;
+        let result = self.collect_output(command, id, sentinel);
+        self.stream
+            .set_read_timeout(Some(self.timeout))
+            .map_err(|error| error.to_string())?;
+        result
+    }
+
+    fn collect_output(&mut self, command: &str, id: i32, sentinel: i32) -> Result<String, String> {
+        let mut output = String::new();
+        let mut answered = false;
+        loop {
+            match self.receive() {
+                Ok(packet) if packet.id == sentinel => return Ok(output),
+                Ok(packet) if packet.id == id => {
+                    if !answered {
+                        answered = true;
+                        self.stream
+                            .set_read_timeout(Some(OUTPUT_IDLE_TIMEOUT))
+                            .map_err(|error| error.to_string())?;
+                    }
+                    output.push_str(&packet.body);
+                }
+                Ok(_) => {}
+                // `quit` answers and then drops the connection.
+                Err(error) if answered && (is_timeout(&error) || is_closed(&error)) => {
+                    return Ok(output);
+                }
+                Err(error) if is_timeout(&error) => {
+                    return Err(format!("The server did not answer \"{command}\"."));
+                }
+                Err(error) => return Err(format!("RCON connection failed: {error}")),
+            }
+        }
+    }
+}
+
+/// Host, port and password of a server's RCON, from its INI.
+fn rcon_settings(
+    user_dir: &str,
+    server_name: &str,
+    host: Option<String>,
+) -> Result<(String, u16, String), String> {
+    let name = validate_server_name(server_name)?;
+    let path = server_config_path(user_dir, name, SERVER_INI_FILE);
+    if !path.is_file() {
+        return Err(format!("Server \"{name}\" does not exist."));
+    }
+    let ini = ServerIni::read(&path)?;
+    let port = match ini
+        .get("RCONPort")
+        .map(str::trim)
+        .filter(|port| !port.is_empty())
+    {
+        Some(port) => port
+            .parse()
+            .map_err(|_| format!("RCONPort \"{port}\" is not a valid port."))?,
+        None => DEFAULT_RCON_PORT,
+    };
+    let password = ini.get("RCONPassword").unwrap_or_default().to_string();
+    if password.is_empty() {
+        return Err(
+            "RCONPassword is empty; the server does not accept RCON without one.".to_string(),
+        );
+    }
+    let host = host
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+    Ok((host, port, password))
+}
+
+fn run_rcon(
+    user_dir: &str,
+    server_name: &str,
+    host: Option<String>,
+    command: &str,
+) -> Result<String, String> {
+    let (host, port, password) = rcon_settings(user_dir, server_name, host)?;
+    RconClient::connect(&host, port, &password, RCON_TIMEOUT)?.execute(command)
+}
+
+/// Quotes an argument for the server console, which has no escape syntax.
+fn quote_argument(value: &str) -> String {
+    format!("\"{}\"", value.trim().replace('"', "'"))
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RconPlayers {
+    pub players: Vec<String>,
+    pub raw: String,
+}
+
+/// Reads the `players` output: a header line and then `-name` per player.
+fn parse_players(raw: &str) -> RconPlayers {
+    RconPlayers {
+        players: raw
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('-'))
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        raw: raw.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn rcon_execute(
+    user_dir: String,
+    server_name: String,
+    command: String,
+    host: Option<String>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("rcon_execute");
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("The RCON command is empty.".to_string());
+    }
+    run_rcon(&user_dir, &server_name, host, command)
+}
+
+#[tauri::command]
+pub fn rcon_players(
+    user_dir: String,
+    server_name: String,
+    host: Option<String>,
+) -> Result<RconPlayers, String> {
+    let _timer = scoped_timer("rcon_players");
+    Ok(parse_players(&run_rcon(
+        &user_dir,
+        &server_name,
+        host,
+        "players",
+    )?))
+}
+
+#[tauri::command]
+pub fn rcon_kick(
+    user_dir: String,
+    server_name: String,
+    username: String,
+    reason: Option<String>,
+    host: Option<String>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("rcon_kick");
+    let mut command = format!("kickuser {}", quote_argument(&username));
+    if let Some(reason) = reason.filter(|reason| !reason.trim().is_empty()) {
+        command.push_str(&format!(" -r {}", quote_argument(&reason)));
+    }
+    run_rcon(&user_dir, &server_name, host, &command)
+}
+
+#[tauri::command]
+pub fn rcon_ban(
+    user_dir: String,
+    server_name: String,
+    username: String,
+    ban_ip: bool,
+    reason: Option<String>,
+    host: Option<String>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("rcon_ban");
+    let mut command = format!("banuser {}", quote_argument(&username));
+    if ban_ip {
+        command.push_str(" -ip");
+    }
+    if let Some(reason) = reason.filter(|reason| !reason.trim().is_empty()) {
+        command.push_str(&format!(" -r {}", quote_argument(&reason)));
+    }
+    run_rcon(&user_dir, &server_name, host, &command)
+}
+
+#[tauri::command]
+pub fn rcon_server_message(
+    user_dir: String,
+    server_name: String,
+    message: String,
+    host: Option<String>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("rcon_server_message");
+    run_rcon(
+        &user_dir,
+        &server_name,
+        host,
+        &format!("servermsg {}", quote_argument(&message)),
+    )
+}
+
+#[tauri::command]
+pub fn rcon_save(
+    user_dir: String,
+    server_name: String,
+    host: Option<String>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("rcon_save");
+    run_rcon(&user_dir, &server_name, host, "save")
+}
+
+#[tauri::command]
+pub fn rcon_quit(
+    user_dir: String,
+    server_name: String,
+    host: Option<String>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("rcon_quit");
+    run_rcon(&user_dir, &server_name, host, "quit")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Instant;
+
+    /// Answers one connection like a Source RCON server that splits command
+    /// output over two packets, optionally mirroring the end-of-response
+    /// marker the way Source servers do.
+    fn fake_server(
+        password: &'static str,
+        mirror_marker: bool,
+    ) -> (u16, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+        let port = listener
+            .local_addr()
+            .expect("listener should have an address")
+            .port();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("client should connect");
+            let auth = read_packet(&mut stream).expect("auth packet should arrive");
+            assert_eq!(auth.kind, SERVERDATA_AUTH);
+            let answer_id = if auth.body == password { auth.id } else { -1 };
+            stream
+                .write_all(&encode_packet(auth.id, SERVERDATA_RESPONSE_VALUE, ""))
+                .and_then(|_| {
+                    stream.write_all(&encode_packet(answer_id, SERVERDATA_AUTH_RESPONSE, ""))
+                })
+                .expect("auth answer should be written");
+            let mut commands = Vec::new();
+            while let Ok(packet) = read_packet(&mut stream) {
+                if commands.last().is_some_and(|command| command == "quit") {
+                    // Like the game, close once `quit` is answered.
+                    break;
+                }
+                if packet.kind == SERVERDATA_RESPONSE_VALUE {
+                    if mirror_marker {
+                        stream
+                            .write_all(&encode_packet(packet.id, SERVERDATA_RESPONSE_VALUE, ""))
+                            .expect("marker should be mirrored");
+                    }
+                    continue;
+                }
+                let parts = match packet.body.as_str() {
+                    "players" => vec![
+                        "Players connected (2): \n".to_string(),
+                        "-Alice\n-Bob\n".to_string(),
+                    ],
+                    "help" => vec!["h".repeat(3 * MAX_SEND_SIZE)],
+                    _ => vec!["ok: ".to_string(), "done".to_string()],
+                };
+                for part in parts {
+                    stream
+                        .write_all(&encode_packet(packet.id, SERVERDATA_RESPONSE_VALUE, &part))
+                        .expect("response should be written");
+                }
+                commands.push(packet.body);
+            }
+            commands
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn joins_multi_packet_responses_up_to_the_mirrored_marker() {
+        let (port, server) = fake_server("secret", true);
+        let mut client = RconClient::connect("127.0.0.1", port, "secret", RCON_TIMEOUT)
+            .expect("client should authenticate");
+        assert_eq!(
+            parse_players(&client.execute("players").expect("players should run")).players,
+            ["Alice", "Bob"]
+        );
+        assert_eq!(
+            client
+                .execute(&format!("servermsg {}", quote_argument("Restart \"soon\"")))
+                .expect("message should be sent"),
+            "ok: done"
+        );
+        drop(client);
+        assert_eq!(
+            server.join().expect("fake server should finish"),
+            ["players", "servermsg \"Restart 'soon'\""]
+        );
+    }
+
+    #[test]
+    fn ends_responses_after_an_idle_timeout_without_a_marker() {
+        let (port, server) = fake_server("secret", false);
+        let mut client = RconClient::connect("127.0.0.1", port, "secret", RCON_TIMEOUT)
+            .expect("client should authenticate");
+        let started = Instant::now();
+        assert_eq!(
+            client.execute("help").expect("help should run").len(),
+            3 * MAX_SEND_SIZE
+        );
+        assert_eq!(client.execute("save").expect("save should run"), "ok: done");
+        assert!(started.elapsed() < RCON_TIMEOUT);
+        drop(client);
+        assert_eq!(
+            server.join().expect("fake server should finish"),
+            ["help", "save"]
+        );
+    }
+
+    #[test]
+    fn keeps_the_answer_when_the_server_closes_the_connection() {
+        let (port, server) = fake_server("secret", false);
+        let mut client = RconClient::connect("127.0.0.1", port, "secret", RCON_TIMEOUT)
+            .expect("client should authenticate");
+        assert_eq!(client.execute("quit").expect("quit should run"), "ok: done");
+        assert_eq!(server.join().expect("fake server should finish"), ["quit"]);
+    }
+
+    #[test]
+    fn reports_a_rejected_password() {
+        let (port, server) = fake_server("secret", true);
+        let error = RconClient::connect("127.0.0.1", port, "wrong", RCON_TIMEOUT)
+            .err()
+            .expect("a wrong password should be rejected");
+        assert!(error.contains("rejected"));
+        server.join().expect("fake server should finish");
+    }
+}
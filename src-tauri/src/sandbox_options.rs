@@ -0,0 +1,384 @@
+use crate::loadout::{LoadoutMod, ordered_media_sources};
+use crate::mod_scanner::mod_content_dirs;
+use crate::pz_compat::{SANDBOX_VARS_FILE, server_config_path, validate_server_name};
+use crate::sandbox_vars::{SandboxEntry, SandboxValue, parse_sandbox_vars};
+use crate::script_overrides::strip_comments;
+use crate::timing::scoped_timer;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxOptionType {
+    Boolean,
+    Integer,
+    Double,
+    Enum,
+    String,
+}
+
+impl SandboxOptionType {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "boolean" => Some(Self::Boolean),
+            "integer" => Some(Self::Integer),
+            "double" => Some(Self::Double),
+            "enum" => Some(Self::Enum),
+            "string" => Some(Self::String),
+            _ => None,
+        }
+    }
+}
+
+/// One `option <Name> { ... }` block of a mod's `media/sandbox-options.txt`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxOptionDef {
+    /// Dotted name, which is also the option's path in SandboxVars.
+    pub name: String,
+    #[serde(rename = "type")]
+    pub option_type: SandboxOptionType,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub default: Option<SandboxValue>,
+    /// Number of choices of an `enum` option; values run from 1.
+    pub num_values: Option<u32>,
+    pub page: Option<String>,
+    pub translation: Option<String>,
+    pub value_translation: Option<String>,
+    pub mod_id: Option<String>,
+    pub file_path: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxOptionSchema {
+    /// Options in load order; a name defined twice keeps the later mod's block.
+    pub options: Vec<SandboxOptionDef>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxOptionIssue {
+    pub path: String,
+    pub mod_id: Option<String>,
+    pub message: String,
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+fn parse_default(option_type: SandboxOptionType, raw: &str) -> Option<SandboxValue> {
+    match option_type {
+        SandboxOptionType::Boolean => match raw.to_ascii_lowercase().as_str() {
+            "true" => Some(SandboxValue::Boolean(true)),
+            "false" => Some(SandboxValue::Boolean(false)),
+            _ => None,
+        },
+        SandboxOptionType::String => Some(SandboxValue::String(raw.to_string())),
+        _ => parse_number(raw).map(SandboxValue::Number),
+    }
+}
+
+/// Parses a `sandbox-options.txt`.  Blocks the game would reject are skipped
+/// with a warning instead of failing the whole file.
+pub(crate) fn parse_sandbox_options(
+    text: &str,
+    mod_id: Option<&str>,
+    file_path: &str,
+) -> (Vec<SandboxOptionDef>, Vec<String>) {
+    let cleaned = strip_comments(text);
+    let mut options = Vec::new();
+    let mut warnings = Vec::new();
+    let mut rest = cleaned.as_str();
+    let mut consumed = 0;
+    while let Some(found) = rest.find("option") {
+        let at_word_start = found == 0
+            || !rest[..found]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let after = &rest[found + "option".len()..];
+        if !at_word_start || !after.starts_with(char::is_whitespace) {
+            consumed += found + "option".len();
+            rest = after;
+            continue;
+        }
+        let line = cleaned[..consumed + found].matches('\n').count() + 1;
+        let (Some(open), Some(close)) = (after.find('{'), after.find('}')) else {
+            warnings.push(format!("{file_path}:{line}: option block is not closed."));
+            break;
+        };
+        let name = after[..open].trim().to_string();
+        let block_end = found + "option".len() + close + 1;
+        if open > close || name.is_empty() || name.contains(char::is_whitespace) {
+            warnings.push(format!(
+                "{file_path}:{line}: option block has no valid name."
+            ));
+            consumed += block_end;
+            rest = &rest[block_end..];
+            continue;
+        }
+
+        let mut fields: HashMap<String, String> = HashMap::new();
+        for part in after[open + 1..close].split([',', '\n']) {
+            if let Some((key, value)) = part.split_once('=') {
+                fields.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+        let field = |key: &str| fields.get(key).filter(|value| !value.is_empty()).cloned();
+        match field("type").as_deref().and_then(SandboxOptionType::parse) {
+            None => warnings.push(format!(
+                "{file_path}:{line}: option {name} has no known type and is ignored."
+            )),
+            Some(option_type) => {
+                let default = field("default").and_then(|raw| parse_default(option_type, &raw));
+                if default.is_none() {
+                    warnings.push(format!(
+                        "{file_path}:{line}: option {name} has no valid default."
+                    ));
+                }
+                options.push(SandboxOptionDef {
+                    name,
+                    option_type,
+                    min: field("min").as_deref().and_then(parse_number),
+                    max: field("max").as_deref().and_then(parse_number),
+                    default,
+                    num_values: field("numvalues").and_then(|raw| raw.parse().ok()),
+                    page: field("page"),
+                    translation: field("translation"),
+                    value_translation: field("valuetranslation"),
+                    mod_id: mod_id.map(str::to_string),
+                    file_path: file_path.to_string(),
+                    line,
+                });
+            }
+        }
+        consumed += block_end;
+        rest = &rest[block_end..];
+    }
+    (options, warnings)
+}
+
+impl SandboxOptionDef {
+    /// Why `value` is not acceptable for this option, if it is not.
+    pub(crate) fn check(&self, value: &SandboxValue) -> Option<String> {
+        let number = match (self.option_type, value) {
+            (SandboxOptionType::Boolean, SandboxValue::Boolean(_))
+            | (SandboxOptionType::String, SandboxValue::String(_)) => return None,
+            (
+                SandboxOptionType::Integer | SandboxOptionType::Double | SandboxOptionType::Enum,
+                SandboxValue::Number(number),
+            ) => *number,
+            _ => {
+                return Some(format!(
+                    "{} expects a {} value.",
+                    self.name,
+                    match self.option_type {
+                        SandboxOptionType::Boolean => "boolean",
+                        SandboxOptionType::String => "string",
+                        _ => "number",
+                    }
+                ));
+            }
+        };
+        if self.option_type != SandboxOptionType::Double && number.fract() != 0.0 {
+            return Some(format!("{} expects a whole number.", self.name));
+        }
+        let (min, max) = match self.option_type {
+            SandboxOptionType::Enum => (Some(1.0), self.num_values.map(f64::from)),
+            _ => (self.min, self.max),
+        };
+        if min.is_some_and(|min| number < min) || max.is_some_and(|max| number > max) {
+            return Some(format!(
+                "{} is {number}, outside {}..{}.",
+                self.name,
+                min.map_or("".to_string(), |min| min.to_string()),
+                max.map_or("".to_string(), |max| max.to_string()),
+            ));
+        }
+        None
+    }
+}
+
+/// Every mod's `sandbox-options.txt`, in load order.  Within a B42 mod the
+/// version folder's file replaces the one in `common/`.
+pub(crate) fn loadout_sandbox_schema(mods: &[LoadoutMod]) -> SandboxOptionSchema {
+    let mut options: Vec<SandboxOptionDef> = Vec::new();
+    let mut warnings = Vec::new();
+    for source in ordered_media_sources(mods) {
+        let Some(path) = mod_content_dirs(Path::new(&source.mod_info_path))
+            .into_iter()
+            .rev()
+            .map(|dir| dir.join("media").join("sandbox-options.txt"))
+            .find(|path| path.is_file())
+        else {
+            continue;
+        };
+        let file_path = path.to_string_lossy().to_string();
+        let text = match fs::read(&path) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(error) => {
+                warnings.push(format!("{file_path}: {error}"));
+                continue;
+            }
+        };
+        let (parsed, file_warnings) =
+            parse_sandbox_options(&text, Some(&source.mod_id), &file_path);
+        warnings.extend(file_warnings);
+        for option in parsed {
+            if let Some(index) = options
+                .iter()
+                .position(|existing| existing.name == option.name)
+            {
+                let previous = options.remove(index);
+                warnings.push(format!(
+                    "Sandbox option {} from {} replaces the one from {}.",
+                    option.name,
+                    source.mod_id,
+                    previous.mod_id.as_deref().unwrap_or("an earlier file")
+                ));
+            }
+            options.push(option);
+        }
+    }
+    SandboxOptionSchema { options, warnings }
+}
+
+fn find_value<'a>(entries: &'a [SandboxEntry], path: &str) -> Option<&'a SandboxValue> {
+    let mut current = entries;
+    let mut keys = path.split('.').peekable();
+    while let Some(key) = keys.next() {
+        let entry = current.iter().find(|entry| entry.key == key)?;
+        if keys.peek().is_none() {
+            return Some(&entry.value);
+        }
+        let SandboxValue::Table(children) = &entry.value else {
+            return None;
+        };
+        current = children;
+    }
+    None
+}
+
+/// Checks the mod options a SandboxVars file sets against `schema`.  Options
+/// the file leaves out are fine; the game falls back to their defaults.
+pub(crate) fn check_sandbox_entries(
+    entries: &[SandboxEntry],
+    schema: &SandboxOptionSchema,
+) -> Vec<SandboxOptionIssue> {
+    schema
+        .options
+        .iter()
+        .filter_map(|option| {
+            let message = option.check(find_value(entries, &option.name)?)?;
+            Some(SandboxOptionIssue {
+                path: option.name.clone(),
+                mod_id: option.mod_id.clone(),
+                message,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_loadout_sandbox_schema(mods: Vec<LoadoutMod>) -> Result<SandboxOptionSchema, String> {
+    let _timer = scoped_timer("get_loadout_sandbox_schema");
+    Ok(loadout_sandbox_schema(&mods))
+}
+
+#[tauri::command]
+pub fn validate_mod_sandbox_options(
+    user_dir: String,
+    server_name: String,
+    mods: Vec<LoadoutMod>,
+) -> Result<Vec<SandboxOptionIssue>, String> {
+    let _timer = scoped_timer("validate_mod_sandbox_options");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SANDBOX_VARS_FILE);
+    let text = fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let entries = parse_sandbox_vars(&String::from_utf8_lossy(&text))?;
+    Ok(check_sandbox_entries(
+        &entries,
+        &loadout_sandbox_schema(&mods),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn merges_mod_sandbox_options_and_checks_values() {
+        let root = TestDir::new("pz-sandbox-options-test");
+        root.write("A/mod.info", "id=A\n");
+        root.write(
+            "A/media/sandbox-options.txt",
+            "VERSION = 1,\n\n/* loot options */\noption A.Loot\n{\n\ttype = integer, min = 0, max = 10, default = 5,\n\tpage = A, translation = A_Loot,\n}\n\n// option A.Hidden { type = boolean, default = true }\noption A.Mode\n{\n\ttype = enum, numValues = 3, default = 2,\n\tpage = A, translation = A_Mode, valueTranslation = A_Modes,\n}\noption A.Broken { type = colour, default = 1, }\n",
+        );
+        root.write("B/common/mod.info", "id=B\nrequire=\\A\n");
+        root.write(
+            "B/common/media/sandbox-options.txt",
+            "option B.Ignored { type = boolean, default = true, }\n",
+        );
+        root.write("B/42/mod.info", "id=B\nrequire=\\A\n");
+        root.write(
+            "B/42/media/sandbox-options.txt",
+            "option A.Loot { type = double, min = 0.5, max = 2.0, default = 1.0, page = B, }\noption B.Name { type = string, default = Knox, page = B, }\n",
+        );
+
+        let loadout_mod = |id: &str, info: &str| LoadoutMod {
+            mod_id: Some(id.to_string()),
+            mod_info_path: Some(root.join(info).to_string_lossy().to_string()),
+            ..LoadoutMod::default()
+        };
+        let schema = loadout_sandbox_schema(&[
+            loadout_mod("B", "B/42/mod.info"),
+            loadout_mod("A", "A/mod.info"),
+        ]);
+        let names: Vec<(&str, Option<&str>)> = schema
+            .options
+            .iter()
+            .map(|option| (option.name.as_str(), option.mod_id.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("A.Mode", Some("A")),
+                ("A.Loot", Some("B")),
+                ("B.Name", Some("B"))
+            ]
+        );
+        assert_eq!(schema.options[0].line, 11);
+        assert_eq!(schema.options[0].num_values, Some(3));
+        assert_eq!(
+            schema.options[0].value_translation.as_deref(),
+            Some("A_Modes")
+        );
+        assert_eq!(schema.options[1].option_type, SandboxOptionType::Double);
+        assert_eq!(
+            schema.options[2].default,
+            Some(SandboxValue::String("Knox".to_string()))
+        );
+        assert_eq!(schema.warnings.len(), 2);
+
+        let entries = parse_sandbox_vars(
+            "SandboxVars = { A = { Loot = 3, Mode = 4 }, B = { Name = \"Rosewood\" } }",
+        )
+        .expect("sandbox vars should parse");
+        let issues: Vec<String> = check_sandbox_entries(&entries, &schema)
+            .into_iter()
+            .map(|issue| issue.path)
+            .collect();
+        assert_eq!(issues, ["A.Mode", "A.Loot"]);
+    }
+}
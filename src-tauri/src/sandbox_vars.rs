@@ -0,0 +1,847 @@
+use crate::pz_compat::{
+    SANDBOX_VARS_FILE, SANDBOX_VERSION, server_config_path, validate_server_name,
+};
+use crate::timing::scoped_timer;
+use crate::utils::write_atomically;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum SandboxValue {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    Table(Vec<SandboxEntry>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxEntry {
+    pub key: String,
+    pub value: SandboxValue,
+    /// `--` comment lines directly above the entry, without the dashes.
+    #[serde(default)]
+    pub comments: Vec<String>,
+    #[serde(default)]
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxVarsFile {
+    pub path: String,
+    pub version: Option<u32>,
+    pub expected_version: u32,
+    pub entries: Vec<SandboxEntry>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxChange {
+    /// Dotted key path such as `ZombieLore.Speed`.
+    pub path: String,
+    pub value: SandboxValue,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxDefaultDiff {
+    pub path: String,
+    pub value: Option<SandboxValue>,
+    pub default: Option<SandboxValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Name(String),
+    Number(f64),
+    Str(String),
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Equals,
+    Separator,
+    /// `(` or `)`, which only appear around function wrappers.
+    Paren(char),
+    Comment(String),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Spanned {
+    pub token: Token,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+}
+
+/// Splits Lua table source into tokens; `--[[ ]]` block comments are dropped.
+pub(crate) fn tokenize(text: &str) -> Result<Vec<Spanned>, String> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut line = 1;
+    while pos < bytes.len() {
+        let start = pos;
+        let c = bytes[pos];
+        let token = match c {
+            b'\n' => {
+                line += 1;
+                pos += 1;
+                continue;
+            }
+            _ if c.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            b'-' if bytes.get(pos + 1) == Some(&b'-') => {
+                if text[pos + 2..].starts_with("[[") {
+                    let end = text[pos + 4..]
+                        .find("]]")
+                        .map(|offset| pos + 4 + offset + 2)
+                        .ok_or_else(|| format!("Unterminated block comment on line {line}."))?;
+                    line += text[pos..end].matches('\n').count();
+                    pos = end;
+                    continue;
+                }
+                let end = text[pos..]
+                    .find('\n')
+                    .map_or(bytes.len(), |offset| pos + offset);
+                pos = end;
+                Token::Comment(text[start + 2..end].trim().to_string())
+            }
+            b'{' => {
+                pos += 1;
+                Token::Open
+            }
+            b'}' => {
+                pos += 1;
+                Token::Close
+            }
+            b'[' => {
+                pos += 1;
+                Token::OpenBracket
+            }
+            b']' => {
+                pos += 1;
+                Token::CloseBracket
+            }
+            b'=' => {
+                pos += 1;
+                Token::Equals
+            }
+            b',' | b';' => {
+                pos += 1;
+                Token::Separator
+            }
+            b'(' | b')' => {
+                pos += 1;
+                Token::Paren(c as char)
+            }
+            b'"' | b'\'' => {
+                let mut value = String::new();
+                let mut chars = text[pos + 1..].char_indices();
+                let mut closed = None;
+                while let Some((offset, ch)) = chars.next() {
+                    match ch {
+                        _ if ch == c as char => {
+                            closed = Some(pos + 1 + offset + 1);
+                            break;
+                        }
+                        '\\' => match chars.next().map(|(_, escaped)| escaped) {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some('r') => value.push('\r'),
+                            Some(other) => value.push(other),
+                            None => break,
+                        },
+                        '\n' => break,
+                        _ => value.push(ch),
+                    }
+                }
+                pos = closed.ok_or_else(|| format!("Unterminated string on line {line}."))?;
+                Token::Str(value)
+            }
+            b'-' | b'.' | b'0'..=b'9' => {
+                pos += 1;
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || matches!(bytes[pos], b'.' | b'+'))
+                {
+                    if matches!(bytes[pos], b'+') && !matches!(bytes[pos - 1], b'e' | b'E') {
+                        break;
+                    }
+                    pos += 1;
+                }
+                let raw = &text[start..pos];
+                Token::Number(
+                    raw.parse()
+                        .map_err(|_| format!("Invalid number \"{raw}\" on line {line}."))?,
+                )
+            }
+            _ if c.is_ascii_alphabetic() || c == b'_' => {
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                Token::Name(text[start..pos].to_string())
+            }
+            _ => {
+                let ch = text[pos..].chars().next().unwrap_or('?');
+                return Err(format!("Unexpected character '{ch}' on line {line}."));
+            }
+        };
+        tokens.push(Spanned {
+            token,
+            start,
+            end: pos,
+            line,
+        });
+    }
+    Ok(tokens)
+}
+
+/// An entry with the byte ranges the writer needs to edit the source text.
+#[derive(Debug, Clone)]
+struct Node {
+    key: String,
+    line: usize,
+    comments: Vec<String>,
+    key_start: usize,
+    value_start: usize,
+    value_end: usize,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Scalar(SandboxValue),
+    Table(Table),
+}
+
+#[derive(Debug, Clone)]
+struct Table {
+    nodes: Vec<Node>,
+    /// Offset of the closing `}`.
+    close: usize,
+    /// Whether the last entry already has a trailing separator.
+    trailing_separator: bool,
+}
+
+struct Parser<'a> {
+    tokens: &'a [Spanned],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Spanned> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Spanned> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, message: &str) -> String {
+        match self
+            .tokens
+            .get(self.pos.min(self.tokens.len().saturating_sub(1)))
+        {
+            Some(token) => format!("{message} on line {}.", token.line),
+            None => format!("{message} at the end of the file."),
+        }
+    }
+
+    fn table(&mut self) -> Result<Table, String> {
+        let mut nodes = Vec::new();
+        let mut comments = Vec::new();
+        let mut trailing_separator = false;
+        loop {
+            let Some(token) = self.next().cloned() else {
+                return Err(self.error("Missing closing '}'"));
+            };
+            let (key, key_start, line) = match token.token {
+                Token::Close => {
+                    return Ok(Table {
+                        nodes,
+                        close: token.start,
+                        trailing_separator,
+                    });
+                }
+                Token::Comment(text) => {
+                    comments.push(text);
+                    continue;
+                }
+                Token::Separator => {
+                    trailing_separator = true;
+                    continue;
+                }
+                Token::Name(name) => (name, token.start, token.line),
+                Token::OpenBracket => {
+                    let key = match self.next().map(|t| t.token.clone()) {
+                        Some(Token::Str(key)) => key,
+                        _ => return Err(self.error("Expected a quoted key after '['")),
+                    };
+                    if !matches!(self.next().map(|t| &t.token), Some(Token::CloseBracket)) {
+                        return Err(self.error("Expected ']'"));
+                    }
+                    (key, token.start, token.line)
+                }
+                _ => return Err(self.error("Expected a key")),
+            };
+            if !matches!(self.next().map(|t| &t.token), Some(Token::Equals)) {
+                return Err(self.error(&format!("Expected '=' after \"{key}\"")));
+            }
+            let Some(value_token) = self.next().cloned() else {
+                return Err(self.error(&format!("Missing value for \"{key}\"")));
+            };
+            let (kind, value_end) = match value_token.token {
+                Token::Number(value) => (
+                    NodeKind::Scalar(SandboxValue::Number(value)),
+                    value_token.end,
+                ),
+                Token::Str(value) => (
+                    NodeKind::Scalar(SandboxValue::String(value)),
+                    value_token.end,
+                ),
+                Token::Name(name) if name == "true" || name == "false" => (
+                    NodeKind::Scalar(SandboxValue::Boolean(name == "true")),
+                    value_token.end,
+                ),
+                Token::Open => {
+                    let table = self.table()?;
+                    let end = table.close + 1;
+                    (NodeKind::Table(table), end)
+                }
+                _ => return Err(self.error(&format!("Unsupported value for \"{key}\""))),
+            };
+            trailing_separator = false;
+            nodes.push(Node {
+                key,
+                line,
+                comments: std::mem::take(&mut comments),
+                key_start,
+                value_start: value_token.start,
+                value_end,
+                kind,
+            });
+            if matches!(self.peek().map(|t| &t.token), Some(Token::Separator)) {
+                self.pos += 1;
+                trailing_separator = true;
+            }
+        }
+    }
+}
+
+/// Parses `SandboxVars = { ... }`, or the `return { ... }` form of the game's
+/// own sandbox presets, into its root table.
+fn parse_root(text: &str) -> Result<Table, String> {
+    let tokens = tokenize(text)?;
+    let open = tokens
+        .iter()
+        .position(|token| token.token == Token::Open)
+        .ok_or_else(|| "No SandboxVars table was found.".to_string())?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: open + 1,
+    };
+    parser.table()
+}
+
+fn to_entries(nodes: &[Node]) -> Vec<SandboxEntry> {
+    nodes
+        .iter()
+        .map(|node| SandboxEntry {
+            key: node.key.clone(),
+            value: match &node.kind {
+                NodeKind::Scalar(value) => value.clone(),
+                NodeKind::Table(table) => SandboxValue::Table(to_entries(&table.nodes)),
+            },
+            comments: node.comments.clone(),
+            line: node.line,
+        })
+        .collect()
+}
+
+pub(crate) fn parse_sandbox_vars(text: &str) -> Result<Vec<SandboxEntry>, String> {
+    Ok(to_entries(&parse_root(text)?.nodes))
+}
+
+pub(crate) fn lua_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn format_value(value: &SandboxValue, indent: &str) -> String {
+    match value {
+        SandboxValue::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+            format!("{}", *number as i64)
+        }
+        SandboxValue::Number(number) => number.to_string(),
+        SandboxValue::Boolean(flag) => flag.to_string(),
+        SandboxValue::String(text) => lua_string(text),
+        SandboxValue::Table(entries) => {
+            let inner = format!("{indent}    ");
+            let mut out = String::from("{\n");
+            for entry in entries {
+                out.push_str(&format!(
+                    "{inner}{} = {},\n",
+                    entry.key,
+                    format_value(&entry.value, &inner)
+                ));
+            }
+            out.push_str(indent);
+            out.push('}');
+            out
+        }
+    }
+}
+
+pub(crate) fn line_start(text: &str, offset: usize) -> usize {
+    text[..offset].rfind('\n').map_or(0, |index| index + 1)
+}
+
+pub(crate) fn indent_of(text: &str, offset: usize) -> String {
+    let start = line_start(text, offset);
+    text[start..offset]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect()
+}
+
+/// Applies one change to `text` and returns the edited text.  Existing values
+/// are replaced in place; missing keys are added at the end of their table.
+fn apply_change(text: &str, change: &SandboxChange) -> Result<String, String> {
+    let keys: Vec<&str> = change.path.split('.').map(str::trim).collect();
+    if keys.iter().any(|key| key.is_empty()) {
+        return Err(format!(
+            "\"{}\" is not a valid sandbox key path.",
+            change.path
+        ));
+    }
+    let mut table = parse_root(text)?;
+    let mut table_indent = indent_of(text, table.close);
+    let mut depth = 0;
+    loop {
+        let key = keys[depth];
+        let Some(node) = table.nodes.iter().find(|node| node.key == key).cloned() else {
+            break;
+        };
+        if depth + 1 == keys.len() {
+            if matches!(node.kind, NodeKind::Table(_))
+                != matches!(change.value, SandboxValue::Table(_))
+            {
+                return Err(format!(
+                    "\"{}\" cannot change between a table and a single value.",
+                    change.path
+                ));
+            }
+            if matches!(&node.kind, NodeKind::Scalar(value) if *value == change.value) {
+                return Ok(text.to_string());
+            }
+            let replacement = format_value(&change.value, &indent_of(text, node.key_start));
+            return Ok(format!(
+                "{}{}{}",
+                &text[..node.value_start],
+                replacement,
+                &text[node.value_end..]
+            ));
+        }
+        let NodeKind::Table(child) = node.kind else {
+            return Err(format!("\"{key}\" in \"{}\" is not a table.", change.path));
+        };
+        table_indent = indent_of(text, node.key_start);
+        table = child;
+        depth += 1;
+    }
+
+    // Build the missing part of the path, innermost value first.
+    let mut value = change.value.clone();
+    for key in keys[depth + 1..].iter().rev() {
+        value = SandboxValue::Table(vec![SandboxEntry {
+            key: key.to_string(),
+            value,
+            comments: Vec::new(),
+            line: 0,
+        }]);
+    }
+    let entry_indent = table
+        .nodes
+        .first()
+        .map(|node| indent_of(text, node.key_start))
+        .unwrap_or_else(|| format!("{table_indent}    "));
+    let insertion = format!(
+        "{entry_indent}{} = {},\n",
+        keys[depth],
+        format_value(&value, &entry_indent)
+    );
+
+    let separator_at = table
+        .nodes
+        .last()
+        .filter(|_| !table.trailing_separator)
+        .map(|node| node.value_end);
+    Ok(insert_entry(
+        text,
+        table.close,
+        separator_at,
+        &table_indent,
+        &insertion,
+    ))
+}
+
+/// Inserts the `insertion` lines as the last entries of the table closing at
+/// `close`, after adding the separator the previous entry lacks at
+/// `separator_at`.
+pub(crate) fn insert_entry(
+    text: &str,
+    close: usize,
+    separator_at: Option<usize>,
+    table_indent: &str,
+    insertion: &str,
+) -> String {
+    let mut out = String::with_capacity(text.len() + insertion.len() + 2);
+    let close_line = line_start(text, close);
+    let close_on_own_line = text[close_line..close].trim().is_empty();
+    let mut cursor = 0;
+    if let Some(at) = separator_at {
+        out.push_str(&text[..at]);
+        out.push(',');
+        cursor = at;
+    }
+    if close_on_own_line {
+        out.push_str(&text[cursor..close_line]);
+        out.push_str(insertion);
+        out.push_str(&text[close_line..]);
+    } else {
+        out.push_str(&text[cursor..close]);
+        out.push('\n');
+        out.push_str(insertion);
+        out.push_str(table_indent);
+        out.push_str(&text[close..]);
+    }
+    out
+}
+
+pub(crate) fn apply_sandbox_changes(
+    text: &str,
+    changes: &[SandboxChange],
+) -> Result<String, String> {
+    let mut current = text.to_string();
+    for change in changes {
+        current = apply_change(&current, change)?;
+    }
+    Ok(current)
+}
+
+/// Removes the entry at the dotted `path`.  A missing key leaves the text
+/// unchanged.
+pub(crate) fn remove_sandbox_key(text: &str, path: &str) -> Result<String, String> {
+    let mut table = parse_root(text)?;
+    let mut keys = path.split('.').map(str::trim).peekable();
+    while let Some(key) = keys.next() {
+        let Some(node) = table.nodes.iter().find(|node| node.key == key).cloned() else {
+            return Ok(text.to_string());
+        };
+        if keys.peek().is_some() {
+            let NodeKind::Table(child) = node.kind else {
+                return Ok(text.to_string());
+            };
+            table = child;
+            continue;
+        }
+        return Ok(remove_entry(text, node.key_start, node.value_end));
+    }
+    Ok(text.to_string())
+}
+
+/// Removes the table entry spanning `start..end` with its separator.  An entry
+/// on a line of its own goes with that line and the comment lines right above
+/// it, which describe it.
+pub(crate) fn remove_entry(text: &str, start: usize, end: usize) -> String {
+    let line = line_start(text, start);
+    let own_line = text[line..start].trim().is_empty();
+    let mut end = end;
+    let rest = &text[end..];
+    let after_space = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    if matches!(rest[after_space..].chars().next(), Some(',' | ';')) {
+        end += after_space + 1;
+    }
+    if !own_line {
+        return format!("{}{}", &text[..start], &text[end..]);
+    }
+    let rest = &text[end..];
+    let trailing = rest.len() - rest.trim_start_matches([' ', '\t', '\r']).len();
+    if rest[trailing..].starts_with('\n') {
+        end += trailing + 1;
+    }
+    let mut start = line;
+    while start > 0 {
+        let above = line_start(text, start - 1);
+        if !text[above..start].trim_start().starts_with("--") {
+            break;
+        }
+        start = above;
+    }
+    format!("{}{}", &text[..start], &text[end..])
+}
+
+/// A single value as it appears on the right of `=`.
+pub(crate) fn format_sandbox_value(value: &SandboxValue) -> String {
+    format_value(value, "")
+}
+
+/// Every leaf value keyed by its dotted path, in file order.
+pub(crate) fn flatten_sandbox(entries: &[SandboxEntry]) -> Vec<(String, SandboxValue)> {
+    let mut values = Vec::new();
+    flatten(entries, "", &mut values);
+    values
+}
+
+fn flatten(entries: &[SandboxEntry], prefix: &str, out: &mut Vec<(String, SandboxValue)>) {
+    for entry in entries {
+        let path = if prefix.is_empty() {
+            entry.key.clone()
+        } else {
+            format!("{prefix}.{}", entry.key)
+        };
+        match &entry.value {
+            SandboxValue::Table(children) => flatten(children, &path, out),
+            value => out.push((path, value.clone())),
+        }
+    }
+}
+
+/// Every leaf that differs between `entries` and `defaults`, in file order
+/// followed by defaults the file does not set.
+pub(crate) fn diff_against_defaults(
+    entries: &[SandboxEntry],
+    defaults: &[SandboxEntry],
+) -> Vec<SandboxDefaultDiff> {
+    let values = flatten_sandbox(entries);
+    let default_values = flatten_sandbox(defaults);
+
+    let mut diffs = Vec::new();
+    for (path, value) in &values {
+        let default = default_values
+            .iter()
+            .find(|(default_path, _)| default_path == path)
+            .map(|(_, value)| value.clone());
+        if path != "VERSION" && default.as_ref() != Some(value) {
+            diffs.push(SandboxDefaultDiff {
+                path: path.clone(),
+                value: Some(value.clone()),
+                default,
+            });
+        }
+    }
+    for (path, default) in default_values {
+        if path != "VERSION" && !values.iter().any(|(value_path, _)| *value_path == path) {
+            diffs.push(SandboxDefaultDiff {
+                path,
+                value: None,
+                default: Some(default),
+            });
+        }
+    }
+    diffs
+}
+
+fn describe(path: &Path, text: &str) -> Result<SandboxVarsFile, String> {
+    let entries = parse_sandbox_vars(text)?;
+    let version = entries
+        .iter()
+        .find(|entry| entry.key == "VERSION")
+        .and_then(|entry| match entry.value {
+            SandboxValue::Number(number) if number >= 0.0 && number.fract() == 0.0 => {
+                Some(number as u32)
+            }
+            _ => None,
+        });
+    let mut warnings = Vec::new();
+    match version {
+        None => {
+            warnings.push("SandboxVars has no VERSION; the game treats it as outdated.".to_string())
+        }
+        Some(version) if version != SANDBOX_VERSION => warnings.push(format!(
+            "SandboxVars VERSION is {version} but this game build expects {SANDBOX_VERSION}."
+        )),
+        Some(_) => {}
+    }
+    Ok(SandboxVarsFile {
+        path: path.to_string_lossy().to_string(),
+        version,
+        expected_version: SANDBOX_VERSION,
+        entries,
+        warnings,
+    })
+}
+
+/// The file written for a server that has no SandboxVars yet.
+pub(crate) fn empty_sandbox_vars() -> String {
+    format!("SandboxVars = {{\n    VERSION = {SANDBOX_VERSION},\n}}\n")
+}
+
+fn read_text(path: &Path) -> Result<String, String> {
+    fs::read(path)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[tauri::command]
+pub fn read_sandbox_vars(user_dir: String, server_name: String) -> Result<SandboxVarsFile, String> {
+    let _timer = scoped_timer("read_sandbox_vars");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SANDBOX_VARS_FILE);
+    describe(&path, &read_text(&path)?)
+}
+
+#[tauri::command]
+pub fn write_sandbox_vars(
+    user_dir: String,
+    server_name: String,
+    changes: Vec<SandboxChange>,
+) -> Result<SandboxVarsFile, String> {
+    let _timer = scoped_timer("write_sandbox_vars");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SANDBOX_VARS_FILE);
+    let text = if path.exists() {
+        read_text(&path)?
+    } else {
+        empty_sandbox_vars()
+    };
+    let updated = apply_sandbox_changes(&text, &changes)?;
+    let described = describe(&path, &updated)?;
+    write_atomically(&path, updated.as_bytes())?;
+    Ok(described)
+}
+
+/// Compares a server's sandbox settings with a defaults file such as one of
+/// the game's `media/lua/shared/Sandbox/*.lua` presets.
+#[tauri::command]
+pub fn diff_sandbox_vars_with_defaults(
+    user_dir: String,
+    server_name: String,
+    defaults_path: String,
+) -> Result<Vec<SandboxDefaultDiff>, String> {
+    let _timer = scoped_timer("diff_sandbox_vars_with_defaults");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SANDBOX_VARS_FILE);
+    let entries = parse_sandbox_vars(&read_text(&path)?)?;
+    let defaults = parse_sandbox_vars(&read_text(Path::new(&defaults_path))?)?;
+    Ok(diff_against_defaults(&entries, &defaults))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "SandboxVars = {\n    VERSION = 6,\n    -- Default=Normal\n    -- 1 = Insane\n    Zombies = 4,\n    DayLength = 3,\n    StartTime = 0.5,\n    ZombieConfig = \"Custom \\\"mix\\\"\",\n    Map = {\n        AllowMiniMap = false,\n        AllowWorldMap = true,\n    },\n    --[[ block\n    comment ]]\n    ZombieLore = {\n        Speed = 2,\n        Strength = -1\n    }\n}\n";
+
+    #[test]
+    fn parses_nested_tables_with_comments() {
+        let entries = parse_sandbox_vars(SAMPLE).expect("sample should parse");
+        let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "VERSION",
+                "Zombies",
+                "DayLength",
+                "StartTime",
+                "ZombieConfig",
+                "Map",
+                "ZombieLore"
+            ]
+        );
+        assert_eq!(entries[1].comments, ["Default=Normal", "1 = Insane"]);
+        assert_eq!(entries[1].line, 5);
+        assert_eq!(entries[3].value, SandboxValue::Number(0.5));
+        assert_eq!(
+            entries[4].value,
+            SandboxValue::String("Custom \"mix\"".to_string())
+        );
+        let SandboxValue::Table(lore) = &entries[6].value else {
+            panic!("ZombieLore should be a table");
+        };
+        assert_eq!(lore[1].value, SandboxValue::Number(-1.0));
+
+        let described = describe(Path::new("test.lua"), SAMPLE).expect("sample should parse");
+        assert_eq!(described.version, Some(SANDBOX_VERSION));
+        assert!(described.warnings.is_empty());
+        assert!(parse_sandbox_vars("SandboxVars = { Zombies = }").is_err());
+    }
+
+    #[test]
+    fn writes_changes_in_place_and_appends_missing_keys() {
+        let changes = [
+            SandboxChange {
+                path: "Zombies".to_string(),
+                value: SandboxValue::Number(2.0),
+            },
+            SandboxChange {
+                path: "ZombieLore.Strength".to_string(),
+                value: SandboxValue::Number(3.0),
+            },
+            SandboxChange {
+                path: "ZombieLore.Toughness".to_string(),
+                value: SandboxValue::Number(1.0),
+            },
+            SandboxChange {
+                path: "MyMod.Enabled".to_string(),
+                value: SandboxValue::Boolean(true),
+            },
+        ];
+        let updated = apply_sandbox_changes(SAMPLE, &changes).expect("changes should apply");
+        assert!(updated.contains("    -- 1 = Insane\n    Zombies = 2,\n"));
+        assert!(updated.contains("        Strength = 3,\n        Toughness = 1,\n    },\n"));
+        assert!(updated.ends_with("    MyMod = {\n        Enabled = true,\n    },\n}\n"));
+        assert!(updated.contains("--[[ block\n    comment ]]"));
+        assert_eq!(
+            apply_sandbox_changes(SAMPLE, &changes[..0]).expect("no changes should apply"),
+            SAMPLE
+        );
+        assert!(
+            apply_sandbox_changes(
+                SAMPLE,
+                &[SandboxChange {
+                    path: "Map".to_string(),
+                    value: SandboxValue::Number(1.0),
+                }]
+            )
+            .is_err()
+        );
+
+        let entries = parse_sandbox_vars(&updated).expect("updated file should parse");
+        let defaults = parse_sandbox_vars(SAMPLE).expect("sample should parse");
+        let diffs: Vec<String> = diff_against_defaults(&entries, &defaults)
+            .into_iter()
+            .map(|diff| diff.path)
+            .collect();
+        assert_eq!(
+            diffs,
+            [
+                "Zombies",
+                "ZombieLore.Strength",
+                "ZombieLore.Toughness",
+                "MyMod.Enabled"
+            ]
+        );
+    }
+}
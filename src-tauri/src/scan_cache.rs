@@ -0,0 +1,490 @@
+use crate::mod_scanner::{
+    build_scan_result, find_mod_info_paths, is_layout_folder, is_mod_media_dir, read_mod_info_entry,
+};
+use crate::models::{ModFileInfo, ModFolderScanResult, ModScanChange, ModScanChanges, ModSummary};
+use crate::steam_workshop::{WorkshopManifestItem, manifest_items_by_id};
+use rayon::prelude::*;
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+const CACHE_FILE_NAME: &str = "mod_scan_cache.sqlite";
+/// Bump whenever `ModFileInfo` or `ModSummary` change in a way old rows
+/// cannot be read back as.
+const SCHEMA_VERSION: i32 = 2;
+/// How long a scan waits for another scan of the same cache to finish.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Raw `mod.info` parse results from previous scans, keyed by scanned folder
+/// and file path.  A row is reused while the file's size and mtime match, and
+/// an item folder whose stamp did not move is not walked again.
+pub(crate) struct ScanCache {
+    connection: Connection,
+}
+
+struct CachedEntry {
+    size: i64,
+    mtime_ns: Option<i64>,
+    file_info: String,
+    summary: String,
+}
+
+struct CachedItem {
+    stamp: String,
+    mod_info_paths: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+    size: i64,
+    mtime_ns: Option<i64>,
+}
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+    let mtime_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .and_then(|duration| i64::try_from(duration.as_nanos()).ok());
+    Some(FileStamp {
+        size: i64::try_from(metadata.len()).unwrap_or(i64::MAX),
+        mtime_ns,
+    })
+}
+
+/// What is checked of an item folder before walking it: the folders a
+/// `mod.info` can sit in, whose mtimes move when entries are added or removed,
+/// every `mod.info` the last walk found and Steam's manifest entry.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct ItemStamp {
+    files: BTreeMap<String, FileStamp>,
+    manifest: Option<(Option<u64>, Option<String>)>,
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn item_stamp(
+    item_dir: &Path,
+    mod_info_paths: &[PathBuf],
+    manifest: Option<&WorkshopManifestItem>,
+) -> ItemStamp {
+    let mut stamp = ItemStamp {
+        manifest: manifest.map(|item| (item.size, item.time_updated.clone())),
+        ..ItemStamp::default()
+    };
+    let mut add = |path: &Path| {
+        if let Some(file) = file_stamp(path) {
+            stamp.files.insert(path.to_string_lossy().to_string(), file);
+        }
+    };
+    // A workshop item keeps its mods under `mods/`; a local mods folder holds
+    // them directly.
+    let mods_dir = item_dir.join("mods");
+    add(&mods_dir);
+    for mod_dir in std::iter::once(item_dir.to_path_buf()).chain(subdirs(&mods_dir)) {
+        add(&mod_dir);
+        add(&mod_dir.join("mod.info"));
+        for layout_dir in subdirs(&mod_dir) {
+            if layout_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_layout_folder)
+            {
+                add(&layout_dir);
+                add(&layout_dir.join("mod.info"));
+            }
+        }
+    }
+    for info_path in mod_info_paths {
+        add(info_path);
+        if let Some(parent) = info_path.parent() {
+            add(parent);
+        }
+    }
+    stamp
+}
+
+/// The folders directly under `root` that are walked and stamped one by one.
+fn item_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = subdirs(root)
+        .into_iter()
+        .filter(|dir| !is_mod_media_dir(dir))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// An item folder's `mod.info` files, with the stamp to store for it when the
+/// folder had to be walked again.
+struct ItemScan {
+    key: String,
+    mod_info_paths: Vec<PathBuf>,
+    walked: Option<ItemStamp>,
+}
+
+fn scan_item(
+    item_dir: &Path,
+    previous: Option<&CachedItem>,
+    manifest: Option<&WorkshopManifestItem>,
+) -> ItemScan {
+    let key = item_dir.to_string_lossy().to_string();
+    if let Some((stamp, mod_info_paths)) = previous.and_then(|item| {
+        Some((
+            serde_json::from_str::<ItemStamp>(&item.stamp).ok()?,
+            serde_json::from_str::<Vec<PathBuf>>(&item.mod_info_paths).ok()?,
+        ))
+    }) {
+        if stamp == item_stamp(item_dir, &mod_info_paths, manifest) {
+            return ItemScan {
+                key,
+                mod_info_paths,
+                walked: None,
+            };
+        }
+    }
+    let mod_info_paths = find_mod_info_paths(&key);
+    let stamp = item_stamp(item_dir, &mod_info_paths, manifest);
+    ItemScan {
+        key,
+        mod_info_paths,
+        walked: Some(stamp),
+    }
+}
+
+fn scan_change(path: &str, summary: Option<&ModSummary>) -> ModScanChange {
+    ModScanChange {
+        path: path.to_string(),
+        mod_id: summary.and_then(|summary| summary.mod_id.clone()),
+        workshop_id: summary.and_then(|summary| summary.workshop_id.clone()),
+    }
+}
+
+impl ScanCache {
+    pub(crate) fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let connection = Connection::open(path).map_err(|e| e.to_string())?;
+        connection
+            .busy_timeout(BUSY_TIMEOUT)
+            .map_err(|e| e.to_string())?;
+        let version: i32 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        if version != SCHEMA_VERSION {
+            connection
+                .execute_batch(&format!(
+                    "DROP TABLE IF EXISTS mod_info;
+                     DROP TABLE IF EXISTS item;
+                     CREATE TABLE mod_info (
+                         root TEXT NOT NULL,
+                         path TEXT NOT NULL,
+                         size INTEGER NOT NULL,
+                         mtime_ns INTEGER,
+                         file_info TEXT NOT NULL,
+                         summary TEXT NOT NULL,
+                         PRIMARY KEY (root, path)
+                     );
+                     CREATE TABLE item (
+                         root TEXT NOT NULL,
+                         path TEXT NOT NULL,
+                         stamp TEXT NOT NULL,
+                         mod_info_paths TEXT NOT NULL,
+                         PRIMARY KEY (root, path)
+                     );
+                     PRAGMA user_version = {SCHEMA_VERSION};"
+                ))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(Self { connection })
+    }
+
+    pub(crate) fn open_for_app(app: &AppHandle) -> Result<Self, String> {
+        let data_dir = app.path().app_local_data_dir().map_err(|error| {
+            format!("Could not resolve the application data directory: {error}")
+        })?;
+        Self::open(&data_dir.join(CACHE_FILE_NAME))
+    }
+
+    fn cached_entries(&self, root: &str) -> Result<HashMap<String, CachedEntry>, String> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT path, size, mtime_ns, file_info, summary FROM mod_info WHERE root = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = statement
+            .query_map(params![root], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    CachedEntry {
+                        size: row.get(1)?,
+                        mtime_ns: row.get(2)?,
+                        file_info: row.get(3)?,
+                        summary: row.get(4)?,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    fn cached_items(&self, root: &str) -> Result<HashMap<String, CachedItem>, String> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT path, stamp, mod_info_paths FROM item WHERE root = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = statement
+            .query_map(params![root], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    CachedItem {
+                        stamp: row.get(1)?,
+                        mod_info_paths: row.get(2)?,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Scans `root` like `scan_mod_folder`.  Only item folders whose stamp
+    /// moved are walked, and only `mod.info` files that are new or whose size
+    /// or mtime changed since the previous scan are parsed.
+    pub(crate) fn scan(&self, root: &str) -> Result<ModFolderScanResult, String> {
+        let mut cached = self.cached_entries(root)?;
+        let mut cached_items = self.cached_items(root)?;
+        let root_dir = Path::new(root);
+        let manifest = manifest_items_by_id(root_dir);
+        let items: Vec<ItemScan> = item_dirs(root_dir)
+            .par_iter()
+            .map(|dir| {
+                let workshop_id = dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                scan_item(
+                    dir,
+                    cached_items.get(dir.to_string_lossy().as_ref()),
+                    manifest.get(&workshop_id),
+                )
+            })
+            .collect();
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let root_info = root_dir.join("mod.info");
+        if root_info.is_file() {
+            paths.push(root_info);
+        }
+        for item in &items {
+            cached_items.remove(&item.key);
+            paths.extend(item.mod_info_paths.iter().cloned());
+        }
+
+        let mut entries: Vec<Option<(ModFileInfo, ModSummary)>> = Vec::with_capacity(paths.len());
+        let mut stale: Vec<(usize, PathBuf, Option<FileStamp>, bool)> = Vec::new();
+        let mut changes = ModScanChanges::default();
+        for (index, path) in paths.into_iter().enumerate() {
+            let key = path.to_string_lossy().to_string();
+            let stamp = file_stamp(&path);
+            let previous = cached.remove(&key);
+            let reused = match (&previous, &stamp) {
+                (Some(entry), Some(stamp))
+                    if entry.size == stamp.size
+                        && entry.mtime_ns.is_some()
+                        && entry.mtime_ns == stamp.mtime_ns =>
+                {
+                    serde_json::from_str::<ModFileInfo>(&entry.file_info)
+                        .ok()
+                        .zip(serde_json::from_str::<ModSummary>(&entry.summary).ok())
+                }
+                _ => None,
+            };
+            if reused.is_some() {
+                changes.unchanged += 1;
+            } else {
+                stale.push((index, path, stamp, previous.is_some()));
+            }
+            entries.push(reused);
+        }
+
+        let parsed = stale
+            .par_iter()
+            .map(|(_, path, _, _)| read_mod_info_entry(root, path))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|e| e.to_string())?;
+        for ((index, path, stamp, existed), (file_info, summary)) in stale.iter().zip(parsed) {
+            let key = path.to_string_lossy().to_string();
+            let change = scan_change(&key, Some(&summary));
+            if *existed {
+                changes.changed.push(change);
+            } else {
+                changes.added.push(change);
+            }
+            if let Some(stamp) = stamp {
+                transaction
+                    .execute(
+                        "INSERT OR REPLACE INTO mod_info (root, path, size, mtime_ns, file_info, summary)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            root,
+                            key,
+                            stamp.size,
+                            stamp.mtime_ns,
+                            serde_json::to_string(&file_info).map_err(|e| e.to_string())?,
+                            serde_json::to_string(&summary).map_err(|e| e.to_string())?,
+                        ],
+                    )
+                    .map_err(|e| e.to_string())?;
+            }
+            entries[*index] = Some((file_info, summary));
+        }
+
+        let mut removed: Vec<(String, CachedEntry)> = cached.into_iter().collect();
+        removed.sort_by(|a, b| a.0.cmp(&b.0));
+        for (path, entry) in &removed {
+            let summary = serde_json::from_str::<ModSummary>(&entry.summary).ok();
+            changes.removed.push(scan_change(path, summary.as_ref()));
+            transaction
+                .execute(
+                    "DELETE FROM mod_info WHERE root = ?1 AND path = ?2",
+                    params![root, path],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        for item in &items {
+            let Some(stamp) = &item.walked else {
+                continue;
+            };
+            transaction
+                .execute(
+                    "INSERT OR REPLACE INTO item (root, path, stamp, mod_info_paths)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        root,
+                        item.key,
+                        serde_json::to_string(stamp).map_err(|e| e.to_string())?,
+                        serde_json::to_string(&item.mod_info_paths).map_err(|e| e.to_string())?,
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        for path in cached_items.keys() {
+            transaction
+                .execute(
+                    "DELETE FROM item WHERE root = ?1 AND path = ?2",
+                    params![root, path],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        transaction.commit().map_err(|e| e.to_string())?;
+
+        let mut result = build_scan_result(entries.into_iter().flatten().collect());
+        result.changes = Some(changes);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn rescans_report_added_changed_and_removed_mod_info_files() {
+        let root = TestDir::new("pz-scan-cache-test");
+        let workshop = root.join("workshop");
+        let write_mod = |workshop_id: &str, content: &str| {
+            let dir = workshop.join(workshop_id).join("mods").join("Example");
+            fs::create_dir_all(&dir).expect("mod directory should be created");
+            fs::write(dir.join("mod.info"), content).expect("mod.info should be written");
+        };
+        write_mod("100", "name=First\nid=First\n");
+        write_mod("200", "name=Second\nid=Second\n");
+
+        let cache = ScanCache::open(&root.join("cache.sqlite")).expect("cache should open");
+        let workshop_path = workshop.to_string_lossy().to_string();
+        let first = cache
+            .scan(&workshop_path)
+            .expect("first scan should succeed");
+        let changes = first.changes.expect("cached scans should report changes");
+        assert_eq!(changes.added.len(), 2);
+        assert_eq!(first.summaries.len(), 2);
+
+        let second = cache.scan(&workshop_path).expect("rescan should succeed");
+        let changes = second.changes.expect("cached scans should report changes");
+        assert_eq!(changes.unchanged, 2);
+        assert!(changes.added.is_empty() && changes.changed.is_empty());
+        assert_eq!(second.summaries.len(), 2);
+
+        write_mod("100", "name=First Renamed\nid=First\n");
+        fs::remove_dir_all(workshop.join("200")).expect("mod should be removed");
+        let third = cache.scan(&workshop_path).expect("rescan should succeed");
+        let changes = third.changes.expect("cached scans should report changes");
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.changed[0].workshop_id.as_deref(), Some("100"));
+        assert_eq!(changes.removed.len(), 1);
+        assert_eq!(changes.removed[0].mod_id.as_deref(), Some("Second"));
+        assert_eq!(third.summaries.len(), 1);
+        assert_eq!(third.summaries[0].name, "First Renamed");
+    }
+
+    #[test]
+    fn walks_an_item_folder_again_only_when_its_stamp_moves() {
+        let root = TestDir::new("pz-scan-cache-item-test");
+        let item = root.join("100");
+        let mod_dir = item.join("mods").join("Example");
+        root.write("100/mods/Example/mod.info", "name=Example\nid=Example\n");
+        let remember = |scan: &ItemScan| CachedItem {
+            stamp: serde_json::to_string(scan.walked.as_ref().expect("item should be walked"))
+                .expect("stamp should serialize"),
+            mod_info_paths: serde_json::to_string(&scan.mod_info_paths)
+                .expect("paths should serialize"),
+        };
+
+        let first = scan_item(&item, None, None);
+        assert_eq!(first.mod_info_paths, [mod_dir.join("mod.info")]);
+        let cached = remember(&first);
+        let again = scan_item(&item, Some(&cached), None);
+        assert!(again.walked.is_none());
+        assert_eq!(again.mod_info_paths, first.mod_info_paths);
+
+        root.write("100/mods/Example/42/mod.info", "name=Example\nid=Example\n");
+        let versioned = scan_item(&item, Some(&cached), None);
+        assert_eq!(versioned.mod_info_paths.len(), 2);
+
+        let updated = WorkshopManifestItem {
+            workshop_id: "100".to_string(),
+            size: Some(10),
+            time_updated: Some("2026-01-01T00:00:00Z".to_string()),
+            installed: true,
+            needs_update: false,
+        };
+        let cached = remember(&versioned);
+        assert!(
+            scan_item(&item, Some(&cached), Some(&updated))
+                .walked
+                .is_some()
+        );
+    }
+}
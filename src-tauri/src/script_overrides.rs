@@ -0,0 +1,286 @@
+use crate::loadout::{LoadoutMod, ordered_media_sources};
+use crate::media::script_files;
+use crate::mod_scanner::mod_content_dirs;
+use crate::timing::scoped_timer;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Script block types whose later definitions replace earlier ones.
+const OVERRIDABLE_KINDS: &[&str] = &[
+    "item",
+    "recipe",
+    "fixing",
+    "vehicle",
+    "model",
+    "sound",
+    "evolvedrecipe",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ScriptBlock {
+    pub kind: String,
+    pub module: String,
+    pub name: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptDefinition {
+    /// `None` for the base game's own scripts.
+    pub mod_id: Option<String>,
+    pub file_path: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptOverride {
+    pub kind: String,
+    pub full_type: String,
+    /// Every definition in load order; the last one is effective.
+    pub definitions: Vec<ScriptDefinition>,
+    pub effective: ScriptDefinition,
+}
+
+/// Blanks out `/* */` and `//` comments while keeping line breaks, so byte
+/// offsets and line numbers still match the original file.
+pub(crate) fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_block = false;
+    let mut in_line = false;
+    while let Some(c) = chars.next() {
+        if in_block {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                in_block = false;
+                out.push_str("  ");
+            } else {
+                out.push(if c == '\n' { '\n' } else { ' ' });
+            }
+            continue;
+        }
+        if in_line {
+            if c == '\n' {
+                in_line = false;
+                out.push('\n');
+            } else {
+                out.push(' ');
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('*')) => {
+                chars.next();
+                in_block = true;
+                out.push_str("  ");
+            }
+            ('/', Some('/')) => {
+                chars.next();
+                in_line = true;
+                out.push_str("  ");
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Extracts the `module <Name> { <kind> <name> { ... } }` definitions of a
+/// script file.  Block bodies are skipped; only their headers matter here.
+pub(crate) fn parse_script_blocks(text: &str) -> Vec<ScriptBlock> {
+    let cleaned = strip_comments(text);
+    let mut blocks = Vec::new();
+    let mut module: Option<String> = None;
+    let mut depth = 0usize;
+    let mut header = String::new();
+    let mut header_line = 1usize;
+    let mut line = 1usize;
+
+    for c in cleaned.chars() {
+        match c {
+            '{' => {
+                let text = header.trim();
+                if depth == 0 {
+                    module = text
+                        .strip_prefix("module")
+                        .filter(|rest| rest.starts_with(char::is_whitespace))
+                        .map(|rest| rest.trim().to_string());
+                } else if depth == 1 {
+                    if let Some(module_name) = &module {
+                        let (kind, name) =
+                            text.split_once(char::is_whitespace).unwrap_or((text, ""));
+                        let kind = kind.to_ascii_lowercase();
+                        let name = name.trim();
+                        if !name.is_empty() && OVERRIDABLE_KINDS.contains(&kind.as_str()) {
+                            blocks.push(ScriptBlock {
+                                kind,
+                                module: module_name.clone(),
+                                name: name.to_string(),
+                                line: header_line,
+                            });
+                        }
+                    }
+                }
+                depth += 1;
+                header.clear();
+            }
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    module = None;
+                }
+                header.clear();
+            }
+            ',' | ';' if depth == 1 => header.clear(),
+            _ if depth > 1 => {}
+            _ => {
+                if header.trim().is_empty() && !c.is_whitespace() {
+                    header_line = line;
+                }
+                header.push(c);
+            }
+        }
+        if c == '\n' {
+            line += 1;
+        }
+    }
+    blocks
+}
+
+fn parse_script_file(path: &Path) -> Vec<ScriptBlock> {
+    fs::read(path)
+        .map(|bytes| parse_script_blocks(&String::from_utf8_lossy(&bytes)))
+        .unwrap_or_default()
+}
+
+/// Reports every script type defined more than once across `sources`, which
+/// must be given in load order as `(mod id, media directory)` pairs.
+pub(crate) fn find_script_overrides(sources: &[(Option<String>, PathBuf)]) -> Vec<ScriptOverride> {
+    let files: Vec<(usize, PathBuf)> = sources
+        .iter()
+        .enumerate()
+        .flat_map(|(index, (_, media_dir))| {
+            script_files(media_dir)
+                .into_iter()
+                .map(move |path| (index, path))
+        })
+        .collect();
+    let parsed: Vec<(usize, PathBuf, Vec<ScriptBlock>)> = files
+        .into_par_iter()
+        .map(|(index, path)| {
+            let blocks = parse_script_file(&path);
+            (index, path, blocks)
+        })
+        .collect();
+
+    let mut by_type: HashMap<(String, String), Vec<ScriptDefinition>> = HashMap::new();
+    for (index, path, blocks) in parsed {
+        for block in blocks {
+            let key = (block.kind, format!("{}.{}", block.module, block.name));
+            by_type.entry(key).or_default().push(ScriptDefinition {
+                mod_id: sources[index].0.clone(),
+                file_path: path.to_string_lossy().to_string(),
+                line: block.line,
+            });
+        }
+    }
+
+    let mut overrides: Vec<ScriptOverride> = by_type
+        .into_iter()
+        .filter(|(_, definitions)| definitions.len() > 1)
+        .filter_map(|((kind, full_type), definitions)| {
+            let effective = definitions.last()?.clone();
+            Some(ScriptOverride {
+                kind,
+                full_type,
+                definitions,
+                effective,
+            })
+        })
+        .collect();
+    overrides.sort_by(|a, b| {
+        a.full_type
+            .to_lowercase()
+            .cmp(&b.full_type.to_lowercase())
+            .then_with(|| a.kind.cmp(&b.kind))
+    });
+    overrides
+}
+
+#[tauri::command]
+pub fn analyze_script_overrides(
+    media_dir: String,
+    mods: Vec<LoadoutMod>,
+) -> Result<Vec<ScriptOverride>, String> {
+    let _timer = scoped_timer("analyze_script_overrides");
+    let mut sources: Vec<(Option<String>, PathBuf)> = Vec::new();
+    if !media_dir.trim().is_empty() {
+        sources.push((None, PathBuf::from(media_dir.trim())));
+    }
+    for source in ordered_media_sources(&mods) {
+        for dir in mod_content_dirs(Path::new(&source.mod_info_path)) {
+            sources.push((Some(source.mod_id.clone()), dir.join("media")));
+        }
+    }
+    Ok(find_script_overrides(&sources))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn parses_module_blocks_with_comments_and_multi_word_names() {
+        let script = "/* header\n comment */\nmodule Base\n{\n    imports { Farming }\n\n    item Axe\n    {\n        Weight = 3, // heavy\n        /* item Fake { } */\n    }\n\n    recipe Make Spear\n    {\n        destroy Plank,\n    }\n    template Hidden { }\n}\n";
+        let blocks = parse_script_blocks(script);
+        assert_eq!(
+            blocks,
+            vec![
+                ScriptBlock {
+                    kind: "item".to_string(),
+                    module: "Base".to_string(),
+                    name: "Axe".to_string(),
+                    line: 7,
+                },
+                ScriptBlock {
+                    kind: "recipe".to_string(),
+                    module: "Base".to_string(),
+                    name: "Make Spear".to_string(),
+                    line: 13,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_types_defined_more_than_once_with_the_last_one_effective() {
+        let root = TestDir::new("pz-script-overrides-test");
+        root.write(
+            "vanilla/scripts/items.txt",
+            "module Base { item Axe { } item Pan { } }",
+        );
+        root.write("modA/media/scripts/axe.txt", "module Base { item Axe { } }");
+        root.write(
+            "modB/media/scripts/axe.txt",
+            "module Base { item Axe { } recipe Axe { } }",
+        );
+
+        let overrides = find_script_overrides(&[
+            (None, root.join("vanilla")),
+            (Some("A".to_string()), root.join("modA/media")),
+            (Some("B".to_string()), root.join("modB/media")),
+        ]);
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].full_type, "Base.Axe");
+        assert_eq!(overrides[0].kind, "item");
+        assert_eq!(overrides[0].definitions.len(), 3);
+        assert_eq!(overrides[0].definitions[0].mod_id, None);
+        assert_eq!(overrides[0].effective.mod_id.as_deref(), Some("B"));
+    }
+}
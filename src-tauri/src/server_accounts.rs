@@ -0,0 +1,641 @@
+use crate::pz_compat::{server_db_path, validate_server_name};
+use crate::server_process::ServerProcessManager;
+use crate::timing::scoped_timer;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, TransactionBehavior, params};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::State;
+
+const ACCESS_LEVELS: [&str; 6] = ["admin", "moderator", "overseer", "gm", "observer", "none"];
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerAccount {
+    pub id: i64,
+    pub username: String,
+    pub access_level: String,
+    pub steam_id: Option<String>,
+    pub banned: bool,
+    pub last_connection: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ServerBanKind {
+    SteamId,
+    Ip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBan {
+    pub kind: ServerBanKind,
+    pub value: String,
+    pub username: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerAccounts {
+    pub path: String,
+    pub accounts: Vec<ServerAccount>,
+    pub bans: Vec<ServerBan>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerAccountUpdate {
+    pub access_level: Option<String>,
+    pub steam_id: Option<String>,
+    pub banned: Option<bool>,
+}
+
+fn table_columns(connection: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut statement = connection
+        .prepare(&format!("PRAGMA table_info({table})"))
+        .map_err(|error| format!("Unable to read the {table} table: {error}"))?;
+    let columns = statement
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|error| format!("Unable to read the {table} table: {error}"))?
+        .filter_map(Result::ok)
+        .map(|column| column.to_ascii_lowercase())
+        .collect();
+    Ok(columns)
+}
+
+fn has_column(columns: &[String], column: &str) -> bool {
+    columns.iter().any(|existing| existing == column)
+}
+
+fn open_accounts_db(user_dir: &str, server_name: &str, write: bool) -> Result<Connection, String> {
+    let name = validate_server_name(server_name)?;
+    let path = server_db_path(user_dir, name);
+    if !path.is_file() {
+        return Err(format!(
+            "Server \"{name}\" has no account database yet; start it once to create {}.",
+            path.display()
+        ));
+    }
+    let flags = if write {
+        OpenFlags::SQLITE_OPEN_READ_WRITE
+    } else {
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+    };
+    Connection::open_with_flags(&path, flags)
+        .map_err(|error| format!("Unable to open {}: {error}", path.display()))
+}
+
+/// Runs `edit` in one exclusive transaction and returns the accounts as they
+/// are afterwards.  A server started from this app is refused outright; any
+/// other server is only caught while it holds a lock on the database.
+fn edit_accounts(
+    manager: &ServerProcessManager,
+    user_dir: &str,
+    server_name: &str,
+    edit: impl FnOnce(&Connection) -> Result<(), String>,
+) -> Result<ServerAccounts, String> {
+    let name = server_name.trim();
+    if manager.is_running(name) {
+        return Err(format!(
+            "Stop \"{name}\" before editing its accounts; the running server owns the database."
+        ));
+    }
+    let mut connection = open_accounts_db(user_dir, server_name, true)?;
+    connection
+        .busy_timeout(Duration::ZERO)
+        .map_err(|error| error.to_string())?;
+    let transaction = connection
+        .transaction_with_behavior(TransactionBehavior::Exclusive)
+        .map_err(|_| {
+            format!(
+                "The account database of \"{name}\" is in use; stop the server before editing \
+                 its accounts. Only servers started from this app are detected when the \
+                 database is idle."
+            )
+        })?;
+    edit(&transaction)?;
+    transaction
+        .commit()
+        .map_err(|error| format!("Unable to save the account changes: {error}"))?;
+    read_server_accounts(&connection)
+}
+
+fn normalize_access_level(level: &str) -> Result<String, String> {
+    let level = level.trim().to_ascii_lowercase();
+    if ACCESS_LEVELS.contains(&level.as_str()) {
+        Ok(level)
+    } else {
+        Err(format!(
+            "\"{level}\" is not an access level; use one of {}.",
+            ACCESS_LEVELS.join(", ")
+        ))
+    }
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads a BOOLEAN column, which older databases may hold as text.
+fn flag(row: &rusqlite::Row, index: usize) -> rusqlite::Result<bool> {
+    Ok(match row.get::<_, Value>(index)? {
+        Value::Integer(value) => value != 0,
+        Value::Text(value) => matches!(value.trim(), "1" | "true" | "TRUE" | "True"),
+        _ => false,
+    })
+}
+
+fn read_accounts(connection: &Connection) -> Result<Vec<ServerAccount>, String> {
+    let columns = table_columns(connection, "whitelist")?;
+    if columns.is_empty() {
+        return Err("The database has no whitelist table.".to_string());
+    }
+    // Older builds only have the admin flag; newer ones add accesslevel.
+    let pick = |column: &str| {
+        if has_column(&columns, column) {
+            column.to_string()
+        } else {
+            "NULL".to_string()
+        }
+    };
+    let sql = format!(
+        "SELECT id, username, {}, {}, {}, {}, {} FROM whitelist ORDER BY username COLLATE NOCASE",
+        pick("accesslevel"),
+        pick("admin"),
+        pick("steamid"),
+        pick("banned"),
+        pick("lastconnection"),
+    );
+    let mut statement = connection
+        .prepare(&sql)
+        .map_err(|error| format!("Unable to read whitelist: {error}"))?;
+    let rows = statement
+        .query_map([], |row| {
+            let access_level = row
+                .get::<_, Option<String>>(2)?
+                .filter(|level| !level.trim().is_empty());
+            let admin = flag(row, 3)?;
+            Ok(ServerAccount {
+                id: row.get(0)?,
+                username: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                access_level: access_level
+                    .map(|level| level.to_ascii_lowercase())
+                    .unwrap_or_else(|| if admin { "admin" } else { "none" }.to_string()),
+                steam_id: row.get::<_, Option<String>>(4)?,
+                banned: flag(row, 5)?,
+                last_connection: row.get::<_, Option<String>>(6)?,
+            })
+        })
+        .map_err(|error| format!("Unable to read whitelist: {error}"))?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|error| format!("Unable to read whitelist: {error}"))
+}
+
+fn read_bans(connection: &Connection) -> Result<Vec<ServerBan>, String> {
+    let mut bans = Vec::new();
+    if !table_columns(connection, "bannedid")?.is_empty() {
+        let mut statement = connection
+            .prepare("SELECT steamid, reason FROM bannedid ORDER BY steamid")
+            .map_err(|error| format!("Unable to read bannedid: {error}"))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(ServerBan {
+                    kind: ServerBanKind::SteamId,
+                    value: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                    username: None,
+                    reason: row.get(1)?,
+                })
+            })
+            .map_err(|error| format!("Unable to read bannedid: {error}"))?;
+        for ban in rows {
+            bans.push(ban.map_err(|error| format!("Unable to read bannedid: {error}"))?);
+        }
+    }
+    if !table_columns(connection, "bannedip")?.is_empty() {
+        let mut statement = connection
+            .prepare("SELECT ip, username, reason FROM bannedip ORDER BY ip")
+            .map_err(|error| format!("Unable to read bannedip: {error}"))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(ServerBan {
+                    kind: ServerBanKind::Ip,
+                    value: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                    username: row.get(1)?,
+                    reason: row.get(2)?,
+                })
+            })
+            .map_err(|error| format!("Unable to read bannedip: {error}"))?;
+        for ban in rows {
+            bans.push(ban.map_err(|error| format!("Unable to read bannedip: {error}"))?);
+        }
+    }
+    Ok(bans)
+}
+
+fn read_server_accounts(connection: &Connection) -> Result<ServerAccounts, String> {
+    Ok(ServerAccounts {
+        path: connection.path().unwrap_or_default().to_string(),
+        accounts: read_accounts(connection)?,
+        bans: read_bans(connection)?,
+    })
+}
+
+fn find_account(connection: &Connection, username: &str) -> Result<Option<i64>, String> {
+    connection
+        .query_row(
+            "SELECT id FROM whitelist WHERE username = ?1 COLLATE NOCASE",
+            params![username],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| format!("Unable to read whitelist: {error}"))
+}
+
+/// Adds a whitelist row without a password; the game stores the password
+/// the player first logs in with.
+fn insert_account(
+    connection: &Connection,
+    server_name: &str,
+    username: &str,
+    access_level: &str,
+    steam_id: Option<&str>,
+) -> Result<(), String> {
+    let username = username.trim();
+    if username.is_empty() {
+        return Err("Username is required.".to_string());
+    }
+    if find_account(connection, username)?.is_some() {
+        return Err(format!("Account \"{username}\" already exists."));
+    }
+    let access_level = normalize_access_level(access_level)?;
+    let columns = table_columns(connection, "whitelist")?;
+    let mut names = vec!["username".to_string()];
+    let mut values = vec![username.to_string()];
+    let mut push = |column: &str, value: String| {
+        if has_column(&columns, column) {
+            names.push(column.to_string());
+            values.push(value);
+        }
+    };
+    push("world", server_name.to_string());
+    push("accesslevel", access_level.clone());
+    push(
+        "admin",
+        if access_level == "admin" { "1" } else { "0" }.to_string(),
+    );
+    if let Some(steam_id) = steam_id {
+        push("steamid", steam_id.to_string());
+    }
+    let placeholders = (1..=values.len())
+        .map(|index| format!("?{index}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    connection
+        .execute(
+            &format!(
+                "INSERT INTO whitelist ({}) VALUES ({placeholders})",
+                names.join(", ")
+            ),
+            rusqlite::params_from_iter(values),
+        )
+        .map_err(|error| format!("Unable to add account \"{username}\": {error}"))?;
+    Ok(())
+}
+
+fn update_account(
+    connection: &Connection,
+    username: &str,
+    update: ServerAccountUpdate,
+) -> Result<(), String> {
+    let id = find_account(connection, username.trim())?
+        .ok_or_else(|| format!("Account \"{}\" does not exist.", username.trim()))?;
+    let columns = table_columns(connection, "whitelist")?;
+    let require = |column: &str| {
+        if has_column(&columns, column) {
+            Ok(())
+        } else {
+            Err(format!("This database's whitelist has no {column} column."))
+        }
+    };
+    // Check every field first so an unsupported one fails before any write.
+    let level = update
+        .access_level
+        .as_deref()
+        .map(normalize_access_level)
+        .transpose()?;
+    if level.is_some() {
+        require("accesslevel")?;
+    }
+    if update.steam_id.is_some() {
+        require("steamid")?;
+    }
+    if update.banned.is_some() {
+        require("banned")?;
+    }
+    if let Some(level) = level {
+        connection
+            .execute(
+                "UPDATE whitelist SET accesslevel = ?1 WHERE id = ?2",
+                params![level, id],
+            )
+            .map_err(|error| format!("Unable to update the access level: {error}"))?;
+        if has_column(&columns, "admin") {
+            connection
+                .execute(
+                    "UPDATE whitelist SET admin = ?1 WHERE id = ?2",
+                    params![level == "admin", id],
+                )
+                .map_err(|error| format!("Unable to update the access level: {error}"))?;
+        }
+    }
+    if let Some(steam_id) = update.steam_id {
+        connection
+            .execute(
+                "UPDATE whitelist SET steamid = ?1 WHERE id = ?2",
+                params![optional_text(Some(steam_id)), id],
+            )
+            .map_err(|error| format!("Unable to update the Steam ID: {error}"))?;
+    }
+    if let Some(banned) = update.banned {
+        connection
+            .execute(
+                "UPDATE whitelist SET banned = ?1 WHERE id = ?2",
+                params![banned, id],
+            )
+            .map_err(|error| format!("Unable to update the ban flag: {error}"))?;
+    }
+    Ok(())
+}
+
+fn insert_ban(connection: &Connection, ban: ServerBan) -> Result<(), String> {
+    let value = ban.value.trim();
+    if value.is_empty() {
+        return Err("The ban needs a Steam ID or IP address.".to_string());
+    }
+    let reason = optional_text(ban.reason).unwrap_or_default();
+    let result = match ban.kind {
+        ServerBanKind::SteamId => {
+            if !value.chars().all(|character| character.is_ascii_digit()) {
+                return Err(format!("\"{value}\" is not a Steam ID."));
+            }
+            connection.execute(
+                "INSERT INTO bannedid (steamid, reason) SELECT ?1, ?2 \
+                 WHERE NOT EXISTS (SELECT 1 FROM bannedid WHERE steamid = ?1)",
+                params![value, reason],
+            )
+        }
+        ServerBanKind::Ip => {
+            if value.parse::<std::net::IpAddr>().is_err() {
+                return Err(format!("\"{value}\" is not an IP address."));
+            }
+            connection.execute(
+                "INSERT INTO bannedip (ip, username, reason) SELECT ?1, ?2, ?3 \
+                 WHERE NOT EXISTS (SELECT 1 FROM bannedip WHERE ip = ?1)",
+                params![
+                    value,
+                    optional_text(ban.username).unwrap_or_default(),
+                    reason
+                ],
+            )
+        }
+    };
+    result
+        .map(|_| ())
+        .map_err(|error| format!("Unable to add the ban: {error}"))
+}
+
+fn delete_ban(connection: &Connection, kind: ServerBanKind, value: &str) -> Result<bool, String> {
+    let sql = match kind {
+        ServerBanKind::SteamId => "DELETE FROM bannedid WHERE steamid = ?1",
+        ServerBanKind::Ip => "DELETE FROM bannedip WHERE ip = ?1",
+    };
+    connection
+        .execute(sql, params![value.trim()])
+        .map(|removed| removed > 0)
+        .map_err(|error| format!("Unable to remove the ban: {error}"))
+}
+
+#[tauri::command]
+pub fn list_server_accounts(
+    user_dir: String,
+    server_name: String,
+) -> Result<ServerAccounts, String> {
+    let _timer = scoped_timer("list_server_accounts");
+    read_server_accounts(&open_accounts_db(&user_dir, &server_name, false)?)
+}
+
+#[tauri::command]
+pub fn add_server_account(
+    manager: State<'_, ServerProcessManager>,
+    user_dir: String,
+    server_name: String,
+    username: String,
+    access_level: Option<String>,
+    steam_id: Option<String>,
+) -> Result<ServerAccounts, String> {
+    let _timer = scoped_timer("add_server_account");
+    edit_accounts(&manager, &user_dir, &server_name, |connection| {
+        insert_account(
+            connection,
+            server_name.trim(),
+            &username,
+            access_level.as_deref().unwrap_or("none"),
+            optional_text(steam_id).as_deref(),
+        )
+    })
+}
+
+#[tauri::command]
+pub fn update_server_account(
+    manager: State<'_, ServerProcessManager>,
+    user_dir: String,
+    server_name: String,
+    username: String,
+    update: ServerAccountUpdate,
+) -> Result<ServerAccounts, String> {
+    let _timer = scoped_timer("update_server_account");
+    edit_accounts(&manager, &user_dir, &server_name, |connection| {
+        update_account(connection, &username, update)
+    })
+}
+
+#[tauri::command]
+pub fn remove_server_account(
+    manager: State<'_, ServerProcessManager>,
+    user_dir: String,
+    server_name: String,
+    username: String,
+) -> Result<ServerAccounts, String> {
+    let _timer = scoped_timer("remove_server_account");
+    edit_accounts(&manager, &user_dir, &server_name, |connection| {
+        let removed = connection
+            .execute(
+                "DELETE FROM whitelist WHERE username = ?1 COLLATE NOCASE",
+                params![username.trim()],
+            )
+            .map_err(|error| {
+                format!("Unable to remove account \"{}\": {error}", username.trim())
+            })?;
+        if removed == 0 {
+            return Err(format!("Account \"{}\" does not exist.", username.trim()));
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn add_server_ban(
+    manager: State<'_, ServerProcessManager>,
+    user_dir: String,
+    server_name: String,
+    ban: ServerBan,
+) -> Result<ServerAccounts, String> {
+    let _timer = scoped_timer("add_server_ban");
+    edit_accounts(&manager, &user_dir, &server_name, |connection| {
+        insert_ban(connection, ban)
+    })
+}
+
+#[tauri::command]
+pub fn remove_server_ban(
+    manager: State<'_, ServerProcessManager>,
+    user_dir: String,
+    server_name: String,
+    kind: ServerBanKind,
+    value: String,
+) -> Result<ServerAccounts, String> {
+    let _timer = scoped_timer("remove_server_ban");
+    edit_accounts(&manager, &user_dir, &server_name, |connection| {
+        if !delete_ban(connection, kind, &value)? {
+            return Err(format!("No ban matches \"{}\".", value.trim()));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+    use std::fs;
+
+    #[test]
+    fn edits_whitelist_accounts_and_bans() {
+        let root = TestDir::new("pz-server-accounts-test");
+        let user_dir = root.to_string_lossy().to_string();
+        let path = server_db_path(&user_dir, "servertest");
+        fs::create_dir_all(path.parent().expect("database should have a folder"))
+            .expect("db folder should be created");
+        Connection::open(&path)
+            .expect("database should be created")
+            .execute_batch(
+                "CREATE TABLE whitelist (id INTEGER PRIMARY KEY, world TEXT, username TEXT, \
+                 password TEXT, admin BOOLEAN DEFAULT false, banned BOOLEAN DEFAULT false, \
+                 lastConnection TEXT, accesslevel TEXT, steamid TEXT);
+                 CREATE TABLE bannedid (steamid TEXT, reason TEXT);
+                 CREATE TABLE bannedip (ip TEXT, username TEXT, reason TEXT);
+                 INSERT INTO whitelist (world, username, admin) VALUES ('servertest', 'admin', 1);",
+            )
+            .expect("schema should be created");
+
+        let connection = open_accounts_db(&user_dir, "servertest", true)
+            .expect("database should open for writing");
+        insert_account(
+            &connection,
+            "servertest",
+            "Alice",
+            "Moderator",
+            Some("76561198000000001"),
+        )
+        .expect("account should be added");
+        assert!(insert_account(&connection, "servertest", "alice", "none", None).is_err());
+        assert!(insert_account(&connection, "servertest", "Bob", "owner", None).is_err());
+        update_account(
+            &connection,
+            "ALICE",
+            ServerAccountUpdate {
+                access_level: Some("admin".to_string()),
+                banned: Some(true),
+                ..ServerAccountUpdate::default()
+            },
+        )
+        .expect("account should be updated");
+        insert_ban(
+            &connection,
+            ServerBan {
+                kind: ServerBanKind::Ip,
+                value: "10.0.0.7".to_string(),
+                username: Some("Alice".to_string()),
+                reason: Some("griefing".to_string()),
+            },
+        )
+        .expect("ip ban should be added");
+        assert!(
+            insert_ban(
+                &connection,
+                ServerBan {
+                    kind: ServerBanKind::SteamId,
+                    value: "not-a-steam-id".to_string(),
+                    username: None,
+                    reason: None,
+                },
+            )
+            .is_err()
+        );
+
+        let accounts = read_server_accounts(
+            &open_accounts_db(&user_dir, "servertest", false).expect("database should open"),
+        )
+        .expect("accounts should be read");
+        let names = accounts
+            .accounts
+            .iter()
+            .map(|account| {
+                (
+                    account.username.as_str(),
+                    account.access_level.as_str(),
+                    account.banned,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, [("admin", "admin", false), ("Alice", "admin", true)]);
+        assert_eq!(accounts.bans.len(), 1);
+        assert_eq!(accounts.bans[0].username.as_deref(), Some("Alice"));
+        assert!(
+            delete_ban(&connection, ServerBanKind::Ip, "10.0.0.7").expect("ban should be removed")
+        );
+        assert!(
+            !delete_ban(&connection, ServerBanKind::Ip, "10.0.0.7").expect("delete should run")
+        );
+
+        let manager = ServerProcessManager::default();
+        let failed = edit_accounts(&manager, &user_dir, "servertest", |connection| {
+            insert_account(connection, "servertest", "Carol", "none", None)?;
+            update_account(
+                connection,
+                "Carol",
+                ServerAccountUpdate {
+                    access_level: Some("nobody".to_string()),
+                    ..ServerAccountUpdate::default()
+                },
+            )
+        });
+        assert!(failed.is_err());
+        connection
+            .execute_batch("BEGIN EXCLUSIVE")
+            .expect("test should lock the database");
+        let locked = edit_accounts(&manager, &user_dir, "servertest", |_| Ok(()))
+            .expect_err("a locked database should be refused");
+        assert!(locked.contains("in use"));
+        connection
+            .execute_batch("ROLLBACK")
+            .expect("test should unlock the database");
+        let accounts = edit_accounts(&manager, &user_dir, "servertest", |_| Ok(()))
+            .expect("an idle database should open");
+        assert_eq!(accounts.accounts.len(), 2);
+    }
+}
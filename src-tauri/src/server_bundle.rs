@@ -0,0 +1,415 @@
+use crate::loadout::workshop_providers;
+use crate::mod_scanner::normalize_mod_ref;
+use crate::models::ModSummary;
+use crate::pz_compat::{
+    GAME_VERSION, SERVER_CONFIG_FILES, SERVER_INI_FILE, server_config_path, validate_server_name,
+};
+use crate::pz_version::{PzVersion, game_version};
+use crate::server_files::rewrite_server_references;
+use crate::server_ini::ServerIni;
+use crate::timing::scoped_timer;
+use crate::utils::{ensure_parent_dir, to_iso_string, write_atomically};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const BUNDLE_FORMAT: &str = "pz-server-bundle";
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBundleManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub game_version: String,
+    pub server_name: String,
+    pub exported_at: Option<String>,
+}
+
+/// One server file, keyed by its `SERVER_CONFIG_FILES` name template.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBundleFile {
+    pub file: String,
+    pub content_base64: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBundleMod {
+    pub mod_id: String,
+    pub workshop_id: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBundle {
+    pub manifest: ServerBundleManifest,
+    pub files: Vec<ServerBundleFile>,
+    pub mods: Vec<ServerBundleMod>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBundleReport {
+    pub manifest: ServerBundleManifest,
+    pub files: Vec<String>,
+    pub mods: Vec<ServerBundleMod>,
+    pub missing_mods: Vec<ServerBundleMod>,
+    pub warnings: Vec<String>,
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(';')
+        .map(normalize_mod_ref)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Pairs every `Mods=` id with the workshop item that ships it, preferring
+/// items already listed in `WorkshopItems=`.
+fn resolve_bundle_mods(ini: &ServerIni, installed: &[ModSummary]) -> Vec<ServerBundleMod> {
+    let workshop_items = split_list(ini.get("WorkshopItems"));
+    let providers = workshop_providers(installed);
+    let mut seen = HashSet::new();
+    split_list(ini.get("Mods"))
+        .into_iter()
+        .filter(|mod_id| seen.insert(mod_id.to_ascii_lowercase()))
+        .map(|mod_id| {
+            let key = mod_id.to_ascii_lowercase();
+            let workshop_id = providers.get(&key).and_then(|ids| {
+                ids.iter()
+                    .find(|id| workshop_items.contains(id))
+                    .or_else(|| ids.iter().next())
+                    .cloned()
+            });
+            let name = installed
+                .iter()
+                .find(|summary| {
+                    summary
+                        .mod_id
+                        .as_deref()
+                        .is_some_and(|id| id.trim().eq_ignore_ascii_case(&mod_id))
+                })
+                .map(|summary| summary.name.clone());
+            ServerBundleMod {
+                mod_id,
+                workshop_id,
+                name,
+            }
+        })
+        .collect()
+}
+
+fn build_bundle(
+    user_dir: &str,
+    server_name: &str,
+    installed: &[ModSummary],
+) -> Result<ServerBundle, String> {
+    let name = validate_server_name(server_name)?;
+    let ini_path = server_config_path(user_dir, name, SERVER_INI_FILE);
+    if !ini_path.is_file() {
+        return Err(format!("Server \"{name}\" does not exist."));
+    }
+    let mut files = Vec::new();
+    for file in SERVER_CONFIG_FILES {
+        let path = server_config_path(user_dir, name, file);
+        if !path.is_file() {
+            continue;
+        }
+        let bytes = fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        files.push(ServerBundleFile {
+            file: file.to_string(),
+            content_base64: BASE64.encode(bytes),
+        });
+    }
+    Ok(ServerBundle {
+        manifest: ServerBundleManifest {
+            format: BUNDLE_FORMAT.to_string(),
+            format_version: BUNDLE_FORMAT_VERSION,
+            game_version: GAME_VERSION.to_string(),
+            server_name: name.to_string(),
+            exported_at: to_iso_string(SystemTime::now()),
+        },
+        files,
+        mods: resolve_bundle_mods(&ServerIni::read(&ini_path)?, installed),
+    })
+}
+
+struct DecodedBundle {
+    files: Vec<(String, Vec<u8>)>,
+    warnings: Vec<String>,
+}
+
+/// Checks the manifest and decodes the files, rejecting anything a newer or
+/// foreign exporter could have put in the bundle.
+fn decode_bundle(bundle: &ServerBundle) -> Result<DecodedBundle, String> {
+    let manifest = &bundle.manifest;
+    if manifest.format != BUNDLE_FORMAT {
+        return Err("This file is not a server bundle.".to_string());
+    }
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "The bundle uses format {}; this version reads up to {BUNDLE_FORMAT_VERSION}.",
+            manifest.format_version
+        ));
+    }
+    validate_server_name(&manifest.server_name)?;
+    let mut warnings = Vec::new();
+    let bundle_version = PzVersion::parse(&manifest.game_version)
+        .ok_or_else(|| format!("\"{}\" is not a game version.", manifest.game_version))?;
+    if bundle_version.major != game_version().major {
+        return Err(format!(
+            "The bundle was made for build {bundle_version}; build {GAME_VERSION} cannot use its settings."
+        ));
+    }
+    if bundle_version != game_version() {
+        warnings.push(format!(
+            "The bundle was made for build {bundle_version}, not {GAME_VERSION}."
+        ));
+    }
+    let mut files = Vec::new();
+    for entry in &bundle.files {
+        if !SERVER_CONFIG_FILES.contains(&entry.file.as_str()) {
+            return Err(format!(
+                "The bundle contains an unknown file \"{}\".",
+                entry.file
+            ));
+        }
+        if files.iter().any(|(file, _)| file == &entry.file) {
+            return Err(format!("The bundle contains \"{}\" twice.", entry.file));
+        }
+        let bytes = BASE64
+            .decode(entry.content_base64.trim())
+            .map_err(|error| format!("\"{}\" is not valid base64: {error}", entry.file))?;
+        files.push((entry.file.clone(), bytes));
+    }
+    if !files.iter().any(|(file, _)| file == SERVER_INI_FILE) {
+        return Err("The bundle has no server INI.".to_string());
+    }
+    Ok(DecodedBundle { files, warnings })
+}
+
+fn missing_mods(mods: &[ServerBundleMod], installed: &[ModSummary]) -> Vec<ServerBundleMod> {
+    let local: HashSet<String> = installed
+        .iter()
+        .filter_map(|summary| summary.mod_id.as_deref())
+        .map(|id| id.trim().to_ascii_lowercase())
+        .collect();
+    mods.iter()
+        .filter(|entry| !local.contains(&entry.mod_id.to_ascii_lowercase()))
+        .cloned()
+        .collect()
+}
+
+fn read_bundle(path: &str) -> Result<ServerBundle, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    serde_json::from_str(&text).map_err(|error| format!("The bundle could not be read: {error}"))
+}
+
+/// Writes every file next to its target first and only then moves them into
+/// place, so a failed import leaves no half-created server behind.
+fn write_all_or_nothing(contents: &[(PathBuf, Vec<u8>)]) -> Result<(), String> {
+    let temp_path = |path: &Path| {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        path.with_file_name(format!(".{file_name}.import.tmp"))
+    };
+    let remove_all = |paths: &mut dyn Iterator<Item = PathBuf>| {
+        for path in paths {
+            let _ = fs::remove_file(path);
+        }
+    };
+    for (index, (path, bytes)) in contents.iter().enumerate() {
+        if let Err(error) = ensure_parent_dir(path)
+            .and_then(|_| fs::write(temp_path(path), bytes).map_err(|e| e.to_string()))
+        {
+            remove_all(&mut contents[..=index].iter().map(|(path, _)| temp_path(path)));
+            return Err(format!("{}: {error}", path.display()));
+        }
+    }
+    for (index, (path, _)) in contents.iter().enumerate() {
+        if let Err(error) = fs::rename(temp_path(path), path) {
+            remove_all(&mut contents[..index].iter().map(|(path, _)| path.clone()));
+            remove_all(&mut contents[index..].iter().map(|(path, _)| temp_path(path)));
+            return Err(format!("{}: {error}", path.display()));
+        }
+    }
+    Ok(())
+}
+
+fn import_bundle(
+    user_dir: &str,
+    bundle: &ServerBundle,
+    new_name: &str,
+    installed: &[ModSummary],
+) -> Result<ServerBundleReport, String> {
+    let target = validate_server_name(new_name)?;
+    let DecodedBundle { files, warnings } = decode_bundle(bundle)?;
+    let source = bundle.manifest.server_name.trim();
+    if let Some(existing) = SERVER_CONFIG_FILES
+        .iter()
+        .map(|file| server_config_path(user_dir, target, file))
+        .find(|path| path.exists())
+    {
+        return Err(format!(
+            "{} already exists; choose another server name.",
+            existing.display()
+        ));
+    }
+    let contents: Vec<(PathBuf, Vec<u8>)> = files
+        .iter()
+        .map(|(file, bytes)| {
+            (
+                server_config_path(user_dir, target, file),
+                rewrite_server_references(file, bytes, source, target),
+            )
+        })
+        .collect();
+    write_all_or_nothing(&contents)?;
+    Ok(ServerBundleReport {
+        manifest: bundle.manifest.clone(),
+        files: contents
+            .iter()
+            .map(|(path, _)| path.to_string_lossy().to_string())
+            .collect(),
+        mods: bundle.mods.clone(),
+        missing_mods: missing_mods(&bundle.mods, installed),
+        warnings,
+    })
+}
+
+#[tauri::command]
+pub fn export_server_bundle(
+    user_dir: String,
+    server_name: String,
+    target_path: String,
+    installed: Option<Vec<ModSummary>>,
+) -> Result<ServerBundleReport, String> {
+    let _timer = scoped_timer("export_server_bundle");
+    let installed = installed.unwrap_or_default();
+    let bundle = build_bundle(&user_dir, &server_name, &installed)?;
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    write_atomically(Path::new(target_path.trim()), json.as_bytes())?;
+    let warnings = bundle
+        .mods
+        .iter()
+        .filter(|entry| entry.workshop_id.is_none())
+        .map(|entry| {
+            format!(
+                "{} has no known workshop item; the receiving admin must install it by hand.",
+                entry.mod_id
+            )
+        })
+        .collect();
+    Ok(ServerBundleReport {
+        files: vec![target_path.trim().to_string()],
+        missing_mods: missing_mods(&bundle.mods, &installed),
+        warnings,
+        manifest: bundle.manifest,
+        mods: bundle.mods,
+    })
+}
+
+/// Validates a bundle and lists the mods it needs without writing anything.
+#[tauri::command]
+pub fn inspect_server_bundle(
+    bundle_path: String,
+    installed: Option<Vec<ModSummary>>,
+) -> Result<ServerBundleReport, String> {
+    let _timer = scoped_timer("inspect_server_bundle");
+    let bundle = read_bundle(bundle_path.trim())?;
+    let DecodedBundle { files, warnings } = decode_bundle(&bundle)?;
+    Ok(ServerBundleReport {
+        files: files.into_iter().map(|(file, _)| file).collect(),
+        missing_mods: missing_mods(&bundle.mods, &installed.unwrap_or_default()),
+        warnings,
+        manifest: bundle.manifest,
+        mods: bundle.mods,
+    })
+}
+
+#[tauri::command]
+pub fn import_server_bundle(
+    user_dir: String,
+    bundle_path: String,
+    new_server_name: String,
+    installed: Option<Vec<ModSummary>>,
+) -> Result<ServerBundleReport, String> {
+    let _timer = scoped_timer("import_server_bundle");
+    let bundle = read_bundle(bundle_path.trim())?;
+    import_bundle(
+        &user_dir,
+        &bundle,
+        &new_server_name,
+        &installed.unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pz_compat::SPAWN_REGIONS_FILE;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn round_trips_a_server_under_a_new_name() {
+        let root = TestDir::new("pz-server-bundle-test");
+        let user_dir = root.to_string_lossy().to_string();
+        let server = root.join("Server");
+        root.write(
+            "Server/alpha.ini",
+            "PublicName=alpha\nMods=\\Hydrocraft;\\Brita\nWorkshopItems=111;222\n",
+        );
+        root.write(
+            "Server/alpha_spawnregions.lua",
+            "{ name = \"Muldraugh, KY\", serverfile = \"alpha_spawnpoints.lua\" },\n",
+        );
+        let installed: Vec<ModSummary> = serde_json::from_value(serde_json::json!([
+            { "id": "a", "mod_id": "Hydrocraft", "name": "Hydrocraft", "workshop_id": "111" },
+        ]))
+        .expect("summaries should deserialize");
+
+        let bundle = build_bundle(&user_dir, "alpha", &installed).expect("bundle should build");
+        assert_eq!(bundle.files.len(), 2);
+        assert_eq!(bundle.mods[0].workshop_id.as_deref(), Some("111"));
+        assert_eq!(bundle.mods[1].workshop_id, None);
+
+        let report =
+            import_bundle(&user_dir, &bundle, "beta", &installed).expect("bundle should import");
+        assert_eq!(report.missing_mods.len(), 1);
+        assert_eq!(report.missing_mods[0].mod_id, "Brita");
+        let ini = ServerIni::read(&server.join("beta.ini")).expect("imported ini should read");
+        assert_eq!(ini.get("PublicName"), Some("beta"));
+        assert!(
+            fs::read_to_string(server_config_path(&user_dir, "beta", SPAWN_REGIONS_FILE))
+                .expect("imported spawn regions should read")
+                .contains("beta_spawnpoints.lua")
+        );
+        assert!(import_bundle(&user_dir, &bundle, "beta", &installed).is_err());
+        root.write("Server/epsilon_SandboxVars.lua", "SandboxVars = {}\n");
+        assert!(import_bundle(&user_dir, &bundle, "epsilon", &installed).is_err());
+        assert!(!server.join("epsilon.ini").exists());
+
+        let blocker = server.join(".delta_spawnregions.lua.import.tmp");
+        fs::create_dir_all(&blocker).expect("blocking folder should be created");
+        assert!(import_bundle(&user_dir, &bundle, "delta", &installed).is_err());
+        assert!(!server.join("delta.ini").exists());
+        assert!(!server.join(".delta.ini.import.tmp").exists());
+        fs::remove_dir(&blocker).expect("blocking folder should be removed");
+        import_bundle(&user_dir, &bundle, "delta", &installed)
+            .expect("a retried import should succeed");
+
+        let mut foreign = bundle.clone();
+        foreign.manifest.game_version = "41.78".to_string();
+        assert!(import_bundle(&user_dir, &foreign, "gamma", &installed).is_err());
+    }
+}
@@ -0,0 +1,533 @@
+use crate::mod_scanner::normalize_mod_ref;
+use crate::pz_compat::{
+    SANDBOX_VARS_FILE, SERVER_INI_FILE, SPAWN_POINTS_FILE, SPAWN_REGIONS_FILE, server_config_path,
+    validate_server_name,
+};
+use crate::sandbox_vars::{
+    SandboxChange, apply_sandbox_changes, empty_sandbox_vars, flatten_sandbox,
+    format_sandbox_value, parse_sandbox_vars, remove_sandbox_key,
+};
+use crate::server_ini::ServerIni;
+use crate::spawn_files::{
+    ProfessionSpawnPoints, SpawnRegion, edit_spawn_points, edit_spawn_region, parse_spawn_points,
+    parse_spawn_regions, render_spawn_points, render_spawn_regions,
+};
+use crate::timing::scoped_timer;
+use crate::utils::{read_optional_text, write_atomically};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ServerConfigSection {
+    Ini,
+    SandboxVars,
+    SpawnRegions,
+    SpawnPoints,
+}
+
+/// One setting that differs; `None` on a side means the key is missing there.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSettingDiff {
+    pub section: ServerConfigSection,
+    pub key: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerListDiff {
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+    /// Whether the entries both lists share appear in the same order.
+    pub same_order: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerComparison {
+    pub left: String,
+    pub right: String,
+    pub settings: Vec<ServerSettingDiff>,
+    pub mods: ServerListDiff,
+    pub workshop_items: ServerListDiff,
+    pub maps: ServerListDiff,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSettingKey {
+    pub section: ServerConfigSection,
+    pub key: String,
+}
+
+/// The four configuration files of one server; missing files read as empty.
+/// The spawn files keep their text so a merge can edit them in place.
+struct ServerConfig {
+    ini: ServerIni,
+    sandbox: Option<String>,
+    regions_text: Option<String>,
+    regions: Vec<SpawnRegion>,
+    points_text: Option<String>,
+    points: Vec<ProfessionSpawnPoints>,
+}
+
+impl ServerConfig {
+    fn read(user_dir: &str, server_name: &str) -> Result<Self, String> {
+        let ini_path = server_config_path(user_dir, server_name, SERVER_INI_FILE);
+        if !ini_path.is_file() {
+            return Err(format!("Server \"{server_name}\" does not exist."));
+        }
+        let regions = read_optional_text(&server_config_path(
+            user_dir,
+            server_name,
+            SPAWN_REGIONS_FILE,
+        ))?;
+        let points = read_optional_text(&server_config_path(
+            user_dir,
+            server_name,
+            SPAWN_POINTS_FILE,
+        ))?;
+        Ok(Self {
+            ini: ServerIni::read(&ini_path)?,
+            sandbox: read_optional_text(&server_config_path(
+                user_dir,
+                server_name,
+                SANDBOX_VARS_FILE,
+            ))?,
+            regions: regions
+                .as_deref()
+                .map(parse_spawn_regions)
+                .transpose()?
+                .unwrap_or_default(),
+            regions_text: regions,
+            points: points
+                .as_deref()
+                .map(parse_spawn_points)
+                .transpose()?
+                .unwrap_or_default(),
+            points_text: points,
+        })
+    }
+
+    fn settings(&self, section: ServerConfigSection) -> Result<Vec<(String, String)>, String> {
+        Ok(match section {
+            ServerConfigSection::Ini => self
+                .ini
+                .entries()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ServerConfigSection::SandboxVars => match &self.sandbox {
+                Some(text) => flatten_sandbox(&parse_sandbox_vars(text)?)
+                    .into_iter()
+                    .map(|(path, value)| (path, format_sandbox_value(&value)))
+                    .collect(),
+                None => Vec::new(),
+            },
+            ServerConfigSection::SpawnRegions => self
+                .regions
+                .iter()
+                .map(|region| (region.name.clone(), describe_region(region)))
+                .collect(),
+            ServerConfigSection::SpawnPoints => self
+                .points
+                .iter()
+                .map(|profession| {
+                    (
+                        profession.profession.clone(),
+                        serde_json::to_string(&profession.points).unwrap_or_default(),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    fn list(&self, key: &str) -> Vec<String> {
+        self.ini
+            .get(key)
+            .unwrap_or_default()
+            .split(';')
+            .map(|entry| match key {
+                "Mods" => normalize_mod_ref(entry),
+                _ => entry.trim().to_string(),
+            })
+            .filter(|entry| !entry.is_empty())
+            .collect()
+    }
+}
+
+fn describe_region(region: &SpawnRegion) -> String {
+    match (&region.file, &region.server_file) {
+        (Some(file), _) => format!("file = {file}"),
+        (None, Some(server_file)) => format!("serverfile = {server_file}"),
+        (None, None) => String::new(),
+    }
+}
+
+fn diff_settings(
+    section: ServerConfigSection,
+    left: &[(String, String)],
+    right: &[(String, String)],
+) -> Vec<ServerSettingDiff> {
+    let find = |entries: &[(String, String)], key: &str| {
+        entries
+            .iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value.clone())
+    };
+    let mut diffs = Vec::new();
+    for (key, value) in left {
+        let other = find(right, key);
+        if other.as_ref() != Some(value) {
+            diffs.push(ServerSettingDiff {
+                section,
+                key: key.clone(),
+                left: Some(value.clone()),
+                right: other,
+            });
+        }
+    }
+    for (key, value) in right {
+        if find(left, key).is_none() {
+            diffs.push(ServerSettingDiff {
+                section,
+                key: key.clone(),
+                left: None,
+                right: Some(value.clone()),
+            });
+        }
+    }
+    diffs
+}
+
+fn diff_lists(left: &[String], right: &[String]) -> ServerListDiff {
+    let shared_left: Vec<&String> = left.iter().filter(|entry| right.contains(entry)).collect();
+    let shared_right: Vec<&String> = right.iter().filter(|entry| left.contains(entry)).collect();
+    ServerListDiff {
+        only_left: left
+            .iter()
+            .filter(|entry| !right.contains(entry))
+            .cloned()
+            .collect(),
+        only_right: right
+            .iter()
+            .filter(|entry| !left.contains(entry))
+            .cloned()
+            .collect(),
+        same_order: shared_left == shared_right,
+    }
+}
+
+const SECTIONS: [ServerConfigSection; 4] = [
+    ServerConfigSection::Ini,
+    ServerConfigSection::SandboxVars,
+    ServerConfigSection::SpawnRegions,
+    ServerConfigSection::SpawnPoints,
+];
+
+fn compare(
+    left_name: &str,
+    left: &ServerConfig,
+    right_name: &str,
+    right: &ServerConfig,
+) -> Result<ServerComparison, String> {
+    let mut settings = Vec::new();
+    for section in SECTIONS {
+        settings.extend(diff_settings(
+            section,
+            &left.settings(section)?,
+            &right.settings(section)?,
+        ));
+    }
+    Ok(ServerComparison {
+        left: left_name.to_string(),
+        right: right_name.to_string(),
+        settings,
+        mods: diff_lists(&left.list("Mods"), &right.list("Mods")),
+        workshop_items: diff_lists(&left.list("WorkshopItems"), &right.list("WorkshopItems")),
+        maps: diff_lists(&left.list("Map"), &right.list("Map")),
+    })
+}
+
+/// Copies the selected settings from `from` into `to`; a key `from` does not
+/// have is removed from `to`.  Returns the sections that changed.
+fn merge_settings(
+    from: &ServerConfig,
+    to: &mut ServerConfig,
+    keys: &[ServerSettingKey],
+) -> Result<Vec<ServerConfigSection>, String> {
+    let mut touched = Vec::new();
+    let mut touch = |section| {
+        if !touched.contains(&section) {
+            touched.push(section);
+        }
+    };
+    let from_sandbox = match &from.sandbox {
+        Some(text) => flatten_sandbox(&parse_sandbox_vars(text)?),
+        None => Vec::new(),
+    };
+    for selected in keys {
+        let key = selected.key.as_str();
+        match selected.section {
+            ServerConfigSection::Ini => match from.ini.get(key) {
+                Some(value) => to.ini.set(key, value),
+                None => {
+                    to.ini.remove(key);
+                }
+            },
+            ServerConfigSection::SandboxVars => {
+                let text = to.sandbox.take().unwrap_or_else(empty_sandbox_vars);
+                let value = from_sandbox
+                    .iter()
+                    .find(|(path, _)| path == key)
+                    .map(|(_, value)| value.clone());
+                to.sandbox = Some(match value {
+                    Some(value) => apply_sandbox_changes(
+                        &text,
+                        &[SandboxChange {
+                            path: key.to_string(),
+                            value,
+                        }],
+                    )?,
+                    None => remove_sandbox_key(&text, key)?,
+                });
+            }
+            ServerConfigSection::SpawnRegions => {
+                let text = to
+                    .regions_text
+                    .take()
+                    .unwrap_or_else(|| render_spawn_regions(&[]));
+                let source = from.regions.iter().find(|region| region.name == key);
+                let text = edit_spawn_region(&text, key, source)?;
+                to.regions = parse_spawn_regions(&text)?;
+                to.regions_text = Some(text);
+            }
+            ServerConfigSection::SpawnPoints => {
+                let text = to
+                    .points_text
+                    .take()
+                    .unwrap_or_else(|| render_spawn_points(&[]));
+                let source = from.points.iter().find(|entry| entry.profession == key);
+                let text = edit_spawn_points(&text, key, source)?;
+                to.points = parse_spawn_points(&text)?;
+                to.points_text = Some(text);
+            }
+        }
+        touch(selected.section);
+    }
+    Ok(touched)
+}
+
+/// The files a merge has replaced so far with what they held before, restored
+/// when a later write fails.
+#[derive(Default)]
+struct StagedMerge {
+    replaced: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl StagedMerge {
+    fn write(&mut self, path: PathBuf, bytes: &[u8]) -> Result<(), String> {
+        let original = match fs::read(&path) {
+            Ok(original) => Some(original),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => return Err(format!("{}: {error}", path.display())),
+        };
+        write_atomically(&path, bytes)?;
+        self.replaced.push((path, original));
+        Ok(())
+    }
+
+    fn roll_back(&self) {
+        for (path, original) in self.replaced.iter().rev() {
+            let _ = match original {
+                Some(bytes) => write_atomically(path, bytes),
+                None => fs::remove_file(path).map_err(|e| e.to_string()),
+            };
+        }
+    }
+}
+
+#[tauri::command]
+pub fn compare_servers(
+    user_dir: String,
+    left_server: String,
+    right_server: String,
+) -> Result<ServerComparison, String> {
+    let _timer = scoped_timer("compare_servers");
+    let left_name = validate_server_name(&left_server)?;
+    let right_name = validate_server_name(&right_server)?;
+    compare(
+        left_name,
+        &ServerConfig::read(&user_dir, left_name)?,
+        right_name,
+        &ServerConfig::read(&user_dir, right_name)?,
+    )
+}
+
+/// Applies the selected settings of `from_server` to `to_server` and returns
+/// the comparison afterwards, with `from_server` on the left.
+#[tauri::command]
+pub fn merge_server_settings(
+    user_dir: String,
+    from_server: String,
+    to_server: String,
+    keys: Vec<ServerSettingKey>,
+) -> Result<ServerComparison, String> {
+    let _timer = scoped_timer("merge_server_settings");
+    let from_name = validate_server_name(&from_server)?;
+    let to_name = validate_server_name(&to_server)?;
+    let from = ServerConfig::read(&user_dir, from_name)?;
+    let mut to = ServerConfig::read(&user_dir, to_name)?;
+    let mut files = Vec::new();
+    for section in merge_settings(&from, &mut to, &keys)? {
+        let (file, bytes) = match section {
+            ServerConfigSection::Ini => (SERVER_INI_FILE, to.ini.to_bytes()),
+            ServerConfigSection::SandboxVars => (
+                SANDBOX_VARS_FILE,
+                to.sandbox
+                    .clone()
+                    .unwrap_or_else(empty_sandbox_vars)
+                    .into_bytes(),
+            ),
+            ServerConfigSection::SpawnRegions => (
+                SPAWN_REGIONS_FILE,
+                to.regions_text.clone().unwrap_or_default().into_bytes(),
+            ),
+            ServerConfigSection::SpawnPoints => (
+                SPAWN_POINTS_FILE,
+                to.points_text.clone().unwrap_or_default().into_bytes(),
+            ),
+        };
+        files.push((server_config_path(&user_dir, to_name, file), bytes));
+    }
+    let mut staged = StagedMerge::default();
+    for (path, bytes) in files {
+        staged
+            .write(path, &bytes)
+            .inspect_err(|_| staged.roll_back())?;
+    }
+    compare(from_name, &from, to_name, &to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+    use std::fs;
+
+    #[test]
+    fn compares_servers_key_by_key_and_merges_a_selection() {
+        let root = TestDir::new("pz-server-diff-test");
+        let user_dir = root.to_string_lossy().to_string();
+        let write = |server: &str, file: &str, content: &str| {
+            write_atomically(
+                &server_config_path(&user_dir, server, file),
+                content.as_bytes(),
+            )
+            .expect("server file should be written");
+        };
+        write(
+            "main",
+            SERVER_INI_FILE,
+            "PVP=true\nMods=\\A;\\B\nWorkshopItems=1;2\nMap=Town;Muldraugh, KY\nPublic=true\n",
+        );
+        write(
+            "main",
+            SANDBOX_VARS_FILE,
+            "SandboxVars = {\n    VERSION = 6,\n    Zombies = 2,\n    ZombieLore = {\n        Speed = 1,\n    },\n}\n",
+        );
+        write(
+            "main",
+            SPAWN_REGIONS_FILE,
+            "function SpawnRegions()\n\treturn {\n\t\t{ name = \"Town\", file = \"media/maps/Town/spawnpoints.lua\" },\n\t}\nend\n",
+        );
+        write(
+            "test",
+            SERVER_INI_FILE,
+            "# test copy\nPVP=false\nMods=B;C\nWorkshopItems=2;3\nMap=Muldraugh, KY\nDebug=true\n",
+        );
+        write(
+            "test",
+            SANDBOX_VARS_FILE,
+            "SandboxVars = {\n    VERSION = 6,\n    Zombies = 4,\n    -- extra\n    Extra = true,\n    ZombieLore = {\n        Speed = 1,\n    },\n}\n",
+        );
+        write(
+            "test",
+            SPAWN_REGIONS_FILE,
+            "function SpawnRegions()\n\treturn {\n\t\t-- test only\n\t\t{ name = \"Camp\", serverfile = \"test_spawnpoints.lua\" }\n\t}\nend\n",
+        );
+
+        let comparison = compare_servers(user_dir.clone(), "main".to_string(), "test".to_string())
+            .expect("servers should compare");
+        let keys: Vec<(ServerConfigSection, &str)> = comparison
+            .settings
+            .iter()
+            .map(|diff| (diff.section, diff.key.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (ServerConfigSection::Ini, "PVP"),
+                (ServerConfigSection::Ini, "Mods"),
+                (ServerConfigSection::Ini, "WorkshopItems"),
+                (ServerConfigSection::Ini, "Map"),
+                (ServerConfigSection::Ini, "Public"),
+                (ServerConfigSection::Ini, "Debug"),
+                (ServerConfigSection::SandboxVars, "Zombies"),
+                (ServerConfigSection::SandboxVars, "Extra"),
+                (ServerConfigSection::SpawnRegions, "Town"),
+                (ServerConfigSection::SpawnRegions, "Camp"),
+            ]
+        );
+        assert_eq!(comparison.mods.only_left, ["A"]);
+        assert_eq!(comparison.mods.only_right, ["C"]);
+        assert!(comparison.mods.same_order);
+        assert_eq!(comparison.maps.only_left, ["Town"]);
+
+        let select = |section, key: &str| ServerSettingKey {
+            section,
+            key: key.to_string(),
+        };
+        let merged = merge_server_settings(
+            user_dir.clone(),
+            "main".to_string(),
+            "test".to_string(),
+            vec![
+                select(ServerConfigSection::Ini, "PVP"),
+                select(ServerConfigSection::Ini, "Debug"),
+                select(ServerConfigSection::SandboxVars, "Zombies"),
+                select(ServerConfigSection::SandboxVars, "Extra"),
+                select(ServerConfigSection::SpawnRegions, "Town"),
+            ],
+        )
+        .expect("settings should merge");
+        let remaining: Vec<&str> = merged
+            .settings
+            .iter()
+            .map(|diff| diff.key.as_str())
+            .collect();
+        assert_eq!(
+            remaining,
+            ["Mods", "WorkshopItems", "Map", "Public", "Camp"]
+        );
+        assert_eq!(
+            fs::read_to_string(server_config_path(&user_dir, "test", SERVER_INI_FILE))
+                .expect("merged ini should be readable"),
+            "# test copy\nPVP=true\nMods=B;C\nWorkshopItems=2;3\nMap=Muldraugh, KY\n"
+        );
+        assert_eq!(
+            fs::read_to_string(server_config_path(&user_dir, "test", SANDBOX_VARS_FILE))
+                .expect("merged sandbox vars should be readable"),
+            "SandboxVars = {\n    VERSION = 6,\n    Zombies = 2,\n    ZombieLore = {\n        Speed = 1,\n    },\n}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(server_config_path(&user_dir, "test", SPAWN_REGIONS_FILE))
+                .expect("merged spawn regions should be readable"),
+            "function SpawnRegions()\n\treturn {\n\t\t-- test only\n\t\t{ name = \"Camp\", serverfile = \"test_spawnpoints.lua\" },\n\t\t{ name = \"Town\", file = \"media/maps/Town/spawnpoints.lua\" },\n\t}\nend\n"
+        );
+    }
+}
@@ -1,6 +1,21 @@
-use crate::pz_compat::{server_config_paths, server_dir, validate_server_name};
+use crate::pz_compat::{
+    SERVER_CONFIG_FILES, SERVER_INI_FILE, server_config_path, server_config_paths, server_db_path,
+    server_dir, server_world_dir, validate_server_name,
+};
+use crate::server_ini::ServerIni;
 use crate::timing::scoped_timer;
+use crate::utils::{copy_directory, ensure_parent_dir, write_atomically};
+use serde::Serialize;
 use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTransferResult {
+    pub files: Vec<String>,
+    pub world: Option<String>,
+    pub database: Option<String>,
+}
 
 #[tauri::command]
 pub fn list_server_names(user_dir: String) -> Result<Vec<String>, String> {
@@ -42,3 +57,311 @@ pub fn delete_server_files(user_dir: String, server_name: String) -> Result<(),
     }
     Ok(())
 }
+
+fn replace_bytes(haystack: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(haystack.len());
+    let mut rest = haystack;
+    while let Some(index) = rest.windows(from.len()).position(|window| window == from) {
+        out.extend_from_slice(&rest[..index]);
+        out.extend_from_slice(to);
+        rest = &rest[index + from.len()..];
+    }
+    out.extend_from_slice(rest);
+    out
+}
+
+/// Rewrites references from one server's files to another's, such as the
+/// `serverfile = "<name>_spawnpoints.lua"` entry of spawnregions, and a
+/// `PublicName` that is just the server name.  File names are only replaced
+/// as whole quoted strings, and a file with nothing to rewrite is returned
+/// byte for byte.
+pub(crate) fn rewrite_server_references(
+    file: &str,
+    bytes: &[u8],
+    source: &str,
+    target: &str,
+) -> Vec<u8> {
+    if file == SERVER_INI_FILE {
+        let mut ini = ServerIni::decode(bytes);
+        if !ini
+            .get("PublicName")
+            .is_some_and(|name| name.trim().eq_ignore_ascii_case(source))
+        {
+            return bytes.to_vec();
+        }
+        ini.set("PublicName", target);
+        return ini.to_bytes();
+    }
+    let mut bytes = bytes.to_vec();
+    for other in SERVER_CONFIG_FILES
+        .iter()
+        .filter(|other| **other != SERVER_INI_FILE)
+    {
+        for quote in ['"', '\''] {
+            let from = format!("{quote}{}{quote}", other.replace("{name}", source));
+            let to = format!("{quote}{}{quote}", other.replace("{name}", target));
+            bytes = replace_bytes(&bytes, from.as_bytes(), to.as_bytes());
+        }
+    }
+    bytes
+}
+
+/// What a transfer has put in place so far, undone when a later step fails.
+#[derive(Default)]
+struct StagedTransfer {
+    created: Vec<PathBuf>,
+    moved: Vec<(PathBuf, PathBuf)>,
+}
+
+impl StagedTransfer {
+    fn roll_back(&self) {
+        for (from, to) in self.moved.iter().rev() {
+            let _ = fs::rename(to, from);
+        }
+        for path in self.created.iter().rev() {
+            let _ = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+        }
+    }
+}
+
+fn stage_transfer(
+    files: &[(&str, PathBuf, PathBuf)],
+    data: &[(PathBuf, PathBuf)],
+    source: &str,
+    target: &str,
+    remove_source: bool,
+    staged: &mut StagedTransfer,
+) -> Result<ServerTransferResult, String> {
+    let mut result = ServerTransferResult::default();
+    for (file, from, to) in files {
+        let bytes = fs::read(from).map_err(|e| format!("{}: {e}", from.display()))?;
+        staged.created.push(to.clone());
+        write_atomically(to, &rewrite_server_references(file, &bytes, source, target))?;
+        result.files.push(to.to_string_lossy().to_string());
+    }
+    for (from, to) in data {
+        ensure_parent_dir(to)?;
+        let is_dir = from.is_dir();
+        if remove_source {
+            fs::rename(from, to).map_err(|e| format!("{}: {e}", from.display()))?;
+            staged.moved.push((from.clone(), to.clone()));
+        } else {
+            staged.created.push(to.clone());
+            if is_dir {
+                copy_directory(from, to)?;
+            } else {
+                fs::copy(from, to).map_err(|e| format!("{}: {e}", from.display()))?;
+            }
+        }
+        let path = Some(to.to_string_lossy().to_string());
+        if is_dir {
+            result.world = path;
+        } else {
+            result.database = path;
+        }
+    }
+    Ok(result)
+}
+
+/// Copies or moves every file of `source` to `target`, optionally with its
+/// world and account database.  Nothing is touched when any target exists,
+/// and a failed step undoes the earlier ones; a moved server's old files are
+/// only deleted once everything else is in place.
+fn transfer_server(
+    user_dir: &str,
+    source: &str,
+    target: &str,
+    include_data: bool,
+    remove_source: bool,
+) -> Result<ServerTransferResult, String> {
+    let source = validate_server_name(source)?;
+    let target = validate_server_name(target)?;
+    if source == target {
+        return Err("The new server name is the same as the current one.".to_string());
+    }
+    if !server_config_path(user_dir, source, SERVER_INI_FILE).is_file() {
+        return Err(format!("Server \"{source}\" does not exist."));
+    }
+
+    let files: Vec<(&str, PathBuf, PathBuf)> = SERVER_CONFIG_FILES
+        .iter()
+        .map(|file| {
+            (
+                *file,
+                server_config_path(user_dir, source, file),
+                server_config_path(user_dir, target, file),
+            )
+        })
+        .filter(|(_, from, _)| from.is_file())
+        .collect();
+    let data = [
+        (
+            server_world_dir(user_dir, source),
+            server_world_dir(user_dir, target),
+        ),
+        (
+            server_db_path(user_dir, source),
+            server_db_path(user_dir, target),
+        ),
+    ];
+    // A world or database left under the target name would be picked up by
+    // the new server, so it blocks a clone without data too.
+    if let Some(existing) = files
+        .iter()
+        .map(|(_, _, to)| to)
+        .chain(data.iter().map(|(_, to)| to))
+        .find(|to| to.exists())
+    {
+        return Err(format!(
+            "{} already exists; choose another server name.",
+            existing.display()
+        ));
+    }
+    let data: Vec<(PathBuf, PathBuf)> = if include_data {
+        data.into_iter().filter(|(from, _)| from.exists()).collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut staged = StagedTransfer::default();
+    let result = stage_transfer(&files, &data, source, target, remove_source, &mut staged)
+        .inspect_err(|_| staged.roll_back())?;
+    if remove_source {
+        for (_, from, _) in &files {
+            fs::remove_file(from).map_err(|e| {
+                format!(
+                    "Server \"{target}\" is in place, but {} could not be removed: {e}",
+                    from.display()
+                )
+            })?;
+        }
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn clone_server(
+    user_dir: String,
+    server_name: String,
+    new_name: String,
+    include_data: bool,
+) -> Result<ServerTransferResult, String> {
+    let _timer = scoped_timer("clone_server");
+    transfer_server(&user_dir, &server_name, &new_name, include_data, false)
+}
+
+/// Renames a server together with its world and account database, which the
+/// game finds by server name.
+#[tauri::command]
+pub fn rename_server(
+    user_dir: String,
+    server_name: String,
+    new_name: String,
+) -> Result<ServerTransferResult, String> {
+    let _timer = scoped_timer("rename_server");
+    transfer_server(&user_dir, &server_name, &new_name, true, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn clones_and_renames_servers_with_their_data() {
+        let root = TestDir::new("pz-server-files-test");
+        let user_dir = root.to_string_lossy().to_string();
+        root.write(
+            server_config_path(&user_dir, "live", SERVER_INI_FILE),
+            "PublicName=live\nMods=A\n",
+        );
+        root.write(
+            server_config_path(&user_dir, "live", "{name}_spawnregions.lua"),
+            "{ name = \"Twdprof\", serverfile = \"live_spawnpoints.lua\" },\n{ name = \"Alive\", serverfile = \"alive_spawnpoints.lua\" },\n",
+        );
+        root.write(
+            server_world_dir(&user_dir, "live").join("map_t.bin"),
+            "world",
+        );
+        root.write(server_db_path(&user_dir, "live"), "db");
+        fs::write(
+            server_config_path(&user_dir, "live", "{name}_SandboxVars.lua"),
+            b"-- Caf\xe9\n",
+        )
+        .expect("sandbox vars should be written");
+
+        let cloned = clone_server(
+            user_dir.clone(),
+            "live".to_string(),
+            "test".to_string(),
+            false,
+        )
+        .expect("server should be cloned");
+        assert_eq!(cloned.files.len(), 3);
+        assert!(cloned.world.is_none());
+        assert_eq!(
+            fs::read_to_string(server_config_path(&user_dir, "test", SERVER_INI_FILE))
+                .expect("cloned ini should exist"),
+            "PublicName=test\nMods=A\n"
+        );
+        assert_eq!(
+            fs::read(server_config_path(
+                &user_dir,
+                "test",
+                "{name}_SandboxVars.lua"
+            ))
+            .expect("cloned sandbox vars should exist"),
+            b"-- Caf\xe9\n"
+        );
+        assert!(
+            fs::read_to_string(server_config_path(
+                &user_dir,
+                "test",
+                "{name}_spawnregions.lua"
+            ))
+            .expect("cloned spawnregions should exist")
+            .ends_with("\"test_spawnpoints.lua\" },\n{ name = \"Alive\", serverfile = \"alive_spawnpoints.lua\" },\n")
+        );
+        assert!(
+            clone_server(
+                user_dir.clone(),
+                "live".to_string(),
+                "test".to_string(),
+                true
+            )
+            .is_err()
+        );
+        root.write(server_db_path(&user_dir, "stale"), "old db");
+        assert!(
+            clone_server(
+                user_dir.clone(),
+                "live".to_string(),
+                "stale".to_string(),
+                false
+            )
+            .is_err()
+        );
+        assert!(!server_config_path(&user_dir, "stale", SERVER_INI_FILE).exists());
+        fs::remove_file(server_db_path(&user_dir, "stale")).expect("stale db should be removed");
+
+        let renamed = rename_server(user_dir.clone(), "live".to_string(), "moved".to_string())
+            .expect("server should be renamed");
+        assert!(renamed.world.is_some() && renamed.database.is_some());
+        assert!(!server_config_path(&user_dir, "live", SERVER_INI_FILE).exists());
+        assert!(!server_world_dir(&user_dir, "live").exists());
+        assert!(
+            server_world_dir(&user_dir, "moved")
+                .join("map_t.bin")
+                .is_file()
+        );
+        assert!(server_db_path(&user_dir, "moved").is_file());
+        assert_eq!(
+            list_server_names(user_dir).expect("servers should be listed"),
+            ["moved", "test"]
+        );
+    }
+}
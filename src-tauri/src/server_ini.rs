@@ -0,0 +1,277 @@
+use encoding_rs::WINDOWS_1252;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum IniLine {
+    Entry {
+        key: String,
+        value: String,
+        /// The original text, written back untouched unless the value changes.
+        raw: String,
+    },
+    /// Comments, blank lines and anything else the game ignores.
+    Other(String),
+}
+
+/// A dedicated server `<name>.ini`, kept line by line so comments, key order
+/// and keys this app does not know about survive a rewrite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ServerIni {
+    lines: Vec<IniLine>,
+    /// The file started with a UTF-8 byte order mark, as Notepad writes it.
+    bom: bool,
+    crlf: bool,
+    trailing_newline: bool,
+    /// The file was not UTF-8 and is written back as Windows-1252, which the
+    /// game uses for values such as `PublicDescription` on Windows.
+    windows_1252: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IniChange {
+    pub key: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl ServerIni {
+    pub(crate) fn parse(text: &str) -> Self {
+        let (bom, text) = match text.strip_prefix('\u{feff}') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let lines = text
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with('#') || trimmed.starts_with(';') {
+                    return IniLine::Other(line.to_string());
+                }
+                match line.split_once('=') {
+                    Some((key, value)) if !key.trim().is_empty() => IniLine::Entry {
+                        key: key.trim().to_string(),
+                        value: value.trim().to_string(),
+                        raw: line.to_string(),
+                    },
+                    _ => IniLine::Other(line.to_string()),
+                }
+            })
+            .collect();
+        Self {
+            lines,
+            bom,
+            crlf: text.contains("\r\n"),
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+            windows_1252: false,
+        }
+    }
+
+    /// Parses UTF-8, or Windows-1252 when the bytes are not valid UTF-8.  Every
+    /// Windows-1252 byte decodes to a character, so `to_bytes` gives the same
+    /// bytes back for lines that were not changed.
+    pub(crate) fn decode(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::parse(text),
+            Err(_) => Self {
+                windows_1252: true,
+                ..Self::parse(&WINDOWS_1252.decode_without_bom_handling(bytes).0)
+            },
+        }
+    }
+
+    /// Reads `path`, or starts an empty file when it does not exist yet.
+    pub(crate) fn read(path: &Path) -> Result<Self, String> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Self::decode(&bytes)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::parse("")),
+            Err(error) => Err(format!("{}: {error}", path.display())),
+        }
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        let position = |exact: bool| {
+            self.lines.iter().position(|line| match line {
+                IniLine::Entry { key: existing, .. } if exact => existing == key,
+                IniLine::Entry { key: existing, .. } => existing.eq_ignore_ascii_case(key),
+                IniLine::Other(_) => false,
+            })
+        };
+        position(true).or_else(|| position(false))
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        match &self.lines[self.position(key)?] {
+            IniLine::Entry { value, .. } => Some(value),
+            IniLine::Other(_) => None,
+        }
+    }
+
+    /// Replaces the value of `key` in place, or appends it when missing.
+    pub(crate) fn set(&mut self, key: &str, value: &str) {
+        match self.position(key) {
+            Some(index) => {
+                if let IniLine::Entry {
+                    key: existing,
+                    value: current,
+                    raw,
+                } = &mut self.lines[index]
+                {
+                    if current != value {
+                        *current = value.to_string();
+                        *raw = format!("{existing}={value}");
+                    }
+                }
+            }
+            None => self.lines.push(IniLine::Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+                raw: format!("{key}={value}"),
+            }),
+        }
+    }
+
+    /// Drops the line of `key`; returns whether it was present.
+    pub(crate) fn remove(&mut self, key: &str) -> bool {
+        match self.position(key) {
+            Some(index) => {
+                self.lines.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            IniLine::Entry { key, value, .. } => Some((key.as_str(), value.as_str())),
+            IniLine::Other(_) => None,
+        })
+    }
+
+    /// Key-by-key differences from `self` to `other`, in `other`'s key order
+    /// followed by keys only `self` has.
+    pub(crate) fn diff(&self, other: &ServerIni) -> Vec<IniChange> {
+        let mut changes = Vec::new();
+        for (key, value) in other.entries() {
+            let before = self.get(key);
+            if before != Some(value) {
+                changes.push(IniChange {
+                    key: key.to_string(),
+                    before: before.map(str::to_string),
+                    after: Some(value.to_string()),
+                });
+            }
+        }
+        for (key, value) in self.entries() {
+            if other.position(key).is_none() {
+                changes.push(IniChange {
+                    key: key.to_string(),
+                    before: Some(value.to_string()),
+                    after: None,
+                });
+            }
+        }
+        changes
+    }
+
+    pub(crate) fn render(&self) -> String {
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        let mut out = if self.bom {
+            "\u{feff}".to_string()
+        } else {
+            String::new()
+        };
+        out.push_str(
+            &self
+                .lines
+                .iter()
+                .map(|line| match line {
+                    IniLine::Entry { raw, .. } | IniLine::Other(raw) => raw.as_str(),
+                })
+                .collect::<Vec<_>>()
+                .join(newline),
+        );
+        if self.trailing_newline && !self.lines.is_empty() {
+            out.push_str(newline);
+        }
+        out
+    }
+
+    /// The rendered file in the encoding it was read with.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let text = self.render();
+        if self.windows_1252 {
+            WINDOWS_1252.encode(&text).0.into_owned()
+        } else {
+            text.into_bytes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_known_keys_and_preserves_everything_else() {
+        let original = "# Server settings\r\nPVP=true\r\nMods=Old\r\n\r\n# Custom\r\nUnknownKey = kept as is\r\nWorkshopItems=1\r\n";
+        let mut ini = ServerIni::parse(original);
+        assert_eq!(ini.render(), original);
+
+        let before = ini.clone();
+        ini.set("Mods", "\\ModA;\\ModB");
+        ini.set("WorkshopItems", "1");
+        ini.set("Map", "Muldraugh, KY");
+        assert_eq!(
+            ini.render(),
+            "# Server settings\r\nPVP=true\r\nMods=\\ModA;\\ModB\r\n\r\n# Custom\r\nUnknownKey = kept as is\r\nWorkshopItems=1\r\nMap=Muldraugh, KY\r\n"
+        );
+        assert_eq!(ini.get("unknownkey"), Some("kept as is"));
+        assert_eq!(
+            before.diff(&ini),
+            vec![
+                IniChange {
+                    key: "Mods".to_string(),
+                    before: Some("Old".to_string()),
+                    after: Some("\\ModA;\\ModB".to_string()),
+                },
+                IniChange {
+                    key: "Map".to_string(),
+                    before: None,
+                    after: Some("Muldraugh, KY".to_string()),
+                },
+            ]
+        );
+
+        let latin1 = b"PublicDescription=Caf\xe9 \x96 open\nPVP=true";
+        let mut ini = ServerIni::decode(latin1);
+        assert_eq!(
+            ini.get("PublicDescription"),
+            Some("Caf\u{e9} \u{2013} open")
+        );
+        assert_eq!(ini.to_bytes(), latin1);
+        ini.set("PVP", "false");
+        assert_eq!(
+            ini.to_bytes(),
+            b"PublicDescription=Caf\xe9 \x96 open\nPVP=false"
+        );
+    }
+
+    #[test]
+    fn keeps_a_leading_byte_order_mark() {
+        let original = "\u{feff}PVP=true\r\nMods=Old\r\n";
+        let mut ini = ServerIni::decode(original.as_bytes());
+        assert_eq!(ini.get("PVP"), Some("true"));
+        assert_eq!(ini.to_bytes(), original.as_bytes());
+        ini.set("PVP", "false");
+        assert_eq!(ini.render(), "\u{feff}PVP=false\r\nMods=Old\r\n");
+        assert!(
+            !ServerIni::parse("PVP=true\n")
+                .render()
+                .starts_with('\u{feff}')
+        );
+    }
+}
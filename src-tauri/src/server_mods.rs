@@ -0,0 +1,265 @@
+use crate::loadout::workshop_providers;
+use crate::mod_scanner::normalize_mod_ref;
+use crate::models::ModSummary;
+use crate::pz_compat::{SERVER_INI_FILE, server_config_path, validate_server_name};
+use crate::server_ini::ServerIni;
+use crate::timing::scoped_timer;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ServerModIssueKind {
+    /// A `Mods=` id that no scanned mod declares.
+    ModWithoutProvider,
+    /// A `Mods=` id whose workshop items are all missing from `WorkshopItems=`.
+    ModWorkshopItemMissing,
+    /// A `WorkshopItems=` id that is not in the scan.
+    UnknownWorkshopItem,
+    /// A `WorkshopItems=` id none of whose mods are enabled.
+    UnusedWorkshopItem,
+    DuplicateMod,
+    DuplicateWorkshopItem,
+    MissingDependency,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerModIssue {
+    pub kind: ServerModIssueKind,
+    pub id: String,
+    /// Workshop items, mods or dependencies the issue points at.
+    pub related: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerModsReport {
+    pub mods: Vec<String>,
+    pub workshop_items: Vec<String>,
+    pub issues: Vec<ServerModIssue>,
+}
+
+fn split_entries(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(';')
+        .map(normalize_mod_ref)
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Entries listed more than once, compared without case, in first-seen order.
+fn duplicates(entries: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    entries
+        .iter()
+        .filter(|entry| {
+            let key = entry.to_ascii_lowercase();
+            !seen.insert(key.clone()) && reported.insert(key)
+        })
+        .cloned()
+        .collect()
+}
+
+fn check_server_mods(ini: &ServerIni, installed: &[ModSummary]) -> ServerModsReport {
+    let mods = split_entries(ini.get("Mods"));
+    let workshop_items = split_entries(ini.get("WorkshopItems"));
+    let providers = workshop_providers(installed);
+    let mut mods_by_item: HashMap<&str, BTreeSet<String>> = HashMap::new();
+    for (mod_id, items) in &providers {
+        for item in items {
+            mods_by_item
+                .entry(item.as_str())
+                .or_default()
+                .insert(mod_id.clone());
+        }
+    }
+    let mut summaries: HashMap<String, &ModSummary> = HashMap::new();
+    for summary in installed {
+        if let Some(mod_id) = summary.mod_id.as_deref().map(str::trim) {
+            summaries
+                .entry(mod_id.to_ascii_lowercase())
+                .or_insert(summary);
+        }
+    }
+    let enabled: HashSet<String> = mods.iter().map(|id| id.to_ascii_lowercase()).collect();
+    let listed_items: HashSet<&str> = workshop_items.iter().map(String::as_str).collect();
+    let mut issues = Vec::new();
+
+    for id in duplicates(&mods) {
+        issues.push(ServerModIssue {
+            kind: ServerModIssueKind::DuplicateMod,
+            message: format!("Mods lists {id} more than once."),
+            id,
+            related: Vec::new(),
+        });
+    }
+    for id in duplicates(&workshop_items) {
+        issues.push(ServerModIssue {
+            kind: ServerModIssueKind::DuplicateWorkshopItem,
+            message: format!("WorkshopItems lists {id} more than once."),
+            id,
+            related: Vec::new(),
+        });
+    }
+
+    let mut checked = HashSet::new();
+    for mod_id in &mods {
+        let key = mod_id.to_ascii_lowercase();
+        if !checked.insert(key.clone()) {
+            continue;
+        }
+        let Some(summary) = summaries.get(&key) else {
+            issues.push(ServerModIssue {
+                kind: ServerModIssueKind::ModWithoutProvider,
+                id: mod_id.clone(),
+                related: Vec::new(),
+                message: format!("No scanned mod provides {mod_id}."),
+            });
+            continue;
+        };
+        // Local mods have no workshop item; the server loads them from disk.
+        if let Some(items) = providers.get(&key) {
+            if !items
+                .iter()
+                .any(|item| listed_items.contains(item.as_str()))
+            {
+                issues.push(ServerModIssue {
+                    kind: ServerModIssueKind::ModWorkshopItemMissing,
+                    id: mod_id.clone(),
+                    related: items.iter().cloned().collect(),
+                    message: format!(
+                        "{mod_id} comes from workshop item {}, which WorkshopItems does not list.",
+                        items.iter().cloned().collect::<Vec<_>>().join(" or ")
+                    ),
+                });
+            }
+        }
+        let mut missing: BTreeMap<String, String> = BTreeMap::new();
+        for dependency in summary
+            .requires
+            .iter()
+            .chain(summary.dependencies.iter())
+            .flatten()
+            .map(|dependency| normalize_mod_ref(dependency))
+            .filter(|dependency| !dependency.is_empty())
+        {
+            if !enabled.contains(&dependency.to_ascii_lowercase()) {
+                missing.insert(dependency.to_ascii_lowercase(), dependency);
+            }
+        }
+        if !missing.is_empty() {
+            let missing: Vec<String> = missing.into_values().collect();
+            issues.push(ServerModIssue {
+                kind: ServerModIssueKind::MissingDependency,
+                id: mod_id.clone(),
+                message: format!(
+                    "{mod_id} requires {}, which Mods does not enable.",
+                    missing.join(", ")
+                ),
+                related: missing,
+            });
+        }
+    }
+
+    let mut checked = HashSet::new();
+    for item in &workshop_items {
+        if !checked.insert(item.as_str()) {
+            continue;
+        }
+        match mods_by_item.get(item.as_str()) {
+            None => issues.push(ServerModIssue {
+                kind: ServerModIssueKind::UnknownWorkshopItem,
+                id: item.clone(),
+                related: Vec::new(),
+                message: format!("Workshop item {item} is not in the latest scan."),
+            }),
+            Some(item_mods) if !item_mods.iter().any(|id| enabled.contains(id)) => {
+                let names: Vec<String> = item_mods
+                    .iter()
+                    .map(|id| {
+                        summaries
+                            .get(id)
+                            .and_then(|summary| summary.mod_id.clone())
+                            .unwrap_or_else(|| id.clone())
+                    })
+                    .collect();
+                issues.push(ServerModIssue {
+                    kind: ServerModIssueKind::UnusedWorkshopItem,
+                    id: item.clone(),
+                    message: format!(
+                        "Workshop item {item} is downloaded but none of its mods ({}) are enabled.",
+                        names.join(", ")
+                    ),
+                    related: names,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    ServerModsReport {
+        mods,
+        workshop_items,
+        issues,
+    }
+}
+
+/// Checks a server's `Mods=` and `WorkshopItems=` against the installed mods
+/// the frontend last scanned.
+#[tauri::command]
+pub fn validate_server_mods(
+    user_dir: String,
+    server_name: String,
+    installed: Vec<ModSummary>,
+) -> Result<ServerModsReport, String> {
+    let _timer = scoped_timer("validate_server_mods");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SERVER_INI_FILE);
+    if !path.is_file() {
+        return Err(format!("Server \"{name}\" does not exist."));
+    }
+    Ok(check_server_mods(&ServerIni::read(&path)?, &installed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_mods_and_workshop_items_that_do_not_line_up() {
+        let installed: Vec<ModSummary> = serde_json::from_value(serde_json::json!([
+            { "id": "1", "mod_id": "Core", "name": "Core", "workshop_id": "100" },
+            { "id": "2", "mod_id": "Addon", "name": "Addon", "workshop_id": "200", "requires": ["\\Core", "Extra"] },
+            { "id": "3", "mod_id": "Unused", "name": "Unused", "workshop_id": "300" },
+            { "id": "4", "mod_id": "Local", "name": "Local" },
+        ]))
+        .expect("summaries should deserialize");
+        let ini = ServerIni::parse(
+            "Mods=\\Core;\\Addon;\\Local;\\Ghost;\\core\nWorkshopItems=100;300;999;100\n",
+        );
+
+        let report = check_server_mods(&ini, &installed);
+        let issues: Vec<(ServerModIssueKind, &str)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.id.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (ServerModIssueKind::DuplicateMod, "core"),
+                (ServerModIssueKind::DuplicateWorkshopItem, "100"),
+                (ServerModIssueKind::ModWorkshopItemMissing, "Addon"),
+                (ServerModIssueKind::MissingDependency, "Addon"),
+                (ServerModIssueKind::ModWithoutProvider, "Ghost"),
+                (ServerModIssueKind::UnusedWorkshopItem, "300"),
+                (ServerModIssueKind::UnknownWorkshopItem, "999"),
+            ]
+        );
+        assert_eq!(report.issues[3].related, ["Extra"]);
+    }
+}
@@ -0,0 +1,480 @@
+use crate::pz_compat::validate_server_name;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+/// Lines kept for the log view; older lines are dropped first.
+const LOG_CAPACITY: usize = 2_000;
+/// Printed by the dedicated server once it accepts connections.
+const STARTED_MARKER: &str = "SERVER STARTED";
+/// Console command that saves the world and shuts the server down.
+const QUIT_COMMAND: &str = "quit";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerProcessStatus {
+    pub server_name: Option<String>,
+    /// `idle`, `starting`, `running`, `stopping`, `stopped` or `crashed`.
+    pub state: String,
+    pub message: String,
+    pub pid: Option<u32>,
+    pub logs: VecDeque<String>,
+    /// Lines that no longer fit in `logs`.
+    pub dropped_log_lines: u64,
+    pub started_at_unix_ms: Option<u64>,
+    pub ready_at_unix_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+    #[serde(skip)]
+    requested_exit: Option<ExitRequest>,
+}
+
+/// How the user asked the running server to end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitRequest {
+    /// `quit` was sent; only a clean exit counts as stopped.
+    Quit,
+    /// The process was killed, which never exits cleanly.
+    Kill,
+}
+
+impl Default for ServerProcessStatus {
+    fn default() -> Self {
+        Self {
+            server_name: None,
+            state: "idle".to_string(),
+            message: "No dedicated server is running.".to_string(),
+            pid: None,
+            logs: VecDeque::new(),
+            dropped_log_lines: 0,
+            started_at_unix_ms: None,
+            ready_at_unix_ms: None,
+            exit_code: None,
+            requested_exit: None,
+        }
+    }
+}
+
+impl ServerProcessStatus {
+    fn add_log(&mut self, line: impl Into<String>) {
+        if self.logs.len() == LOG_CAPACITY {
+            self.logs.pop_front();
+            self.dropped_log_lines += 1;
+        }
+        self.logs.push_back(line.into());
+    }
+}
+
+/// Runs one dedicated server at a time from its start script and follows its
+/// console output.
+#[derive(Default)]
+pub struct ServerProcessManager {
+    status: Arc<Mutex<ServerProcessStatus>>,
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+}
+
+fn unix_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|value| value.as_millis().try_into().unwrap_or(u64::MAX))
+        .unwrap_or(0)
+}
+
+fn is_active_state(state: &str) -> bool {
+    matches!(state, "starting" | "running" | "stopping")
+}
+
+fn update_status(
+    status: &Arc<Mutex<ServerProcessStatus>>,
+    update: impl FnOnce(&mut ServerProcessStatus),
+) {
+    let mut current = status.lock().expect("server status lock poisoned");
+    update(&mut current);
+}
+
+fn handle_console_line(status: &Arc<Mutex<ServerProcessStatus>>, line: &str) {
+    update_status(status, |current| {
+        if current.state == "starting" && line.contains(STARTED_MARKER) {
+            current.state = "running".to_string();
+            current.message = "Dedicated server is accepting connections.".to_string();
+            current.ready_at_unix_ms = Some(unix_time_millis());
+        }
+        current.add_log(line);
+    });
+}
+
+fn follow_output(
+    status: Arc<Mutex<ServerProcessStatus>>,
+    output: impl Read + Send + 'static,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            handle_console_line(&status, &line);
+        }
+    })
+}
+
+impl ServerProcessManager {
+    pub(crate) fn snapshot(&self) -> ServerProcessStatus {
+        self.status
+            .lock()
+            .expect("server status lock poisoned")
+            .clone()
+    }
+
+    /// Whether this manager is running `server_name`.
+    pub(crate) fn is_running(&self, server_name: &str) -> bool {
+        let status = self.status.lock().expect("server status lock poisoned");
+        is_active_state(&status.state)
+            && status
+                .server_name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(server_name))
+    }
+
+    /// Launches `script` with `-servername <name>` from the script's folder,
+    /// which the stock start scripts expect.
+    pub(crate) fn start(
+        &self,
+        script: &Path,
+        server_name: &str,
+        extra_args: &[String],
+    ) -> Result<ServerProcessStatus, String> {
+        let server_name = validate_server_name(server_name)?;
+        if !script.is_file() {
+            return Err(format!(
+                "The server start script does not exist: {}",
+                script.display()
+            ));
+        }
+        let mut status = self.status.lock().expect("server status lock poisoned");
+        if is_active_state(&status.state) {
+            return Err(format!(
+                "Server \"{}\" is already running.",
+                status.server_name.as_deref().unwrap_or_default()
+            ));
+        }
+
+        let mut command = Command::new(script);
+        command
+            .arg("-servername")
+            .arg(server_name)
+            .args(extra_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dir) = script.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            command.current_dir(dir);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x08000000);
+        }
+        #[cfg(unix)]
+        {
+            // Its own process group, so a kill reaches the JVM the script starts.
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|error| format!("Could not start the dedicated server: {error}"))?;
+
+        *status = ServerProcessStatus {
+            server_name: Some(server_name.to_string()),
+            state: "starting".to_string(),
+            message: format!("Starting dedicated server \"{server_name}\"…"),
+            pid: Some(child.id()),
+            started_at_unix_ms: Some(unix_time_millis()),
+            ..ServerProcessStatus::default()
+        };
+        status.add_log(
+            format!(
+                "{} -servername {server_name} {}",
+                script.display(),
+                extra_args.join(" ")
+            )
+            .trim_end()
+            .to_string(),
+        );
+        let snapshot = status.clone();
+        drop(status);
+
+        *self.stdin.lock().expect("server stdin lock poisoned") = child.stdin.take();
+        let readers: Vec<thread::JoinHandle<()>> = [
+            child
+                .stdout
+                .take()
+                .map(|out| follow_output(Arc::clone(&self.status), out)),
+            child
+                .stderr
+                .take()
+                .map(|err| follow_output(Arc::clone(&self.status), err)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let status = Arc::clone(&self.status);
+        let stdin = Arc::clone(&self.stdin);
+        thread::spawn(move || {
+            let result = child.wait();
+            for reader in readers {
+                let _ = reader.join();
+            }
+            *stdin.lock().expect("server stdin lock poisoned") = None;
+            update_status(&status, |current| {
+                current.pid = None;
+                current.exit_code = result.as_ref().ok().and_then(|exit| exit.code());
+                let clean = result.as_ref().is_ok_and(|exit| exit.success());
+                match current.requested_exit.take() {
+                    Some(ExitRequest::Quit) if clean => {
+                        current.state = "stopped".to_string();
+                        current.message = "Dedicated server stopped.".to_string();
+                    }
+                    Some(ExitRequest::Kill) => {
+                        current.state = "stopped".to_string();
+                        current.message =
+                            "Dedicated server killed without saving the world.".to_string();
+                    }
+                    _ => {
+                        current.state = "crashed".to_string();
+                        current.message = match &result {
+                            Ok(exit) => format!("Dedicated server exited unexpectedly ({exit})."),
+                            Err(error) => format!("Lost track of the dedicated server: {error}"),
+                        };
+                    }
+                }
+                let message = current.message.clone();
+                current.add_log(message);
+            });
+        });
+        Ok(snapshot)
+    }
+
+    /// Records the request before acting on it, so the exit is never seen
+    /// first, and puts the previous status back when `act` fails.
+    fn request_exit(
+        &self,
+        request: ExitRequest,
+        message: &str,
+        act: impl FnOnce() -> Result<(), String>,
+    ) -> Result<ServerProcessStatus, String> {
+        let previous = self.snapshot();
+        update_status(&self.status, |current| {
+            current.state = "stopping".to_string();
+            current.message = message.to_string();
+            current.requested_exit = Some(request);
+        });
+        if let Err(error) = act() {
+            update_status(&self.status, |current| {
+                if current.state == "stopping" {
+                    current.state = previous.state;
+                    current.message = previous.message;
+                    current.requested_exit = previous.requested_exit;
+                }
+            });
+            return Err(error);
+        }
+        Ok(self.snapshot())
+    }
+
+    /// Sends `quit` to the server console, which saves the world first.
+    pub(crate) fn stop(&self) -> Result<ServerProcessStatus, String> {
+        if !is_active_state(&self.snapshot().state) {
+            return Ok(self.snapshot());
+        }
+        self.request_exit(
+            ExitRequest::Quit,
+            "Saving the world and shutting down…",
+            || {
+                let mut stdin = self.stdin.lock().expect("server stdin lock poisoned");
+                let pipe = stdin
+                    .as_mut()
+                    .ok_or_else(|| "The dedicated server console is not available.".to_string())?;
+                writeln!(pipe, "{QUIT_COMMAND}")
+                    .and_then(|_| pipe.flush())
+                    .map_err(|error| {
+                        format!("Could not send quit to the dedicated server: {error}")
+                    })?;
+                update_status(&self.status, |current| {
+                    current.add_log(format!("> {QUIT_COMMAND}"))
+                });
+                Ok(())
+            },
+        )
+    }
+
+    /// Ends the server immediately without saving.
+    pub(crate) fn kill(&self) -> Result<ServerProcessStatus, String> {
+        let current = self.snapshot();
+        let Some(pid) = current.pid.filter(|_| is_active_state(&current.state)) else {
+            return Ok(current);
+        };
+        self.request_exit(ExitRequest::Kill, "Killing the dedicated server…", || {
+            kill_server_process(pid)
+        })
+    }
+}
+
+#[cfg(windows)]
+fn kill_server_process(pid: u32) -> Result<(), String> {
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .status()
+        .map_err(|error| format!("Could not kill the dedicated server: {error}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "The dedicated server could not be killed (exit status {status})."
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn kill_server_process(pid: u32) -> Result<(), String> {
+    let status = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pid}")])
+        .status()
+        .map_err(|error| format!("Could not kill the dedicated server: {error}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "The dedicated server could not be killed (exit status {status})."
+        ))
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+fn kill_server_process(_pid: u32) -> Result<(), String> {
+    Err("This platform does not support killing the dedicated server.".to_string())
+}
+
+#[tauri::command]
+pub fn start_dedicated_server(
+    manager: State<'_, ServerProcessManager>,
+    script_path: String,
+    server_name: String,
+    extra_args: Option<Vec<String>>,
+) -> Result<ServerProcessStatus, String> {
+    manager.start(
+        Path::new(script_path.trim()),
+        &server_name,
+        &extra_args.unwrap_or_default(),
+    )
+}
+
+#[tauri::command]
+pub fn get_dedicated_server_status(
+    manager: State<'_, ServerProcessManager>,
+) -> ServerProcessStatus {
+    manager.snapshot()
+}
+
+#[tauri::command]
+pub fn stop_dedicated_server(
+    manager: State<'_, ServerProcessManager>,
+) -> Result<ServerProcessStatus, String> {
+    manager.stop()
+}
+
+#[tauri::command]
+pub fn kill_dedicated_server(
+    manager: State<'_, ServerProcessManager>,
+) -> Result<ServerProcessStatus, String> {
+    manager.kill()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    fn wait_for_state(manager: &ServerProcessManager, state: &str) -> ServerProcessStatus {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let status = manager.snapshot();
+            if status.state == state || Instant::now() > deadline {
+                return status;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn follows_a_stand_in_server_through_start_stop_crash_and_kill() {
+        let root = TestDir::new("pz-server-process-test");
+        let script = root.join("start-server.sh");
+        fs::write(
+            &script,
+            "#!/bin/sh\necho \"args: $*\"\nif [ \"$3\" = crash ]; then echo boom >&2; exit 3; fi\necho 'LOG  : General     > *** SERVER STARTED ****'\nif [ \"$3\" = hang ]; then while true; do sleep 1; done; fi\nread command\necho \"console: $command\"\nif [ \"$3\" = dirty ]; then exit 4; fi\n",
+        )
+        .expect("script should be written");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))
+            .expect("script should be executable");
+
+        let manager = ServerProcessManager::default();
+        manager
+            .start(&script, "test", &[])
+            .expect("server should start");
+        assert!(manager.start(&script, "other", &[]).is_err());
+        let running = wait_for_state(&manager, "running");
+        assert!(running.ready_at_unix_ms.is_some());
+        assert!(manager.is_running("TEST"));
+        manager.stop().expect("server should stop");
+        let stopped = wait_for_state(&manager, "stopped");
+        assert_eq!(stopped.exit_code, Some(0));
+        assert!(
+            stopped
+                .logs
+                .iter()
+                .any(|line| line == "args: -servername test")
+        );
+        assert!(stopped.logs.iter().any(|line| line == "console: quit"));
+
+        manager
+            .start(&script, "test", &["crash".to_string()])
+            .expect("server should start");
+        let crashed = wait_for_state(&manager, "crashed");
+        assert_eq!(crashed.exit_code, Some(3));
+        assert!(crashed.ready_at_unix_ms.is_none());
+        assert!(crashed.logs.iter().any(|line| line == "boom"));
+
+        manager
+            .start(&script, "test", &["dirty".to_string()])
+            .expect("server should start");
+        wait_for_state(&manager, "running");
+        manager.stop().expect("quit should be sent");
+        assert_eq!(wait_for_state(&manager, "crashed").exit_code, Some(4));
+
+        manager
+            .start(&script, "test", &["hang".to_string()])
+            .expect("server should start");
+        wait_for_state(&manager, "running");
+        manager.kill().expect("server should be killed");
+        assert_eq!(wait_for_state(&manager, "stopped").state, "stopped");
+        assert!(!manager.is_running("test"));
+    }
+
+    #[test]
+    fn keeps_a_bounded_log() {
+        let mut status = ServerProcessStatus::default();
+        for index in 0..LOG_CAPACITY + 5 {
+            status.add_log(index.to_string());
+        }
+        assert_eq!(status.logs.len(), LOG_CAPACITY);
+        assert_eq!(status.dropped_log_lines, 5);
+        assert_eq!(status.logs.front().map(String::as_str), Some("5"));
+    }
+}
@@ -0,0 +1,782 @@
+use crate::loadout::LoadoutMod;
+use crate::map_mods::mod_map_sources;
+use crate::pz_compat::{
+    SERVER_INI_FILE, SPAWN_POINTS_FILE, SPAWN_REGIONS_FILE, VANILLA_MAPS, VANILLA_WORLD_MAP,
+    server_config_path, server_dir, validate_server_name,
+};
+use crate::sandbox_vars::{
+    Spanned, Token, indent_of, insert_entry, lua_string, remove_entry, tokenize,
+};
+use crate::server_ini::ServerIni;
+use crate::timing::scoped_timer;
+use crate::utils::{read_optional_text, write_atomically};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// One `{ name = ..., file = ... }` row of `{name}_spawnregions.lua`.  `file`
+/// is relative to the game or a mod folder; `serverfile` to `Zomboid/Server`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnRegion {
+    pub name: String,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub server_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnPoint {
+    #[serde(default)]
+    pub world_x: Option<i64>,
+    #[serde(default)]
+    pub world_y: Option<i64>,
+    #[serde(default)]
+    pub pos_x: Option<i64>,
+    #[serde(default)]
+    pub pos_y: Option<i64>,
+    #[serde(default)]
+    pub pos_z: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfessionSpawnPoints {
+    pub profession: String,
+    pub points: Vec<SpawnPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSpawnFiles {
+    pub regions_path: String,
+    /// `None` when the file does not exist.
+    pub regions: Option<Vec<SpawnRegion>>,
+    pub points_path: String,
+    pub points: Option<Vec<ProfessionSpawnPoints>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnRegionPlan {
+    /// The server's `Map=` entries the regions were built from.
+    pub maps: Vec<String>,
+    pub regions: Vec<SpawnRegion>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnRegionIssue {
+    pub region: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LuaValue {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    Table {
+        items: Vec<LuaValue>,
+        fields: Vec<(String, LuaValue)>,
+    },
+}
+
+impl LuaValue {
+    fn field(&self, key: &str) -> Option<&LuaValue> {
+        match self {
+            LuaValue::Table { fields, .. } => fields
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            LuaValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            LuaValue::Number(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+}
+
+struct LuaReader<'a> {
+    tokens: Vec<&'a Spanned>,
+    pos: usize,
+}
+
+impl LuaReader<'_> {
+    fn error(&self, message: &str) -> String {
+        match self.tokens.get(self.pos) {
+            Some(token) => format!("{message} on line {}.", token.line),
+            None => format!("{message} at the end of the file."),
+        }
+    }
+
+    fn value(&mut self) -> Result<LuaValue, String> {
+        let token = self.tokens.get(self.pos).map(|token| &token.token);
+        self.pos += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(LuaValue::Number(*value)),
+            Some(Token::Str(value)) => Ok(LuaValue::String(value.clone())),
+            Some(Token::Name(name)) if name == "true" || name == "false" => {
+                Ok(LuaValue::Boolean(name == "true"))
+            }
+            Some(Token::Open) => self.table(),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("Expected a value"))
+            }
+        }
+    }
+
+    fn table(&mut self) -> Result<LuaValue, String> {
+        let (entries, _) = self.entries()?;
+        let mut items = Vec::new();
+        let mut fields = Vec::new();
+        for entry in entries {
+            match entry.key {
+                Some(key) => fields.push((key, entry.value)),
+                None => items.push(entry.value),
+            }
+        }
+        Ok(LuaValue::Table { items, fields })
+    }
+
+    /// Reads the entries of a table up to its closing `}`, whose offset is
+    /// returned with them.
+    fn entries(&mut self) -> Result<(Vec<TableEntry>, usize), String> {
+        let mut entries = Vec::new();
+        loop {
+            let start = self.tokens.get(self.pos).map_or(0, |token| token.start);
+            let key = match (
+                self.tokens.get(self.pos).map(|token| &token.token),
+                self.tokens.get(self.pos + 1).map(|token| &token.token),
+            ) {
+                (Some(Token::Close), _) => {
+                    self.pos += 1;
+                    return Ok((entries, start));
+                }
+                (Some(Token::Separator), _) => {
+                    self.pos += 1;
+                    continue;
+                }
+                (Some(Token::Name(name)), Some(Token::Equals)) => {
+                    self.pos += 2;
+                    Some(name.clone())
+                }
+                (Some(Token::OpenBracket), Some(Token::Str(name))) => {
+                    let name = name.clone();
+                    self.pos += 2;
+                    if !matches!(
+                        self.tokens.get(self.pos).map(|token| &token.token),
+                        Some(Token::CloseBracket)
+                    ) || !matches!(
+                        self.tokens.get(self.pos + 1).map(|token| &token.token),
+                        Some(Token::Equals)
+                    ) {
+                        return Err(self.error("Expected '] ='"));
+                    }
+                    self.pos += 2;
+                    Some(name)
+                }
+                (None, _) => return Err(self.error("Missing closing '}'")),
+                _ => None,
+            };
+            let value = self.value()?;
+            entries.push(TableEntry {
+                key,
+                value,
+                start,
+                end: self.tokens[self.pos - 1].end,
+            });
+        }
+    }
+}
+
+/// An entry of a Lua table with the byte range it spans in the source text.
+struct TableEntry {
+    key: Option<String>,
+    value: LuaValue,
+    start: usize,
+    end: usize,
+}
+
+/// A reader positioned at the table a spawn file's function returns.
+fn returned_table_reader(tokens: &[Spanned]) -> Result<LuaReader<'_>, String> {
+    let tokens: Vec<&Spanned> = tokens
+        .iter()
+        .filter(|token| !matches!(token.token, Token::Comment(_)))
+        .collect();
+    let start = tokens
+        .iter()
+        .position(|token| token.token == Token::Name("return".to_string()))
+        .ok_or_else(|| "No returned table was found.".to_string())?;
+    Ok(LuaReader {
+        tokens,
+        pos: start + 1,
+    })
+}
+
+/// Reads the table a spawn file's function returns.
+fn parse_returned_table(text: &str) -> Result<LuaValue, String> {
+    let tokens = tokenize(text)?;
+    returned_table_reader(&tokens)?.value()
+}
+
+/// Replaces, adds or removes the entry of the returned table that `matches`
+/// picks, keeping every other line and comment of the file as it is.
+fn edit_returned_entry(
+    text: &str,
+    matches: impl Fn(&TableEntry) -> bool,
+    replacement: Option<String>,
+) -> Result<String, String> {
+    let tokens = tokenize(text)?;
+    let mut reader = returned_table_reader(&tokens)?;
+    if !matches!(
+        reader.tokens.get(reader.pos).map(|token| &token.token),
+        Some(Token::Open)
+    ) {
+        return Err(reader.error("Expected a returned table"));
+    }
+    reader.pos += 1;
+    let (entries, close) = reader.entries()?;
+    let existing = entries.iter().find(|entry| matches(entry));
+    let indented = |entry: &str, indent: &str| entry.replace('\n', &format!("\n{indent}"));
+    Ok(match (existing, replacement) {
+        (Some(entry), Some(replacement)) => format!(
+            "{}{}{}",
+            &text[..entry.start],
+            indented(&replacement, &indent_of(text, entry.start)),
+            &text[entry.end..]
+        ),
+        (Some(entry), None) => remove_entry(text, entry.start, entry.end),
+        (None, Some(replacement)) => {
+            let table_indent = indent_of(text, close);
+            let entry_indent = entries
+                .first()
+                .map(|entry| indent_of(text, entry.start))
+                .unwrap_or_else(|| format!("{table_indent}\t"));
+            let separator_at = entries.last().map(|entry| entry.end).filter(|&end| {
+                !text[end..]
+                    .trim_start_matches([' ', '\t'])
+                    .starts_with([',', ';'])
+            });
+            insert_entry(
+                text,
+                close,
+                separator_at,
+                &table_indent,
+                &format!("{entry_indent}{},\n", indented(&replacement, &entry_indent)),
+            )
+        }
+        (None, None) => text.to_string(),
+    })
+}
+
+pub(crate) fn parse_spawn_regions(text: &str) -> Result<Vec<SpawnRegion>, String> {
+    let LuaValue::Table { items, .. } = parse_returned_table(text)? else {
+        return Err("SpawnRegions does not return a table.".to_string());
+    };
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            Some(SpawnRegion {
+                name: item.field("name")?.as_str()?.to_string(),
+                file: item
+                    .field("file")
+                    .and_then(LuaValue::as_str)
+                    .map(str::to_string),
+                server_file: item
+                    .field("serverfile")
+                    .and_then(LuaValue::as_str)
+                    .map(str::to_string),
+            })
+        })
+        .collect())
+}
+
+pub(crate) fn parse_spawn_points(text: &str) -> Result<Vec<ProfessionSpawnPoints>, String> {
+    let LuaValue::Table { fields, .. } = parse_returned_table(text)? else {
+        return Err("SpawnPoints does not return a table.".to_string());
+    };
+    Ok(fields
+        .iter()
+        .map(|(profession, value)| ProfessionSpawnPoints {
+            profession: profession.clone(),
+            points: match value {
+                LuaValue::Table { items, .. } => items
+                    .iter()
+                    .map(|point| SpawnPoint {
+                        world_x: point.field("worldX").and_then(LuaValue::as_int),
+                        world_y: point.field("worldY").and_then(LuaValue::as_int),
+                        pos_x: point.field("posX").and_then(LuaValue::as_int),
+                        pos_y: point.field("posY").and_then(LuaValue::as_int),
+                        pos_z: point.field("posZ").and_then(LuaValue::as_int),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            },
+        })
+        .collect())
+}
+
+fn lua_key(key: &str) -> String {
+    let identifier = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if identifier {
+        key.to_string()
+    } else {
+        format!("[{}]", lua_string(key))
+    }
+}
+
+/// One region row as written between the braces of the returned table.
+fn region_entry(region: &SpawnRegion) -> String {
+    let mut out = format!("{{ name = {}", lua_string(&region.name));
+    if let Some(file) = &region.file {
+        out.push_str(&format!(", file = {}", lua_string(file)));
+    }
+    if let Some(server_file) = &region.server_file {
+        out.push_str(&format!(", serverfile = {}", lua_string(server_file)));
+    }
+    out.push_str(" }");
+    out
+}
+
+/// One profession and its points, with lines after the first indented
+/// relative to the profession's own line.
+fn points_entry(profession: &ProfessionSpawnPoints) -> String {
+    let mut out = format!("{} = {{\n", lua_key(&profession.profession));
+    for point in &profession.points {
+        let fields: Vec<String> = [
+            ("worldX", point.world_x),
+            ("worldY", point.world_y),
+            ("posX", point.pos_x),
+            ("posY", point.pos_y),
+            ("posZ", point.pos_z),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some(format!("{key} = {}", value?)))
+        .collect();
+        out.push_str(&format!("\t{{ {} }},\n", fields.join(", ")));
+    }
+    out.push('}');
+    out
+}
+
+pub(crate) fn render_spawn_regions(regions: &[SpawnRegion]) -> String {
+    let mut out = String::from("function SpawnRegions()\n\treturn {\n");
+    for region in regions {
+        out.push_str(&format!("\t\t{},\n", region_entry(region)));
+    }
+    out.push_str("\t}\nend\n");
+    out
+}
+
+pub(crate) fn render_spawn_points(professions: &[ProfessionSpawnPoints]) -> String {
+    let mut out = String::from("function SpawnPoints()\n\treturn {\n");
+    for profession in professions {
+        out.push_str(&format!(
+            "\t\t{},\n",
+            points_entry(profession).replace('\n', "\n\t\t")
+        ));
+    }
+    out.push_str("\t}\nend\n");
+    out
+}
+
+/// Sets the region called `name` in a spawnregions file to `region`, or
+/// removes it when `region` is `None`, leaving the rest of the file intact.
+pub(crate) fn edit_spawn_region(
+    text: &str,
+    name: &str,
+    region: Option<&SpawnRegion>,
+) -> Result<String, String> {
+    edit_returned_entry(
+        text,
+        |entry| {
+            entry.key.is_none()
+                && entry.value.field("name").and_then(LuaValue::as_str) == Some(name)
+        },
+        region.map(region_entry),
+    )
+}
+
+/// Sets the points of `profession` in a spawnpoints file, or removes the
+/// profession when `points` is `None`, leaving the rest of the file intact.
+pub(crate) fn edit_spawn_points(
+    text: &str,
+    profession: &str,
+    points: Option<&ProfessionSpawnPoints>,
+) -> Result<String, String> {
+    edit_returned_entry(
+        text,
+        |entry| entry.key.as_deref() == Some(profession),
+        points.map(points_entry),
+    )
+}
+
+fn spawnpoints_path(map_name: &str) -> String {
+    format!("media/maps/{map_name}/spawnpoints.lua")
+}
+
+/// The server's `Map=` entries in order.
+fn server_maps(user_dir: &str, server_name: &str) -> Result<Vec<String>, String> {
+    let ini = ServerIni::read(&server_config_path(user_dir, server_name, SERVER_INI_FILE))?;
+    Ok(ini
+        .get("Map")
+        .unwrap_or_default()
+        .split(';')
+        .map(str::trim)
+        .filter(|map| !map.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// One region per vanilla town when the vanilla world is loaded, and one per
+/// mod map that ships a `spawnpoints.lua`, in `Map=` order.
+pub(crate) fn plan_regions(maps: &[String], mods: &[LoadoutMod]) -> SpawnRegionPlan {
+    let sources = mod_map_sources(mods);
+    let mut regions: Vec<SpawnRegion> = Vec::new();
+    let mut warnings = Vec::new();
+    let push = |regions: &mut Vec<SpawnRegion>, name: &str| {
+        if !regions.iter().any(|region| region.name == name) {
+            regions.push(SpawnRegion {
+                name: name.to_string(),
+                file: Some(spawnpoints_path(name)),
+                server_file: None,
+            });
+        }
+    };
+    for map in maps {
+        if map == VANILLA_WORLD_MAP {
+            for vanilla in VANILLA_MAPS {
+                push(&mut regions, vanilla);
+            }
+            continue;
+        }
+        if VANILLA_MAPS.contains(&map.as_str()) {
+            push(&mut regions, map);
+            continue;
+        }
+        match sources.iter().find(|source| source.map_name == *map) {
+            None => warnings.push(format!(
+                "Map \"{map}\" is not provided by any mod in the loadout."
+            )),
+            Some(source)
+                if !source
+                    .dirs
+                    .iter()
+                    .any(|dir| dir.join("spawnpoints.lua").is_file()) =>
+            {
+                warnings.push(format!(
+                    "Map \"{map}\" from {} has no spawnpoints.lua; players cannot spawn there.",
+                    source.mod_id.as_deref().unwrap_or("an unknown mod")
+                ))
+            }
+            Some(_) => push(&mut regions, map),
+        }
+    }
+    SpawnRegionPlan {
+        maps: maps.to_vec(),
+        regions,
+        warnings,
+    }
+}
+
+/// Flags regions whose map is not loaded by the server or the loadout, and
+/// regions whose spawn point file cannot be found.
+pub(crate) fn check_regions(
+    regions: &[SpawnRegion],
+    maps: &[String],
+    mods: &[LoadoutMod],
+    game_dir: Option<&Path>,
+    server_dir: &Path,
+) -> Vec<SpawnRegionIssue> {
+    let sources = mod_map_sources(mods);
+    let vanilla_loaded = maps.iter().any(|map| map == VANILLA_WORLD_MAP);
+    let mut roots: Vec<PathBuf> = game_dir.map(Path::to_path_buf).into_iter().collect();
+    for source in &sources {
+        // `<content>/media/maps/<map>` back to `<content>`.
+        roots.extend(
+            source
+                .dirs
+                .iter()
+                .filter_map(|dir| Some(dir.parent()?.parent()?.parent()?.to_path_buf())),
+        );
+    }
+
+    let mut issues = Vec::new();
+    for region in regions {
+        let mut issue = |message: String| {
+            issues.push(SpawnRegionIssue {
+                region: region.name.clone(),
+                message,
+            })
+        };
+        let map = region
+            .file
+            .as_deref()
+            .and_then(|file| {
+                file.replace('\\', "/")
+                    .strip_prefix("media/maps/")
+                    .map(str::to_string)
+            })
+            .and_then(|rest| rest.split('/').next().map(str::to_string));
+        if let Some(map) = &map {
+            let vanilla = VANILLA_MAPS.contains(&map.as_str());
+            if vanilla && !vanilla_loaded {
+                issue(format!(
+                    "Uses vanilla map \"{map}\" but Map= does not include {VANILLA_WORLD_MAP}."
+                ));
+            } else if !vanilla && !maps.contains(map) {
+                issue(format!(
+                    "Uses map \"{map}\", which is not in the server's Map= list."
+                ));
+            } else if !vanilla && !sources.iter().any(|source| source.map_name == *map) {
+                issue(format!(
+                    "Uses map \"{map}\", which no mod in the loadout provides."
+                ));
+            }
+        }
+        match (&region.file, &region.server_file) {
+            (Some(file), _) => {
+                let checkable = game_dir.is_some()
+                    || map
+                        .as_deref()
+                        .is_some_and(|map| !VANILLA_MAPS.contains(&map));
+                if checkable && !roots.iter().any(|root| root.join(file).is_file()) {
+                    issue(format!("Spawn point file \"{file}\" does not exist."));
+                }
+            }
+            (None, Some(server_file)) => {
+                if !server_dir.join(server_file).is_file() {
+                    issue(format!(
+                        "Server spawn point file \"{server_file}\" does not exist."
+                    ));
+                }
+            }
+            (None, None) => issue("Region has neither a file nor a serverfile.".to_string()),
+        }
+    }
+    issues
+}
+
+#[tauri::command]
+pub fn read_spawn_files(user_dir: String, server_name: String) -> Result<ServerSpawnFiles, String> {
+    let _timer = scoped_timer("read_spawn_files");
+    let name = validate_server_name(&server_name)?;
+    let regions_path = server_config_path(&user_dir, name, SPAWN_REGIONS_FILE);
+    let points_path = server_config_path(&user_dir, name, SPAWN_POINTS_FILE);
+    Ok(ServerSpawnFiles {
+        regions: read_optional_text(&regions_path)?
+            .map(|text| parse_spawn_regions(&text))
+            .transpose()?,
+        points: read_optional_text(&points_path)?
+            .map(|text| parse_spawn_points(&text))
+            .transpose()?,
+        regions_path: regions_path.to_string_lossy().to_string(),
+        points_path: points_path.to_string_lossy().to_string(),
+    })
+}
+
+#[tauri::command]
+pub fn write_spawn_regions(
+    user_dir: String,
+    server_name: String,
+    regions: Vec<SpawnRegion>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("write_spawn_regions");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SPAWN_REGIONS_FILE);
+    write_atomically(&path, render_spawn_regions(&regions).as_bytes())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn write_spawn_points(
+    user_dir: String,
+    server_name: String,
+    points: Vec<ProfessionSpawnPoints>,
+) -> Result<String, String> {
+    let _timer = scoped_timer("write_spawn_points");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SPAWN_POINTS_FILE);
+    write_atomically(&path, render_spawn_points(&points).as_bytes())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Builds spawn regions for the server's `Map=` list without writing them;
+/// pass the result to `write_spawn_regions` to apply it.
+#[tauri::command]
+pub fn plan_spawn_regions(
+    user_dir: String,
+    server_name: String,
+    mods: Vec<LoadoutMod>,
+) -> Result<SpawnRegionPlan, String> {
+    let _timer = scoped_timer("plan_spawn_regions");
+    let name = validate_server_name(&server_name)?;
+    Ok(plan_regions(&server_maps(&user_dir, name)?, &mods))
+}
+
+#[tauri::command]
+pub fn validate_spawn_regions(
+    user_dir: String,
+    server_name: String,
+    media_dir: String,
+    mods: Vec<LoadoutMod>,
+) -> Result<Vec<SpawnRegionIssue>, String> {
+    let _timer = scoped_timer("validate_spawn_regions");
+    let name = validate_server_name(&server_name)?;
+    let path = server_config_path(&user_dir, name, SPAWN_REGIONS_FILE);
+    let text =
+        read_optional_text(&path)?.ok_or_else(|| format!("{} does not exist.", path.display()))?;
+    let media_dir = media_dir.trim();
+    let game_dir = (!media_dir.is_empty())
+        .then(|| Path::new(media_dir).parent().map(Path::to_path_buf))
+        .flatten();
+    Ok(check_regions(
+        &parse_spawn_regions(&text)?,
+        &server_maps(&user_dir, name)?,
+        &mods,
+        game_dir.as_deref(),
+        &server_dir(&user_dir),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn round_trips_spawn_files() {
+        let regions_text = "function SpawnRegions()\n\treturn {\n\t\t{ name = \"Muldraugh, KY\", file = \"media/maps/Muldraugh, KY/spawnpoints.lua\" },\n\t\t-- { name = \"Old\", file = \"media/maps/Old/spawnpoints.lua\" },\n\t\t{ name = \"Twdprof\", serverfile = \"servertest_spawnpoints.lua\" },\n\t}\nend\n";
+        let regions = parse_spawn_regions(regions_text).expect("regions should parse");
+        assert_eq!(
+            regions,
+            vec![
+                SpawnRegion {
+                    name: "Muldraugh, KY".to_string(),
+                    file: Some("media/maps/Muldraugh, KY/spawnpoints.lua".to_string()),
+                    server_file: None,
+                },
+                SpawnRegion {
+                    name: "Twdprof".to_string(),
+                    file: None,
+                    server_file: Some("servertest_spawnpoints.lua".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            parse_spawn_regions(&render_spawn_regions(&regions))
+                .expect("rendered regions should parse"),
+            regions
+        );
+
+        let points_text = "function SpawnPoints()\n\treturn {\n\t\tunemployed = {\n\t\t\t{ worldX = 40, worldY = 22, posX = 67, posY = 201 },\n\t\t\t{ worldX = 41, worldY = 22, posX = 10, posY = 20, posZ = 1 },\n\t\t},\n\t\t[\"fire officer\"] = {\n\t\t},\n\t}\nend\n";
+        let points = parse_spawn_points(points_text).expect("points should parse");
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].points[1].pos_z, Some(1));
+        assert_eq!(points[1].profession, "fire officer");
+        assert_eq!(render_spawn_points(&points), points_text);
+    }
+
+    #[test]
+    fn edits_spawn_entries_in_place() {
+        let points_text = "function SpawnPoints()\n\treturn {\n\t\t-- default spawn\n\t\tunemployed = {\n\t\t\t{ worldX = 40, worldY = 22, posX = 67, posY = 201 },\n\t\t},\n\t\t-- firefighters\n\t\t[\"fire officer\"] = {\n\t\t},\n\t}\nend\n";
+        let moved = ProfessionSpawnPoints {
+            profession: "unemployed".to_string(),
+            points: vec![SpawnPoint {
+                world_x: Some(41),
+                world_y: Some(23),
+                pos_x: Some(1),
+                pos_y: Some(2),
+                pos_z: None,
+            }],
+        };
+        let edited = edit_spawn_points(points_text, "unemployed", Some(&moved))
+            .expect("points should be replaced");
+        let edited =
+            edit_spawn_points(&edited, "fire officer", None).expect("points should be removed");
+        assert_eq!(
+            edited,
+            "function SpawnPoints()\n\treturn {\n\t\t-- default spawn\n\t\tunemployed = {\n\t\t\t{ worldX = 41, worldY = 23, posX = 1, posY = 2 },\n\t\t},\n\t}\nend\n"
+        );
+
+        let regions_text =
+            "function SpawnRegions()\n\treturn { { name = \"Town\", file = \"a.lua\" } }\nend\n";
+        let camp = SpawnRegion {
+            name: "Camp".to_string(),
+            file: None,
+            server_file: Some("servertest_spawnpoints.lua".to_string()),
+        };
+        let edited =
+            edit_spawn_region(regions_text, "Camp", Some(&camp)).expect("region should be added");
+        assert_eq!(
+            parse_spawn_regions(&edited).expect("edited regions should parse")[1],
+            camp
+        );
+        let removed = edit_spawn_region(&edited, "Camp", None).expect("region should be removed");
+        assert_eq!(
+            parse_spawn_regions(&removed).expect("edited regions should parse"),
+            parse_spawn_regions(regions_text).expect("regions should parse")
+        );
+    }
+
+    #[test]
+    fn generates_and_validates_regions_from_the_map_list() {
+        let root = TestDir::new("pz-spawn-files-test");
+        let write = |relative: &str| root.write(relative, "function SpawnPoints() return {} end");
+        write("Town/mod.info");
+        write("Town/media/maps/Town/spawnpoints.lua");
+        write("Bare/mod.info");
+        write("Bare/media/maps/Bare/0_0.lotheader");
+        let mods: Vec<LoadoutMod> = ["Town", "Bare"]
+            .iter()
+            .map(|id| LoadoutMod {
+                mod_id: Some(id.to_string()),
+                mod_info_path: Some(root.join(id).join("mod.info").to_string_lossy().to_string()),
+                ..LoadoutMod::default()
+            })
+            .collect();
+        let maps: Vec<String> = ["Town", "Bare", "Missing", VANILLA_WORLD_MAP]
+            .iter()
+            .map(|map| map.to_string())
+            .collect();
+
+        let plan = plan_regions(&maps, &mods);
+        assert_eq!(plan.regions.len(), 1 + VANILLA_MAPS.len());
+        assert_eq!(plan.regions[0].name, "Town");
+        assert_eq!(plan.warnings.len(), 2);
+
+        let mut regions = plan.regions[..2].to_vec();
+        regions.push(SpawnRegion {
+            name: "Gone".to_string(),
+            file: Some("media/maps/Gone/spawnpoints.lua".to_string()),
+            server_file: None,
+        });
+        regions.push(SpawnRegion {
+            name: "Custom".to_string(),
+            file: None,
+            server_file: Some("custom_spawnpoints.lua".to_string()),
+        });
+        let issues = check_regions(&regions, &maps[..1], &mods, None, &root);
+        let flagged: Vec<&str> = issues.iter().map(|issue| issue.region.as_str()).collect();
+        assert_eq!(flagged, ["Brandenburg, KY", "Gone", "Gone", "Custom"]);
+    }
+}
@@ -0,0 +1,425 @@
+use crate::mod_scanner::scan_mod_folder_cached;
+use crate::models::ModSummary;
+use crate::pz_compat::WORKSHOP_APP_ID;
+use crate::timing::scoped_timer;
+use crate::utils::to_iso_string;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+/// A node of Valve's KeyValues text format, as used by `.acf` and `.vdf` files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum VdfValue {
+    String(String),
+    Object(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Looks up a child by key; Steam does not keep key case consistent.
+    pub(crate) fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            VdfValue::String(_) => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(value) => Some(value),
+            VdfValue::Object(_) => None,
+        }
+    }
+
+    pub(crate) fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Object(entries) => entries,
+            VdfValue::String(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum VdfToken {
+    Text(String),
+    Open,
+    Close,
+}
+
+fn tokenize_vdf(text: &str) -> Result<Vec<VdfToken>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(VdfToken::Open),
+            '}' => tokens.push(VdfToken::Close),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(other) => value.push(other),
+                            None => return Err("Unterminated escape in VDF string.".to_string()),
+                        },
+                        Some(other) => value.push(other),
+                        None => return Err("Unterminated VDF string.".to_string()),
+                    }
+                }
+                tokens.push(VdfToken::Text(value));
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            // Platform conditionals such as `[$WIN32]` do not matter here.
+            '[' => {
+                for next in chars.by_ref() {
+                    if next == ']' {
+                        break;
+                    }
+                }
+            }
+            _ if c.is_whitespace() => {}
+            _ => {
+                let mut value = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '{' | '}' | '"') {
+                        break;
+                    }
+                    value.push(next);
+                    chars.next();
+                }
+                tokens.push(VdfToken::Text(value));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_vdf_object(
+    tokens: &mut std::vec::IntoIter<VdfToken>,
+    nested: bool,
+) -> Result<Vec<(String, VdfValue)>, String> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match tokens.next() {
+            Some(VdfToken::Text(key)) => key,
+            Some(VdfToken::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(VdfToken::Close) => return Err("Unexpected '}' in VDF.".to_string()),
+            Some(VdfToken::Open) => return Err("Expected a key before '{' in VDF.".to_string()),
+            None => return Err("Missing closing '}' in VDF.".to_string()),
+        };
+        let value = match tokens.next() {
+            Some(VdfToken::Text(value)) => VdfValue::String(value),
+            Some(VdfToken::Open) => VdfValue::Object(parse_vdf_object(tokens, true)?),
+            _ => return Err(format!("Missing value for VDF key \"{key}\".")),
+        };
+        entries.push((key, value));
+    }
+}
+
+/// Parses a KeyValues document into its top-level object.
+pub(crate) fn parse_vdf(text: &str) -> Result<VdfValue, String> {
+    let mut tokens = tokenize_vdf(text)?.into_iter();
+    Ok(VdfValue::Object(parse_vdf_object(&mut tokens, false)?))
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopManifestItem {
+    pub workshop_id: String,
+    pub size: Option<u64>,
+    pub time_updated: Option<String>,
+    /// Listed under `WorkshopItemsInstalled`.
+    pub installed: bool,
+    /// Steam has a newer manifest than the one on disk.
+    pub needs_update: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanWorkshopFolder {
+    pub workshop_id: String,
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopManifestReport {
+    pub manifest_path: String,
+    pub items: Vec<WorkshopManifestItem>,
+    /// Items Steam lists that have no folder in the workshop directory.
+    pub missing_workshop_ids: Vec<String>,
+    /// Workshop folders Steam no longer tracks; deleting them is safe.
+    pub orphans: Vec<OrphanWorkshopFolder>,
+    /// The scanned mods with `install_date` taken from Steam's update time.
+    pub summaries: Vec<ModSummary>,
+}
+
+fn unix_seconds_to_iso(raw: &str) -> Option<String> {
+    let seconds: u64 = raw.trim().parse().ok().filter(|seconds| *seconds > 0)?;
+    to_iso_string(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Reads the per-item entries of an `appworkshop_<appid>.acf` manifest.
+pub(crate) fn parse_workshop_manifest(text: &str) -> Result<Vec<WorkshopManifestItem>, String> {
+    let document = parse_vdf(text)?;
+    let root = document
+        .get("AppWorkshop")
+        .ok_or_else(|| "The workshop manifest has no AppWorkshop section.".to_string())?;
+    let installed = root.get("WorkshopItemsInstalled");
+    let details = root.get("WorkshopItemDetails");
+
+    let ids: BTreeSet<&str> = installed
+        .into_iter()
+        .chain(details)
+        .flat_map(|section| section.entries().iter().map(|(id, _)| id.as_str()))
+        .collect();
+    let field = |section: Option<&VdfValue>, id: &str, key: &str| -> Option<String> {
+        section?.get(id)?.get(key)?.as_str().map(str::to_string)
+    };
+    Ok(ids
+        .into_iter()
+        .map(|id| {
+            let manifest =
+                field(installed, id, "manifest").or_else(|| field(details, id, "manifest"));
+            let needs_update = match (manifest, field(details, id, "latest_manifest")) {
+                (Some(current), Some(latest)) => current != latest,
+                _ => false,
+            };
+            WorkshopManifestItem {
+                workshop_id: id.to_string(),
+                size: field(installed, id, "size").and_then(|size| size.parse().ok()),
+                time_updated: field(installed, id, "timeupdated")
+                    .or_else(|| field(details, id, "timeupdated"))
+                    .and_then(|raw| unix_seconds_to_iso(&raw)),
+                installed: installed.and_then(|section| section.get(id)).is_some(),
+                needs_update,
+            }
+        })
+        .collect())
+}
+
+/// Finds `steamapps/workshop/appworkshop_108600.acf` from the validated
+/// `steamapps/workshop/content/108600` folder.
+pub(crate) fn workshop_manifest_path(workshop_path: &Path) -> Option<PathBuf> {
+    let file_name = format!("appworkshop_{WORKSHOP_APP_ID}.acf");
+    workshop_path
+        .ancestors()
+        .take(4)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The manifest entries of the workshop folder at `workshop_path` by workshop
+/// id; empty when Steam's manifest is missing or unreadable.
+pub(crate) fn manifest_items_by_id(workshop_path: &Path) -> BTreeMap<String, WorkshopManifestItem> {
+    workshop_manifest_path(workshop_path)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| parse_workshop_manifest(&String::from_utf8_lossy(&bytes)).ok())
+        .map(|items| {
+            items
+                .into_iter()
+                .map(|item| (item.workshop_id.clone(), item))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn folder_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn workshop_folders(workshop_path: &Path) -> BTreeMap<String, PathBuf> {
+    fs::read_dir(workshop_path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    (!name.is_empty() && name.chars().all(|c| c.is_ascii_digit()))
+                        .then(|| (name, entry.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn correlate_workshop_manifest(
+    manifest_path: &Path,
+    items: Vec<WorkshopManifestItem>,
+    workshop_path: &Path,
+    mut summaries: Vec<ModSummary>,
+) -> WorkshopManifestReport {
+    let mut folders = workshop_folders(workshop_path);
+    let mut missing_workshop_ids = Vec::new();
+    for item in &items {
+        if folders.remove(&item.workshop_id).is_none() {
+            missing_workshop_ids.push(item.workshop_id.clone());
+        }
+    }
+    let orphans = folders
+        .into_iter()
+        .map(|(workshop_id, path)| OrphanWorkshopFolder {
+            size: folder_size(&path),
+            workshop_id,
+            path: path.to_string_lossy().to_string(),
+        })
+        .collect();
+
+    let updated: BTreeMap<&str, &str> = items
+        .iter()
+        .filter_map(|item| Some((item.workshop_id.as_str(), item.time_updated.as_deref()?)))
+        .collect();
+    for summary in &mut summaries {
+        if let Some(time) = summary
+            .workshop_id
+            .as_deref()
+            .and_then(|id| updated.get(id.trim()))
+        {
+            summary.install_date = Some(time.to_string());
+        }
+    }
+
+    WorkshopManifestReport {
+        manifest_path: manifest_path.to_string_lossy().to_string(),
+        items,
+        missing_workshop_ids,
+        orphans,
+        summaries,
+    }
+}
+
+#[tauri::command]
+pub fn read_workshop_manifest(
+    app: AppHandle,
+    path: String,
+) -> Result<WorkshopManifestReport, String> {
+    let _timer = scoped_timer("read_workshop_manifest");
+    let workshop_path = Path::new(&path);
+    let manifest_path = workshop_manifest_path(workshop_path).ok_or_else(|| {
+        format!("Could not find appworkshop_{WORKSHOP_APP_ID}.acf next to {path}.")
+    })?;
+    let text = fs::read(&manifest_path).map_err(|e| e.to_string())?;
+    let items = parse_workshop_manifest(&String::from_utf8_lossy(&text))?;
+    let scan = scan_mod_folder_cached(&app, &path)?;
+    Ok(correlate_workshop_manifest(
+        &manifest_path,
+        items,
+        workshop_path,
+        scan.summaries,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    const MANIFEST: &str = r#"
+"AppWorkshop"
+{
+	"appid"		"108600"
+	"SizeOnDisk"		"3000"
+	"WorkshopItemsInstalled"
+	{
+		"100"
+		{
+			"size"		"1000"
+			"timeupdated"		"1700000000"
+			"manifest"		"11"
+		}
+		"200"
+		{
+			"size"		"2000"
+			"timeupdated"		"1710000000"
+			"manifest"		"22"
+		}
+	}
+	"WorkshopItemDetails"
+	{
+		"100"
+		{
+			"manifest"		"11"
+			"timeupdated"		"1700000000"
+			"latest_manifest"		"12"
+		}
+		"300"
+		{
+			"manifest"		"33"
+			"timeupdated"		"1720000000" // not downloaded yet
+		}
+	}
+}
+"#;
+
+    #[test]
+    fn parses_workshop_manifest_items() {
+        let items = parse_workshop_manifest(MANIFEST).expect("manifest should parse");
+        let ids: Vec<&str> = items.iter().map(|item| item.workshop_id.as_str()).collect();
+        assert_eq!(ids, ["100", "200", "300"]);
+        assert_eq!(items[0].size, Some(1000));
+        assert!(items[0].installed && items[0].needs_update);
+        assert!(!items[1].needs_update);
+        assert!(!items[2].installed);
+        assert_eq!(
+            items[0].time_updated.as_deref(),
+            Some("2023-11-14T22:13:20+00:00")
+        );
+        assert!(parse_vdf("\"a\" { \"b\" \"c\"").is_err());
+    }
+
+    #[test]
+    fn correlates_manifest_with_workshop_folders() {
+        let root = TestDir::new("pz-steam-workshop-test");
+        let workshop = root.join("content").join(WORKSHOP_APP_ID);
+        for id in ["100", "200", "900"] {
+            let dir = workshop.join(id).join("mods").join("Example");
+            fs::create_dir_all(&dir).expect("item directory should be created");
+            fs::write(dir.join("mod.info"), "id=Example").expect("mod.info should be written");
+        }
+        let manifest_path = root.join(format!("appworkshop_{WORKSHOP_APP_ID}.acf"));
+        fs::write(&manifest_path, MANIFEST).expect("manifest should be written");
+        assert_eq!(
+            workshop_manifest_path(&workshop).as_deref(),
+            Some(manifest_path.as_path())
+        );
+
+        let summaries = vec![ModSummary {
+            workshop_id: Some("200".to_string()),
+            install_date: Some("2000-01-01T00:00:00+00:00".to_string()),
+            ..ModSummary::default()
+        }];
+        let items = parse_workshop_manifest(MANIFEST).expect("manifest should parse");
+        let report = correlate_workshop_manifest(&manifest_path, items, &workshop, summaries);
+        assert_eq!(report.missing_workshop_ids, ["300"]);
+        assert_eq!(report.orphans.len(), 1);
+        assert_eq!(report.orphans[0].workshop_id, "900");
+        assert_eq!(report.orphans[0].size, "id=Example".len() as u64);
+        assert_eq!(
+            report.summaries[0].install_date.as_deref(),
+            Some("2024-03-09T16:00:00+00:00")
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 use std::fs;
 use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::SystemTime;
+use walkdir::WalkDir;
 
 pub(crate) fn to_iso_string(time: SystemTime) -> Option<String> {
     let dt: DateTime<Utc> = time.into();
@@ -27,6 +29,57 @@ pub(crate) fn ensure_parent_dir(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+/// Writes through a sibling temporary file and a rename, so readers never see
+/// a half-written file.  The temporary name is unique per process and call, so
+/// concurrent writers of the same file never share one.
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), String> {
+    static NEXT_TEMP: AtomicUsize = AtomicUsize::new(0);
+    ensure_parent_dir(path)?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(
+        ".{file_name}.{}.{}.tmp",
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp_path, contents).map_err(|e| e.to_string())?;
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        e.to_string()
+    })
+}
+
+pub(crate) fn copy_directory(source: &Path, destination: &Path) -> Result<(), String> {
+    for entry in WalkDir::new(source).follow_links(false) {
+        let entry = entry.map_err(|error| error.to_string())?;
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|error| error.to_string())?;
+        let target = destination.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).map_err(|error| error.to_string())?;
+        } else if entry.file_type().is_file() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+            }
+            fs::copy(entry.path(), &target).map_err(|error| error.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a text file, or `None` when it does not exist.
+pub(crate) fn read_optional_text(path: &Path) -> Result<Option<String>, String> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(format!("{}: {error}", path.display())),
+    }
+}
+
 pub(crate) fn safe_relative_path(base: &Path, relative: &str) -> Result<PathBuf, String> {
     let trimmed = relative.trim();
     if trimmed.is_empty() {
@@ -49,3 +102,56 @@ pub(crate) fn safe_relative_path(base: &Path, relative: &str) -> Result<PathBuf,
 
     Ok(base.join(relative_path))
 }
+
+/// A scratch folder under the system temp dir for tests, removed on drop so a
+/// failing assertion does not leak it.
+#[cfg(test)]
+pub(crate) struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+    pub(crate) fn new(prefix: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "{prefix}-{nanos}-{}",
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).expect("test directory should be created");
+        Self(path)
+    }
+
+    /// Writes `contents` to `relative`, creating its folders.
+    pub(crate) fn write(&self, relative: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(relative);
+        ensure_parent_dir(&path).expect("test folder should be created");
+        fs::write(&path, contents).expect("test file should be written");
+        path
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,550 @@
+use crate::mod_scanner::{is_layout_folder, validate_pz_workshop_path};
+use crate::steam_workshop::{WorkshopManifestItem, manifest_items_by_id};
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, State};
+use walkdir::WalkDir;
+
+pub const WORKSHOP_CHANGED_EVENT: &str = "workshop-items-changed";
+/// Used only when the folder cannot be watched for filesystem events, such as
+/// on some network shares or past the system's watch limit.
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+/// Steam writes an update over several seconds; wait until the folder has
+/// been quiet for this long before reporting it.
+const SETTLE_DURATION: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopChanges {
+    pub path: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Items whose `mod.info` files were edited, added or deleted.
+    pub mod_info_changed: Vec<String>,
+    /// Items with other files changed while their `mod.info` files did not.
+    pub content_changed: Vec<String>,
+}
+
+impl WorkshopChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.mod_info_changed.is_empty()
+            && self.content_changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopWatchStatus {
+    pub watching: bool,
+    pub path: Option<String>,
+}
+
+type FileStamp = (u64, Option<SystemTime>);
+type ItemFiles = BTreeMap<PathBuf, FileStamp>;
+
+/// What can be checked cheaply of an item: its folder, its mod folders with
+/// their `common/` and version folders, every `mod.info` and Steam's manifest
+/// entry for the item.  Steam records each finished update in the manifest,
+/// so a poll only reads file contents once an item changes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct ItemStamp {
+    mod_info: ItemFiles,
+    folders: BTreeMap<PathBuf, Option<SystemTime>>,
+    manifest: Option<(Option<u64>, Option<String>)>,
+}
+
+type WorkshopSnapshot = BTreeMap<String, ItemStamp>;
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn stamp_folder(stamp: &mut ItemStamp, dir: &Path) {
+    stamp.folders.insert(dir.to_path_buf(), modified(dir));
+    let info_path = dir.join("mod.info");
+    if let Ok(metadata) = fs::metadata(&info_path) {
+        stamp
+            .mod_info
+            .insert(info_path, (metadata.len(), metadata.modified().ok()));
+    }
+}
+
+fn item_stamp(item_dir: &Path, manifest: Option<&WorkshopManifestItem>) -> ItemStamp {
+    let mut stamp = ItemStamp {
+        manifest: manifest.map(|item| (item.size, item.time_updated.clone())),
+        ..ItemStamp::default()
+    };
+    stamp
+        .folders
+        .insert(item_dir.to_path_buf(), modified(item_dir));
+    let mods_dir = item_dir.join("mods");
+    stamp.folders.insert(mods_dir.clone(), modified(&mods_dir));
+    for mod_dir in subdirs(&mods_dir) {
+        stamp_folder(&mut stamp, &mod_dir);
+        for layout_dir in subdirs(&mod_dir) {
+            if layout_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_layout_folder)
+            {
+                stamp_folder(&mut stamp, &layout_dir);
+            }
+        }
+    }
+    stamp
+}
+
+/// Stats every file of one item; only done for items whose stamp moved.
+fn item_files(item_dir: &Path) -> ItemFiles {
+    WalkDir::new(item_dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((
+                entry.path().to_path_buf(),
+                (metadata.len(), metadata.modified().ok()),
+            ))
+        })
+        .collect()
+}
+
+fn is_item_folder_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Stamps every numeric workshop item folder under `root`.
+fn snapshot_workshop(root: &Path) -> WorkshopSnapshot {
+    let manifest = manifest_items_by_id(root);
+    let item_dirs: Vec<(String, PathBuf)> = fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    is_item_folder_name(&name).then(|| (name, entry.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    item_dirs
+        .into_iter()
+        .map(|(workshop_id, dir)| {
+            let stamp = item_stamp(&dir, manifest.get(&workshop_id));
+            (workshop_id, stamp)
+        })
+        .collect()
+}
+
+/// The workshop item a changed path belongs to.
+fn item_of(root: &Path, path: &Path) -> Option<String> {
+    let name = path.strip_prefix(root).ok()?.components().next()?;
+    let name = name.as_os_str().to_str()?;
+    is_item_folder_name(name).then(|| name.to_string())
+}
+
+fn diff_snapshots(
+    path: &str,
+    before: &WorkshopSnapshot,
+    after: &WorkshopSnapshot,
+) -> WorkshopChanges {
+    let mut changes = WorkshopChanges {
+        path: path.to_string(),
+        ..WorkshopChanges::default()
+    };
+    let ids: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    for id in ids {
+        match (before.get(id), after.get(id)) {
+            (None, Some(_)) => changes.added.push(id.clone()),
+            (Some(_), None) => changes.removed.push(id.clone()),
+            (Some(old), Some(new)) if old.mod_info != new.mod_info => {
+                changes.mod_info_changed.push(id.clone())
+            }
+            (Some(old), Some(new)) if old != new => changes.content_changed.push(id.clone()),
+            _ => {}
+        }
+    }
+    changes
+}
+
+struct Pending {
+    snapshot: WorkshopSnapshot,
+    /// Every file of the items that differ from the baseline, so a download
+    /// still in progress keeps the change pending.
+    files: BTreeMap<String, ItemFiles>,
+    since: Instant,
+}
+
+/// Holds back a change until the folder stops moving for `SETTLE_DURATION`.
+struct Debouncer {
+    path: String,
+    baseline: WorkshopSnapshot,
+    pending: Option<Pending>,
+}
+
+impl Debouncer {
+    fn new(path: &str, baseline: WorkshopSnapshot) -> Self {
+        Self {
+            path: path.to_string(),
+            baseline,
+            pending: None,
+        }
+    }
+
+    fn changed_item_files(&self, current: &WorkshopSnapshot) -> BTreeMap<String, ItemFiles> {
+        current
+            .iter()
+            .filter(|(id, stamp)| self.baseline.get(*id) != Some(*stamp))
+            .map(|(id, _)| (id.clone(), item_files(&Path::new(&self.path).join(id))))
+            .collect()
+    }
+
+    fn observe(&mut self, current: WorkshopSnapshot, now: Instant) -> Option<WorkshopChanges> {
+        if current == self.baseline {
+            self.pending = None;
+            return None;
+        }
+        let files = self.changed_item_files(&current);
+        match &self.pending {
+            Some(pending) if pending.snapshot == current && pending.files == files => {
+                if now.duration_since(pending.since) < SETTLE_DURATION {
+                    return None;
+                }
+                let changes = diff_snapshots(&self.path, &self.baseline, &current);
+                self.baseline = current;
+                self.pending = None;
+                (!changes.is_empty()).then_some(changes)
+            }
+            _ => {
+                self.pending = Some(Pending {
+                    snapshot: current,
+                    files,
+                    since: now,
+                });
+                None
+            }
+        }
+    }
+}
+
+/// Collects the items filesystem events touch until no event has arrived for
+/// `SETTLE_DURATION`.
+#[derive(Default)]
+struct EventDebouncer {
+    items: BTreeSet<String>,
+    last_event: Option<Instant>,
+}
+
+impl EventDebouncer {
+    fn record(&mut self, items: impl IntoIterator<Item = String>, now: Instant) {
+        let mut touched = false;
+        for item in items {
+            self.items.insert(item);
+            touched = true;
+        }
+        if touched {
+            self.last_event = Some(now);
+        }
+    }
+
+    /// How long to wait for further events before the items settle.
+    fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.last_event
+            .map(|at| SETTLE_DURATION.saturating_sub(now.duration_since(at)))
+    }
+
+    fn settled(&mut self, now: Instant) -> Option<BTreeSet<String>> {
+        match self.last_event {
+            Some(at) if now.duration_since(at) >= SETTLE_DURATION => {
+                self.last_event = None;
+                Some(std::mem::take(&mut self.items))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Compares the items events touched with `baseline` and moves it on.  An
+/// item whose stamp did not move still had some other file change.
+fn classify_items(
+    root: &Path,
+    baseline: &mut WorkshopSnapshot,
+    items: &BTreeSet<String>,
+) -> WorkshopChanges {
+    let manifest = manifest_items_by_id(root);
+    let mut changes = WorkshopChanges {
+        path: root.to_string_lossy().to_string(),
+        ..WorkshopChanges::default()
+    };
+    for id in items {
+        let dir = root.join(id);
+        let current = dir.is_dir().then(|| item_stamp(&dir, manifest.get(id)));
+        match (baseline.remove(id), current) {
+            (None, Some(stamp)) => {
+                changes.added.push(id.clone());
+                baseline.insert(id.clone(), stamp);
+            }
+            (Some(_), None) => changes.removed.push(id.clone()),
+            (Some(old), Some(stamp)) => {
+                if old.mod_info != stamp.mod_info {
+                    changes.mod_info_changed.push(id.clone());
+                } else {
+                    changes.content_changed.push(id.clone());
+                }
+                baseline.insert(id.clone(), stamp);
+            }
+            (None, None) => {}
+        }
+    }
+    changes
+}
+
+enum WatchMessage {
+    Stop,
+    Changed(Vec<PathBuf>),
+}
+
+struct ActiveWatch {
+    path: String,
+    messages: Sender<WatchMessage>,
+}
+
+#[derive(Default)]
+pub struct WorkshopWatcher {
+    active: Arc<Mutex<Option<ActiveWatch>>>,
+}
+
+impl WorkshopWatcher {
+    fn status(&self) -> WorkshopWatchStatus {
+        let active = self.active.lock().expect("workshop watcher lock poisoned");
+        WorkshopWatchStatus {
+            watching: active.is_some(),
+            path: active.as_ref().map(|watch| watch.path.clone()),
+        }
+    }
+
+    fn stop(&self) {
+        let mut active = self.active.lock().expect("workshop watcher lock poisoned");
+        if let Some(watch) = active.take() {
+            let _ = watch.messages.send(WatchMessage::Stop);
+        }
+    }
+}
+
+fn emit_changes(app: &AppHandle, changes: WorkshopChanges) {
+    if !changes.is_empty() {
+        let _ = app.emit(WORKSHOP_CHANGED_EVENT, changes);
+    }
+}
+
+fn watch_events(app: &AppHandle, root: &Path, messages: &Receiver<WatchMessage>) {
+    let mut baseline = snapshot_workshop(root);
+    let mut debouncer = EventDebouncer::default();
+    loop {
+        let message = match debouncer.timeout(Instant::now()) {
+            Some(timeout) => messages.recv_timeout(timeout),
+            None => messages.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match message {
+            Ok(WatchMessage::Changed(paths)) => debouncer.record(
+                paths.iter().filter_map(|path| item_of(root, path)),
+                Instant::now(),
+            ),
+            Err(RecvTimeoutError::Timeout) => {}
+            Ok(WatchMessage::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if let Some(items) = debouncer.settled(Instant::now()) {
+            emit_changes(app, classify_items(root, &mut baseline, &items));
+        }
+    }
+}
+
+fn poll(app: &AppHandle, root: &Path, messages: &Receiver<WatchMessage>) {
+    let mut debouncer = Debouncer::new(&root.to_string_lossy(), snapshot_workshop(root));
+    loop {
+        match messages.recv_timeout(POLL_INTERVAL) {
+            Err(RecvTimeoutError::Timeout) | Ok(WatchMessage::Changed(_)) => {}
+            Ok(WatchMessage::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if let Some(changes) = debouncer.observe(snapshot_workshop(root), Instant::now()) {
+            emit_changes(app, changes);
+        }
+    }
+}
+
+fn watch_loop(
+    app: AppHandle,
+    path: String,
+    sender: Sender<WatchMessage>,
+    messages: Receiver<WatchMessage>,
+) {
+    let root = PathBuf::from(&path);
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = sender.send(WatchMessage::Changed(event.paths));
+        }
+    })
+    .and_then(|mut watcher| {
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(_watcher) => watch_events(&app, &root, &messages),
+        Err(_) => poll(&app, &root, &messages),
+    }
+}
+
+#[tauri::command]
+pub fn start_workshop_watch(
+    app: AppHandle,
+    watcher: State<'_, WorkshopWatcher>,
+    path: String,
+) -> Result<WorkshopWatchStatus, String> {
+    if !validate_pz_workshop_path(path.clone())? {
+        return Err(format!("{path} is not a Project Zomboid workshop folder."));
+    }
+    watcher.stop();
+    let (messages, receiver) = mpsc::channel();
+    let watched_path = path.clone();
+    let sender = messages.clone();
+    thread::spawn(move || watch_loop(app, watched_path, sender, receiver));
+    *watcher
+        .active
+        .lock()
+        .expect("workshop watcher lock poisoned") = Some(ActiveWatch { path, messages });
+    Ok(watcher.status())
+}
+
+#[tauri::command]
+pub fn stop_workshop_watch(watcher: State<'_, WorkshopWatcher>) -> WorkshopWatchStatus {
+    watcher.stop();
+    watcher.status()
+}
+
+#[tauri::command]
+pub fn get_workshop_watch_status(watcher: State<'_, WorkshopWatcher>) -> WorkshopWatchStatus {
+    watcher.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn reports_settled_changes_per_workshop_item() {
+        let root = TestDir::new("pz-workshop-watcher-test");
+        let manifest = |time_updated: u64| {
+            format!(
+                "\"AppWorkshop\"\n{{\n\t\"WorkshopItemsInstalled\"\n\t{{\n\t\t\"100\"\n\t\t{{\n\t\t\t\"size\"\t\t\"8\"\n\t\t\t\"timeupdated\"\t\t\"{time_updated}\"\n\t\t}}\n\t}}\n}}\n"
+            )
+        };
+        root.write("appworkshop_108600.acf", manifest(1_700_000_000));
+        root.write("100/mods/A/mod.info", "id=A");
+        root.write("100/mods/A/media/lua/shared/a.lua", "return 1");
+        root.write("200/mods/B/42/mod.info", "id=B");
+        root.write("300/mods/C/mod.info", "id=C");
+
+        let path = root.to_string_lossy().to_string();
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(&path, snapshot_workshop(&root));
+
+        root.write("100/mods/A/media/lua/shared/a.lua", "return 12");
+        root.write("appworkshop_108600.acf", manifest(1_700_000_100));
+        root.write("200/mods/B/42/mod.info", "id=B\nname=B");
+        fs::remove_dir_all(root.join("300")).expect("item should be removed");
+        root.write("400/mods/D/mod.info", "id=D");
+
+        assert_eq!(debouncer.observe(snapshot_workshop(&root), start), None);
+        root.write("100/mods/A/media/lua/shared/a.lua", "return 123");
+        assert_eq!(
+            debouncer.observe(snapshot_workshop(&root), start + SETTLE_DURATION),
+            None
+        );
+        let changes = debouncer
+            .observe(snapshot_workshop(&root), start + SETTLE_DURATION * 2)
+            .expect("settled changes should be reported");
+        assert_eq!(changes.added, vec!["400".to_string()]);
+        assert_eq!(changes.removed, vec!["300".to_string()]);
+        assert_eq!(changes.mod_info_changed, vec!["200".to_string()]);
+        assert_eq!(changes.content_changed, vec!["100".to_string()]);
+        assert_eq!(
+            debouncer.observe(snapshot_workshop(&root), start + SETTLE_DURATION * 3),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_items_touched_by_filesystem_events_once_they_settle() {
+        let root = TestDir::new("pz-workshop-events-test");
+        root.write("100/mods/A/mod.info", "id=A");
+        root.write("100/mods/A/media/lua/shared/a.lua", "return 1");
+        root.write("200/mods/B/mod.info", "id=B");
+        let mut baseline = snapshot_workshop(&root);
+
+        root.write("100/mods/A/media/lua/shared/a.lua", "return 12");
+        root.write("200/mods/B/mod.info", "id=B\nname=B");
+        root.write("300/mods/C/mod.info", "id=C");
+        let start = Instant::now();
+        let mut debouncer = EventDebouncer::default();
+        debouncer.record(
+            [
+                root.join("100/mods/A/media/lua/shared/a.lua"),
+                root.join("200/mods/B/mod.info"),
+                root.join("appworkshop_108600.acf"),
+            ]
+            .iter()
+            .filter_map(|path| item_of(&root, path)),
+            start,
+        );
+        debouncer.record(
+            item_of(&root, &root.join("300")),
+            start + SETTLE_DURATION / 2,
+        );
+        assert_eq!(
+            debouncer.timeout(start + SETTLE_DURATION / 2),
+            Some(SETTLE_DURATION)
+        );
+        assert_eq!(debouncer.settled(start + SETTLE_DURATION), None);
+
+        let items = debouncer
+            .settled(start + SETTLE_DURATION * 2)
+            .expect("quiet items should settle");
+        let changes = classify_items(&root, &mut baseline, &items);
+        assert_eq!(changes.added, ["300"]);
+        assert_eq!(changes.mod_info_changed, ["200"]);
+        assert_eq!(changes.content_changed, ["100"]);
+        assert!(changes.removed.is_empty());
+        assert_eq!(debouncer.timeout(start + SETTLE_DURATION * 2), None);
+
+        fs::remove_dir_all(root.join("300")).expect("item should be removed");
+        let removed = classify_items(&root, &mut baseline, &BTreeSet::from(["300".to_string()]));
+        assert_eq!(removed.removed, ["300"]);
+    }
+}
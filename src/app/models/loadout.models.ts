@@ -1,4 +1,8 @@
-import type { ModSummary } from './mod.models';
+import type {
+  ModContentProfile,
+  ModSummary,
+  ModVersionCompatibility,
+} from './mod.models';
 
 export type LoadoutTargetMode =
   | 'singleplayer'
@@ -30,6 +34,8 @@ export interface LoadoutResolvedMod {
   loadAfter?: string[] | null;
   loadBefore?: string[] | null;
   incompatible?: string[] | null;
+  compatibility?: ModVersionCompatibility | null;
+  worldmap?: string | null;
 }
 
 export interface LoadoutConflictGroup {
@@ -38,23 +44,57 @@ export interface LoadoutConflictGroup {
     modId: string;
     name?: string | null;
     modInfoPath?: string | null;
+    filePath?: string;
+    size?: number;
   }>;
+  winnerModId?: string;
+  identical?: boolean;
+}
+
+export interface LoadoutMissingDependency {
+  modId: string;
+  requiredBy: string[];
+  workshopIds: string[];
+}
+
+export interface LoadoutModContent {
+  modId?: string | null;
+  name?: string | null;
+  workshopId?: string | null;
+  profile: ModContentProfile;
+}
+
+export interface LoadoutContentProfile {
+  mods: LoadoutModContent[];
+  total: ModContentProfile;
+  download: ModContentProfile;
 }
 
 export interface LoadoutAnalysis {
   orderedModIds: string[];
   missingModIds: string[];
   missingWorkshopIds: string[];
+  missingDependencies?: LoadoutMissingDependency[];
   cycles: string[][];
   incompatiblePairs: Array<{ a: string; b: string }>;
   conflicts: LoadoutConflictGroup[];
   warnings: string[];
 }
 
+export interface IniChange {
+  key: string;
+  before?: string | null;
+  after?: string | null;
+}
+
 export interface LoadoutApplyPlan {
   presetName: string;
   targetPath: string;
   iniPreview: string;
+  exists?: boolean;
+  changes?: IniChange[];
+  map?: string | null;
+  mapWarnings?: string[];
 }
 
 export function modsToResolvedMods(
@@ -84,6 +124,8 @@ export function modsToResolvedMods(
       loadAfter: found?.load_after ?? null,
       loadBefore: found?.load_before ?? null,
       incompatible: found?.incompatible ?? null,
+      compatibility: found?.compatibility ?? null,
+      worldmap: found?.worldmap ?? null,
     };
   });
 }
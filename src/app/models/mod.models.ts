@@ -12,6 +12,12 @@ export interface RequiredByInfo {
   name: string;
 }
 
+export type ModVersionCompatibility =
+  | 'compatible'
+  | 'too_old'
+  | 'requires_newer_build'
+  | 'unknown';
+
 export interface ModSummary {
   id: string;
   mod_id?: string | null;
@@ -43,9 +49,122 @@ export interface ModSummary {
   // Attached Workshop metadata (joined in-memory; persisted separately)
   workshop?: WorkshopMetadata | null;
   required_by?: RequiredByInfo[] | null;
+  version_folders?: string[] | null;
+  selected_version_folder?: string | null;
+  no_usable_folder?: boolean | null;
+  compatibility?: ModVersionCompatibility | null;
+  content_profile?: ModContentProfile | null;
+}
+
+export interface ContentUsage {
+  bytes: number;
+  files: number;
+}
+
+export interface ModContentProfile {
+  total: ContentUsage;
+  lua: ContentUsage;
+  scripts: ContentUsage;
+  textures: ContentUsage;
+  sounds: ContentUsage;
+  maps: ContentUsage;
+  models: ContentUsage;
+  tiledefs: ContentUsage;
+  other: ContentUsage;
+}
+
+export interface ModScanChange {
+  path: string;
+  mod_id?: string | null;
+  workshop_id?: string | null;
+}
+
+export interface ModScanChanges {
+  added: ModScanChange[];
+  changed: ModScanChange[];
+  removed: ModScanChange[];
+  unchanged: number;
 }
 
 export interface ModFolderScanResult {
   files: ModFileInfo[];
   summaries: ModSummary[];
+  changes?: ModScanChanges;
+}
+
+/** Payload of the `workshop-items-changed` event. */
+export interface WorkshopChanges {
+  path: string;
+  added: string[];
+  removed: string[];
+  modInfoChanged: string[];
+  contentChanged: string[];
+}
+
+export interface WorkshopWatchStatus {
+  watching: boolean;
+  path?: string | null;
+}
+
+export interface WorkshopManifestItem {
+  workshopId: string;
+  size?: number | null;
+  timeUpdated?: string | null;
+  installed: boolean;
+  needsUpdate: boolean;
+}
+
+export interface OrphanWorkshopFolder {
+  workshopId: string;
+  path: string;
+  size: number;
+}
+
+export interface WorkshopManifestReport {
+  manifestPath: string;
+  items: WorkshopManifestItem[];
+  missingWorkshopIds: string[];
+  orphans: OrphanWorkshopFolder[];
+  summaries: ModSummary[];
+}
+
+export interface ModInfoDiagnostic {
+  modInfoPath: string;
+  severity: 'error' | 'warning';
+  line?: number | null;
+  key?: string | null;
+  message: string;
+}
+
+export interface CellBounds {
+  minX: number;
+  minY: number;
+  maxX: number;
+  maxY: number;
+}
+
+export interface MapFolderInfo {
+  modId?: string | null;
+  mapName: string;
+  paths: string[];
+  title?: string | null;
+  lots: string[];
+  description?: string | null;
+  cellCount: number;
+  cellSize?: number | null;
+  bounds?: CellBounds | null;
+  error?: string | null;
+}
+
+export interface MapCellOverlap {
+  firstModId?: string | null;
+  firstMap: string;
+  secondModId?: string | null;
+  secondMap: string;
+  cells: [number, number][];
+}
+
+export interface MapModReport {
+  maps: MapFolderInfo[];
+  overlaps: MapCellOverlap[];
 }
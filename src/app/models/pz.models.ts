@@ -19,3 +19,254 @@ export const PROJECT_ZOMBOID = {
     'West Point, KY',
   ],
 } as const;
+
+export type SandboxValue =
+  | { type: 'number'; value: number }
+  | { type: 'boolean'; value: boolean }
+  | { type: 'string'; value: string }
+  | { type: 'table'; value: SandboxEntry[] };
+
+export interface SandboxEntry {
+  key: string;
+  value: SandboxValue;
+  comments: string[];
+  line: number;
+}
+
+export interface SandboxVarsFile {
+  path: string;
+  version?: number | null;
+  expectedVersion: number;
+  entries: SandboxEntry[];
+  warnings: string[];
+}
+
+export interface SandboxChange {
+  /** Dotted key path such as `ZombieLore.Speed`. */
+  path: string;
+  value: SandboxValue;
+}
+
+export interface SandboxDefaultDiff {
+  path: string;
+  value?: SandboxValue | null;
+  default?: SandboxValue | null;
+}
+
+export type SandboxOptionType = 'boolean' | 'integer' | 'double' | 'enum' | 'string';
+
+export interface SandboxOptionDef {
+  /** Dotted name, which is also the option's path in SandboxVars. */
+  name: string;
+  type: SandboxOptionType;
+  min?: number | null;
+  max?: number | null;
+  default?: SandboxValue | null;
+  numValues?: number | null;
+  page?: string | null;
+  translation?: string | null;
+  valueTranslation?: string | null;
+  modId?: string | null;
+  filePath: string;
+  line: number;
+}
+
+export interface SandboxOptionSchema {
+  options: SandboxOptionDef[];
+  warnings: string[];
+}
+
+export interface SandboxOptionIssue {
+  path: string;
+  modId?: string | null;
+  message: string;
+}
+
+export interface SpawnRegion {
+  name: string;
+  file?: string | null;
+  serverFile?: string | null;
+}
+
+export interface SpawnPoint {
+  worldX?: number | null;
+  worldY?: number | null;
+  posX?: number | null;
+  posY?: number | null;
+  posZ?: number | null;
+}
+
+export interface ProfessionSpawnPoints {
+  profession: string;
+  points: SpawnPoint[];
+}
+
+export interface ServerSpawnFiles {
+  regionsPath: string;
+  regions?: SpawnRegion[] | null;
+  pointsPath: string;
+  points?: ProfessionSpawnPoints[] | null;
+}
+
+export interface SpawnRegionPlan {
+  maps: string[];
+  regions: SpawnRegion[];
+  warnings: string[];
+}
+
+export interface SpawnRegionIssue {
+  region: string;
+  message: string;
+}
+
+export interface ServerTransferResult {
+  files: string[];
+  world?: string | null;
+  database?: string | null;
+}
+
+export type ServerConfigSection = 'ini' | 'sandboxVars' | 'spawnRegions' | 'spawnPoints';
+
+export interface ServerSettingDiff {
+  section: ServerConfigSection;
+  key: string;
+  left?: string | null;
+  right?: string | null;
+}
+
+export interface ServerListDiff {
+  onlyLeft: string[];
+  onlyRight: string[];
+  sameOrder: boolean;
+}
+
+export interface ServerComparison {
+  left: string;
+  right: string;
+  settings: ServerSettingDiff[];
+  mods: ServerListDiff;
+  workshopItems: ServerListDiff;
+  maps: ServerListDiff;
+}
+
+export interface ServerSettingKey {
+  section: ServerConfigSection;
+  key: string;
+}
+
+export type ServerProcessState = 'idle' | 'starting' | 'running' | 'stopping' | 'stopped' | 'crashed';
+
+export interface ServerProcessStatus {
+  serverName?: string | null;
+  state: ServerProcessState;
+  message: string;
+  pid?: number | null;
+  logs: string[];
+  droppedLogLines: number;
+  startedAtUnixMs?: number | null;
+  readyAtUnixMs?: number | null;
+  exitCode?: number | null;
+}
+
+export interface RconPlayers {
+  players: string[];
+  raw: string;
+}
+
+export interface ServerAccount {
+  id: number;
+  username: string;
+  accessLevel: string;
+  steamId?: string | null;
+  banned: boolean;
+  lastConnection?: string | null;
+}
+
+export type ServerBanKind = 'steamId' | 'ip';
+
+export interface ServerBan {
+  kind: ServerBanKind;
+  value: string;
+  username?: string | null;
+  reason?: string | null;
+}
+
+export interface ServerAccounts {
+  path: string;
+  accounts: ServerAccount[];
+  bans: ServerBan[];
+}
+
+export interface ServerAccountUpdate {
+  accessLevel?: string | null;
+  steamId?: string | null;
+  banned?: boolean | null;
+}
+
+export interface ServerBundleManifest {
+  format: string;
+  formatVersion: number;
+  gameVersion: string;
+  serverName: string;
+  exportedAt?: string | null;
+}
+
+export interface ServerBundleMod {
+  modId: string;
+  workshopId?: string | null;
+  name?: string | null;
+}
+
+export interface ServerBundleReport {
+  manifest: ServerBundleManifest;
+  files: string[];
+  mods: ServerBundleMod[];
+  missingMods: ServerBundleMod[];
+  warnings: string[];
+}
+
+export type ServerModIssueKind =
+  | 'modWithoutProvider'
+  | 'modWorkshopItemMissing'
+  | 'unknownWorkshopItem'
+  | 'unusedWorkshopItem'
+  | 'duplicateMod'
+  | 'duplicateWorkshopItem'
+  | 'missingDependency';
+
+export interface ServerModIssue {
+  kind: ServerModIssueKind;
+  id: string;
+  related: string[];
+  message: string;
+}
+
+export interface ServerModsReport {
+  mods: string[];
+  workshopItems: string[];
+  issues: ServerModIssue[];
+}
+
+export interface SaveModsFile {
+  path: string;
+  exists: boolean;
+  mods: string[];
+  maps: string[];
+}
+
+export interface ModlistHeader {
+  name: string;
+  value: string;
+}
+
+export interface ModlistPreset {
+  name: string;
+  modIds: string[];
+}
+
+export interface ModlistSettingsFile {
+  path: string;
+  exists: boolean;
+  headers: ModlistHeader[];
+  presets: ModlistPreset[];
+}
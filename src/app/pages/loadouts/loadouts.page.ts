@@ -23,7 +23,6 @@ import type { ModSummary } from '../../models/mod.models';
 import type {
   Loadout,
   LoadoutAnalysis,
-  LoadoutResolvedMod,
   LoadoutTargetMode,
 } from '../../models/loadout.models';
 import { modsToResolvedMods } from '../../models/loadout.models';
@@ -125,6 +124,7 @@ export class LoadoutsPageComponent implements OnInit {
 
   analysis: LoadoutAnalysis | null = null;
   analysisCycleDisplay: string[] = [];
+  analysisMissingDisplay: string[] = [];
   analysisConflictDisplay: Array<{ relativePath: string; modsDisplay: string }> = [];
   analyzing = false;
   confirmVisible = false;
@@ -140,6 +140,11 @@ export class LoadoutsPageComponent implements OnInit {
   exporting = false;
   exportingLoadout: Loadout | null = null;
   exportTargetPath = '';
+  exportMap: string | null = null;
+  exportMapWarnings: string[] = [];
+  /** The `Map=` value the export will overwrite, when it differs. */
+  exportMapReplaces: string | null = null;
+  exportWritten = false;
   userDirExample = '';
   deleteConfirmVisible = false;
   pendingDeleteLoadout: Loadout | null = null;
@@ -530,7 +535,10 @@ export class LoadoutsPageComponent implements OnInit {
     let analysis: LoadoutAnalysis;
     this.analyzing = true;
     try {
-      analysis = this.analyzeResolvedMods(resolvedMods);
+      analysis = await this.loadoutsApi.analyzeLoadout(
+        resolvedMods,
+        this.installedMods,
+      );
     } finally {
       this.analyzing = false;
     }
@@ -853,7 +861,9 @@ export class LoadoutsPageComponent implements OnInit {
         this.installedMods,
         this.draft.modIds,
       );
-      this.setAnalysis(this.analyzeResolvedMods(resolvedMods));
+      this.setAnalysis(
+        await this.loadoutsApi.analyzeLoadout(resolvedMods, this.installedMods),
+      );
     } finally {
       this.analyzing = false;
     }
@@ -918,6 +928,10 @@ export class LoadoutsPageComponent implements OnInit {
     this.exportPresetName = loadout.name.replace(/[^\w\- ]+/g, '').trim() || 'servertest';
     this.exportVisible = true;
     this.exportPlanText = '';
+    this.exportMap = null;
+    this.exportMapWarnings = [];
+    this.exportMapReplaces = null;
+    this.exportWritten = false;
 
     if (!this.exportZomboidUserDir) {
       const guess = await this.loadoutsApi.getDefaultZomboidUserDir();
@@ -931,21 +945,34 @@ export class LoadoutsPageComponent implements OnInit {
       this.exportPresetName,
       loadout.modIds,
       loadout.workshopIds,
+      modsToResolvedMods(this.installedMods, loadout.modIds),
     );
     this.exportPlanText = plan.iniPreview;
     this.exportTargetPath = plan.targetPath;
+    this.exportMap = plan.map ?? null;
+    this.exportMapWarnings = plan.mapWarnings ?? [];
+    const mapChange = (plan.changes ?? []).find(
+      (change) => change.key.toLowerCase() === 'map',
+    );
+    this.exportMapReplaces = mapChange?.before ?? null;
   }
 
   async writeExport(loadout: Loadout): Promise<void> {
     this.exporting = true;
     try {
-      await this.loadoutsApi.writeServerPreset(
+      const mapWarnings = await this.loadoutsApi.writeServerPreset(
         this.exportZomboidUserDir,
         this.exportPresetName,
         loadout.modIds,
         loadout.workshopIds,
+        modsToResolvedMods(this.installedMods, loadout.modIds),
       );
-      this.exportVisible = false;
+      // Keep the dialog open so missing map folders are not missed.
+      this.exportMapWarnings = mapWarnings;
+      this.exportWritten = true;
+      if (!mapWarnings.length) {
+        this.exportVisible = false;
+      }
     } finally {
       this.exporting = false;
     }
@@ -1043,6 +1070,9 @@ export class LoadoutsPageComponent implements OnInit {
     if (!l) {
       return '';
     }
+    if (this.exportMap) {
+      return `Map=${this.exportMap}`;
+    }
     const mapFolders = this.collectMapFolders(l);
     return mapFolders.length ? `Map=${mapFolders.join(';')}` : '';
   }
@@ -1055,172 +1085,6 @@ export class LoadoutsPageComponent implements OnInit {
     return JSON.stringify(l, null, 2);
   }
 
-  private analyzeResolvedMods(mods: LoadoutResolvedMod[]): LoadoutAnalysis {
-    const modIds = mods.map((m) => (m.modId ?? '').trim()).filter((id) => !!id);
-    const selected = new Set(modIds);
-    const byId = new Map<string, LoadoutResolvedMod>();
-    for (const mod of mods) {
-      const id = (mod.modId ?? '').trim();
-      if (id) {
-        byId.set(id, mod);
-      }
-    }
-
-    const edges = new Map<string, Set<string>>();
-    const indegree = new Map<string, number>();
-    const missingModIds = new Set<string>();
-
-    const ensureNode = (id: string): void => {
-      if (!edges.has(id)) {
-        edges.set(id, new Set<string>());
-      }
-      if (!indegree.has(id)) {
-        indegree.set(id, 0);
-      }
-    };
-
-    const addEdge = (from: string, to: string): void => {
-      ensureNode(from);
-      ensureNode(to);
-      const next = edges.get(from);
-      if (!next || next.has(to)) {
-        return;
-      }
-      next.add(to);
-      indegree.set(to, (indegree.get(to) ?? 0) + 1);
-    };
-
-    const collectRefs = (
-      ...inputs: Array<string[] | null | undefined>
-    ): string[] => {
-      const out: string[] = [];
-      for (const list of inputs) {
-        for (const raw of list ?? []) {
-          const cleaned = (raw ?? '').toString().replace(/^\\+/, '').trim();
-          if (!cleaned) {
-            continue;
-          }
-          out.push(cleaned);
-        }
-      }
-      return out;
-    };
-
-    for (const id of modIds) {
-      ensureNode(id);
-    }
-
-    for (const mod of mods) {
-      const id = (mod.modId ?? '').trim();
-      if (!id) {
-        continue;
-      }
-
-      const deps = collectRefs(mod.dependencies, mod.requires);
-      const loadAfter = collectRefs(mod.loadAfter);
-      const loadBefore = collectRefs(mod.loadBefore);
-
-      for (const dep of [...deps, ...loadAfter]) {
-        if (!selected.has(dep)) {
-          missingModIds.add(dep);
-          continue;
-        }
-        addEdge(dep, id);
-      }
-
-      for (const dep of loadBefore) {
-        if (!selected.has(dep)) {
-          missingModIds.add(dep);
-          continue;
-        }
-        addEdge(id, dep);
-      }
-    }
-
-    const originalOrder = new Map<string, number>();
-    modIds.forEach((id, index) => {
-      originalOrder.set(id, index);
-    });
-
-    const ready = Array.from(indegree.entries())
-      .filter(([, count]) => count === 0)
-      .map(([id]) => id)
-      .sort((a, b) => (originalOrder.get(a) ?? 0) - (originalOrder.get(b) ?? 0));
-
-    const ordered: string[] = [];
-    const queue = ready.slice();
-
-    while (queue.length) {
-      const id = queue.shift() as string;
-      ordered.push(id);
-      const next = edges.get(id);
-      if (!next) {
-        continue;
-      }
-      for (const to of next) {
-        indegree.set(to, (indegree.get(to) ?? 0) - 1);
-        if ((indegree.get(to) ?? 0) === 0) {
-          queue.push(to);
-        }
-      }
-      queue.sort(
-        (a, b) => (originalOrder.get(a) ?? 0) - (originalOrder.get(b) ?? 0),
-      );
-    }
-
-    const leftover = modIds.filter((id) => !ordered.includes(id));
-    if (leftover.length) {
-      ordered.push(...leftover);
-    }
-
-    const incompatiblePairs: Array<{ a: string; b: string }> = [];
-    const incompatibleKeys = new Set<string>();
-    for (const mod of mods) {
-      const id = (mod.modId ?? '').trim();
-      if (!id) {
-        continue;
-      }
-      for (const raw of mod.incompatible ?? []) {
-        const other = (raw ?? '').toString().replace(/^\\+/, '').trim();
-        if (!other || !selected.has(other)) {
-          continue;
-        }
-        const a = id.toLowerCase();
-        const b = other.toLowerCase();
-        const key = a < b ? `${a}|${b}` : `${b}|${a}`;
-        if (incompatibleKeys.has(key)) {
-          continue;
-        }
-        incompatibleKeys.add(key);
-        incompatiblePairs.push({ a: id, b: other });
-      }
-    }
-
-    const missingWorkshopIds: string[] = [];
-    for (const mod of mods) {
-      const id = (mod.modId ?? '').trim();
-      if (!id) {
-        continue;
-      }
-      const workshopId = (mod.workshopId ?? '').trim();
-      if (!workshopId) {
-        missingWorkshopIds.push(id);
-      }
-    }
-
-    const cycles = leftover.length ? [leftover] : [];
-
-    return {
-      orderedModIds: ordered,
-      missingModIds: Array.from(missingModIds).sort((a, b) => a.localeCompare(b)),
-      missingWorkshopIds,
-      cycles,
-      incompatiblePairs,
-      conflicts: [],
-      warnings: [],
-    };
-  }
-
   private mergeOrderedModIds(ordered: string[], original: string[]): string[] {
     const seen = new Set<string>();
     const out: string[] = [];
@@ -1413,10 +1277,22 @@ export class LoadoutsPageComponent implements OnInit {
     this.analysis = analysis;
     if (!analysis) {
       this.analysisCycleDisplay = [];
+      this.analysisMissingDisplay = [];
       this.analysisConflictDisplay = [];
       return;
     }
 
+    const providersById = new Map(
+      (analysis.missingDependencies ?? []).map((dep) => [
+        dep.modId,
+        dep.workshopIds,
+      ]),
+    );
+    this.analysisMissingDisplay = analysis.missingModIds.map((id) => {
+      const workshopIds = providersById.get(id) ?? [];
+      return workshopIds.length ? `${id} (workshop ${workshopIds.join(', ')})` : id;
+    });
+
     this.analysisCycleDisplay = analysis.cycles.map((cycle) =>
       cycle.join(' -> '),
     );
@@ -6,6 +6,7 @@ import type {
   LoadoutApplyPlan,
   LoadoutResolvedMod,
 } from '../models/loadout.models';
+import type { ModSummary } from '../models/mod.models';
 
 @Injectable({ providedIn: 'root' })
 export class LoadoutsService {
@@ -223,9 +224,27 @@ export class LoadoutsService {
     );
   }
 
-  async analyzeLoadout(mods: LoadoutResolvedMod[]): Promise<LoadoutAnalysis> {
+  async analyzeLoadout(
+    mods: LoadoutResolvedMod[],
+    installed: ModSummary[],
+  ): Promise<LoadoutAnalysis> {
+    // Only the fields used to find providers and version verdicts are sent.
+    const installedRefs = installed.map((mod) => ({
+      id: mod.id,
+      name: mod.name,
+      mod_id: mod.mod_id ?? null,
+      workshop_id:
+        (mod.workshop_id ?? null) ||
+        (mod.workshop?.fileid != null ? String(mod.workshop.fileid) : null),
+      version_min: mod.version_min ?? null,
+      version_max: mod.version_max ?? null,
+      compatibility: mod.compatibility ?? null,
+    }));
     return profileAsync('invoke.analyze_mod_loadout', () =>
-      invoke<LoadoutAnalysis>('analyze_mod_loadout', { mods }),
+      invoke<LoadoutAnalysis>('analyze_mod_loadout', {
+        mods,
+        installed: installedRefs,
+      }),
     );
   }
 
@@ -234,6 +253,7 @@ export class LoadoutsService {
     presetName: string,
     modIds: string[],
     workshopIds: string[],
+    mods?: LoadoutResolvedMod[],
   ): Promise<LoadoutApplyPlan> {
     return profileAsync('invoke.plan_server_preset', () =>
       invoke<LoadoutApplyPlan>('plan_server_preset', {
@@ -241,6 +261,7 @@ export class LoadoutsService {
         presetName,
         modIds,
         workshopIds,
+        mods: mods ?? null,
       }),
     );
   }
@@ -250,13 +271,15 @@ export class LoadoutsService {
     presetName: string,
     modIds: string[],
     workshopIds: string[],
-  ): Promise<void> {
+    mods?: LoadoutResolvedMod[],
+  ): Promise<string[]> {
     return profileAsync('invoke.write_server_preset', () =>
-      invoke<void>('write_server_preset', {
+      invoke<string[]>('write_server_preset', {
         zomboidUserDir,
         presetName,
         modIds,
         workshopIds,
+        mods: mods ?? null,
       }),
     );
   }